@@ -1,4 +1,4 @@
-// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// Copyright 2013-2014 The Rust Project Developers. See the COPYRIGHT
 // file at the top-level directory of this distribution and at
 // http://rust-lang.org/COPYRIGHT.
 //
@@ -7,587 +7,1549 @@
 // <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-//
-// btree.rs
-//
 
-//! Starting implementation of a btree for rust.
-//! Structure inspired by github user davidhalperin's gist.
+//! An ordered map and set implemented as B-trees. The only requirement for
+//! the types is that the key implements `TotalOrd`.
+//!
+//! Each node of a B-tree holds several keys in sorted order, so a tree holds
+//! far fewer nodes than a binary tree of the same size, and lookups touch far
+//! fewer cache lines. `BTreeMap` and `BTreeSet` offer the same interface as
+//! `TreeMap` and `TreeSet`, and are generally the better choice for large
+//! collections.
+
+use std::cmp;
+use std::util::replace;
+use std::vec;
+
+// This is a textbook B-tree in which every node other than the root holds
+// between MIN_KEYS and MAX_KEYS keys, and every internal node with n keys has
+// n + 1 children. Insertion and removal work in a single pass down from the
+// root: insertion splits any full node it passes through, and removal makes
+// sure that any node it descends into has a key to spare, so that neither
+// ever has to walk back up the tree to rebalance it.
+
+/// The minimum degree of the tree
+static B: uint = 6;
+static MIN_KEYS: uint = B - 1;
+static MAX_KEYS: uint = 2 * B - 1;
 
-#[allow(dead_code)];
-#[allow(unused_variable)];
-
-///A B-tree contains a root node (which contains a vector of elements),
-///a length (the height of the tree), and lower and upper bounds on the
-///number of elements that a given node can contain.
 #[allow(missing_doc)]
-pub struct BTree<K, V> {
-    root: Node<K, V>,
-    len: uint,
-    lower_bound: uint,
-    upper_bound: uint
+#[deriving(Clone)]
+pub struct BTreeMap<K, V> {
+    priv root: ~Node<K, V>,
+    priv length: uint
 }
 
-//We would probably want to remove the dependence on the Clone trait in the future.
-//It is here as a crutch to ensure values can be passed around through the tree's nodes
-//especially during insertions and deletions.
-//Using the swap or replace methods is one option for replacing dependence on Clone, or
-//changing the way in which the BTree is stored could also potentially work.
-impl<K: TotalOrd, V> BTree<K, V> {
-
-    ///Returns new BTree with root node (leaf) and user-supplied lower bound
-    pub fn new(k: K, v: V, lb: uint) -> BTree<K, V> {
-        BTree {
-            root: Node::new_leaf(~[LeafElt::new(k, v)]),
-            len: 1,
-            lower_bound: lb,
-            upper_bound: 2 * lb
-        }
+impl<K: Eq + TotalOrd, V: Eq> Eq for BTreeMap<K, V> {
+    fn eq(&self, other: &BTreeMap<K, V>) -> bool {
+        self.len() == other.len() &&
+            self.iter().zip(other.iter()).all(|(a, b)| a == b)
     }
+}
 
-    ///Helper function for clone: returns new BTree with supplied root node,
-    ///length, and lower bound.  For use when the length is known already.
-    pub fn new_with_node_len(n: Node<K, V>,
-                             length: uint,
-                             lb: uint) -> BTree<K, V> {
-        BTree {
-            root: n,
-            len: length,
-            lower_bound: lb,
-            upper_bound: 2 * lb
-        }
+// Lexicographical comparison
+fn lt<K: Ord + TotalOrd, V: Ord>(a: &BTreeMap<K, V>,
+                                 b: &BTreeMap<K, V>) -> bool {
+    // the Zip iterator is as long as the shortest of a and b.
+    for ((key_a, value_a), (key_b, value_b)) in a.iter().zip(b.iter()) {
+        if *key_a < *key_b { return true; }
+        if *key_a > *key_b { return false; }
+        if *value_a < *value_b { return true; }
+        if *value_a > *value_b { return false; }
     }
 
+    a.len() < b.len()
+}
 
-    ///Stub for add method in progress.
-    pub fn add(self, k: K, v: V) -> BTree<K, V> {
-        //replace(&self.root,self.root.add(k, v));
-        return BTree::new(k, v, 2);
-    }
+impl<K: Ord + TotalOrd, V: Ord> Ord for BTreeMap<K, V> {
+    #[inline]
+    fn lt(&self, other: &BTreeMap<K, V>) -> bool { lt(self, other) }
+    #[inline]
+    fn le(&self, other: &BTreeMap<K, V>) -> bool { !lt(other, self) }
+    #[inline]
+    fn ge(&self, other: &BTreeMap<K, V>) -> bool { !lt(self, other) }
+    #[inline]
+    fn gt(&self, other: &BTreeMap<K, V>) -> bool { lt(other, self) }
 }
 
-impl<K: TotalOrd, V: Clone> BTree<K, V> {
+impl<K: TotalOrd, V> Container for BTreeMap<K, V> {
+    /// Return the number of elements in the map
+    fn len(&self) -> uint { self.length }
 
-    ///Returns the value of a given key, which may not exist in the tree.
-    ///Calls the root node's get method.
-    pub fn get(self, k: K) -> Option<V> {
-        return self.root.get(k);
-    }
+    /// Return true if the map contains no elements
+    fn is_empty(&self) -> bool { self.length == 0 }
 }
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for BTree<K, V> {
-    ///Implements the Clone trait for the BTree.
-    ///Uses a helper function/constructor to produce a new BTree.
-    fn clone(&self) -> BTree<K, V> {
-        BTree::new_with_node_len(self.root.clone(), self.len, self.lower_bound)
+impl<K: TotalOrd, V> Mutable for BTreeMap<K, V> {
+    /// Clear the map, removing all key-value pairs.
+    fn clear(&mut self) {
+        self.root = ~Node::new();
+        self.length = 0
     }
 }
 
-
-impl<K: TotalOrd, V: TotalEq> TotalEq for BTree<K, V> {
-    ///Testing equality on BTrees by comparing the root.
-    fn equals(&self, other: &BTree<K, V>) -> bool {
-        self.root.cmp(&other.root) == Equal
+impl<K: TotalOrd, V> Map<K, V> for BTreeMap<K, V> {
+    /// Return a reference to the value corresponding to the key
+    fn find<'a>(&'a self, key: &K) -> Option<&'a V> {
+        let mut current: &'a Node<K, V> = &*self.root;
+        loop {
+            match current.search(key) {
+                Found(i) => return Some(&current.vals[i]),
+                GoDown(_) if current.is_leaf() => return None,
+                GoDown(i) => current = &*current.edges[i],
+            }
+        }
     }
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for BTree<K, V> {
-    ///Returns an ordering based on the root nodes of each BTree.
-    fn cmp(&self, other: &BTree<K, V>) -> Ordering {
-        self.root.cmp(&other.root)
+impl<K: TotalOrd, V> MutableMap<K, V> for BTreeMap<K, V> {
+    /// Return a mutable reference to the value corresponding to the key
+    #[inline]
+    fn find_mut<'a>(&'a mut self, key: &K) -> Option<&'a mut V> {
+        find_mut(&mut *self.root, key)
     }
-}
 
-impl<K: ToStr + TotalOrd, V: ToStr> ToStr for BTree<K, V> {
-    ///Returns a string representation of the BTree
-    fn to_str(&self) -> ~str {
-        let ret = self.root.to_str();
+    /// Insert a key-value pair into the map. If the key already had a value
+    /// present in the map, that value is returned. Otherwise None is returned.
+    fn swap(&mut self, key: K, value: V) -> Option<V> {
+        if self.root.is_full() {
+            // Grow the tree by one level, splitting the old root in two
+            let old = replace(&mut self.root, ~Node::new());
+            self.root.edges.push(old);
+            self.root.split_child(0);
+        }
+        let ret = self.root.insert(key, value);
+        if ret.is_none() { self.length += 1 }
+        ret
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    fn pop(&mut self, key: &K) -> Option<V> {
+        let ret = self.root.remove(key);
+        // Merging the last two children of the root leaves it without any
+        // keys, in which case the tree shrinks by one level
+        if self.root.keys.is_empty() && !self.root.is_leaf() {
+            let child = self.root.edges.pop();
+            self.root = child;
+        }
+        if ret.is_some() { self.length -= 1 }
         ret
     }
 }
 
+impl<K: TotalOrd, V> BTreeMap<K, V> {
+    /// Create an empty BTreeMap
+    pub fn new() -> BTreeMap<K, V> {
+        BTreeMap { root: ~Node::new(), length: 0 }
+    }
 
-//Node types
-//A node is either a LeafNode or a BranchNode, which contain either a Leaf or a Branch.
-//Branches contain BranchElts, which contain a left child (another node) and a key-value
-//pair.  Branches also contain the rightmost child of the elements in the array.
-//Leaves contain LeafElts, which do not have children.
-enum Node<K, V> {
-    LeafNode(Leaf<K, V>),
-    BranchNode(Branch<K, V>)
-}
+    /// Get a lazy iterator over the key-value pairs in the map.
+    /// Requires that it be frozen (immutable).
+    pub fn iter<'a>(&'a self) -> Entries<'a, K, V> {
+        let mut iter = Entries {
+            stack: ~[],
+            remaining_min: self.length,
+            remaining_max: self.length
+        };
+        iter.descend(&*self.root, true);
+        iter
+    }
 
+    /// Get a lazy reverse iterator over the key-value pairs in the map.
+    /// Requires that it be frozen (immutable).
+    pub fn rev_iter<'a>(&'a self) -> RevEntries<'a, K, V> {
+        let mut iter = Entries {
+            stack: ~[],
+            remaining_min: self.length,
+            remaining_max: self.length
+        };
+        iter.descend(&*self.root, false);
+        RevEntries { iter: iter }
+    }
 
-//Node functions/methods
-impl<K: TotalOrd, V> Node<K, V> {
+    /// Get a lazy forward iterator over the key-value pairs in the
+    /// map, with the values being mutable.
+    pub fn mut_iter<'a>(&'a mut self) -> MutEntries<'a, K, V> {
+        let mut iter = MutEntries {
+            stack: ~[],
+            remaining_min: self.length,
+            remaining_max: self.length
+        };
+        iter.descend(&mut *self.root, true);
+        iter
+    }
 
-    ///Differentiates between leaf and branch nodes.
-    fn is_leaf(&self) -> bool {
-        match self{
-            &LeafNode(..) => true,
-            &BranchNode(..) => false
-        }
+    /// Get a lazy reverse iterator over the key-value pairs in the
+    /// map, with the values being mutable.
+    pub fn mut_rev_iter<'a>(&'a mut self) -> RevMutEntries<'a, K, V> {
+        let mut iter = MutEntries {
+            stack: ~[],
+            remaining_min: self.length,
+            remaining_max: self.length
+        };
+        iter.descend(&mut *self.root, false);
+        RevMutEntries { iter: iter }
     }
 
-    ///Creates a new leaf node given a vector of elements.
-    fn new_leaf(vec: ~[LeafElt<K, V>]) -> Node<K,V> {
-        LeafNode(Leaf::new(vec))
+    /// Get a lazy iterator that consumes the map.
+    pub fn move_iter(self) -> MoveEntries<K, V> {
+        let BTreeMap { root: ~root, length: length } = self;
+        let mut iter = MoveEntries { stack: ~[], remaining: length };
+        iter.descend(root);
+        iter
     }
 
+    /// Return a lazy iterator to the first key-value pair whose key is not less than `k`
+    /// If all keys in map are less than `k` an empty iterator is returned.
+    pub fn lower_bound<'a>(&'a self, k: &K) -> Entries<'a, K, V> {
+        let mut iter = Entries {
+            stack: ~[],
+            remaining_min: 0,
+            remaining_max: self.length
+        };
+        iter.seek(&*self.root, k, true);
+        iter
+    }
 
-    ///Creates a new branch node given a vector of an elements and a pointer to a rightmost child.
-    fn new_branch(vec: ~[BranchElt<K, V>], right: ~Node<K, V>) -> Node<K, V> {
-        BranchNode(Branch::new(vec, right))
+    /// Return a lazy iterator to the first key-value pair whose key is greater than `k`
+    /// If all keys in map are not greater than `k` an empty iterator is returned.
+    pub fn upper_bound<'a>(&'a self, k: &K) -> Entries<'a, K, V> {
+        let mut iter = Entries {
+            stack: ~[],
+            remaining_min: 0,
+            remaining_max: self.length
+        };
+        iter.seek(&*self.root, k, false);
+        iter
     }
 
-    ///A placeholder/stub for add
-    ///Currently returns a leaf node with a single value (the added one)
-    fn add(self, k: K, v: V) -> Node<K, V> {
-        return Node::new_leaf(~[LeafElt::new(k, v)]);
+    /// Return a lazy value iterator to the first key-value pair (with
+    /// the value being mutable) whose key is not less than `k`.
+    ///
+    /// If all keys in map are less than `k` an empty iterator is
+    /// returned.
+    pub fn mut_lower_bound<'a>(&'a mut self, k: &K) -> MutEntries<'a, K, V> {
+        let mut iter = MutEntries {
+            stack: ~[],
+            remaining_min: 0,
+            remaining_max: self.length
+        };
+        iter.seek(&mut *self.root, k, true);
+        iter
     }
-}
 
-impl<K: TotalOrd, V: Clone> Node<K, V> {
-    ///Returns the corresponding value to the provided key.
-    ///get() is called in different ways on a branch or a leaf.
-    fn get(&self, k: K) -> Option<V> {
-        match *self {
-            LeafNode(ref leaf) => return leaf.get(k),
-            BranchNode(ref branch) => return branch.get(k)
-        }
+    /// Return a lazy iterator to the first key-value pair (with the
+    /// value being mutable) whose key is greater than `k`.
+    ///
+    /// If all keys in map are not greater than `k` an empty iterator
+    /// is returned.
+    pub fn mut_upper_bound<'a>(&'a mut self, k: &K) -> MutEntries<'a, K, V> {
+        let mut iter = MutEntries {
+            stack: ~[],
+            remaining_min: 0,
+            remaining_max: self.length
+        };
+        iter.seek(&mut *self.root, k, false);
+        iter
     }
 }
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for Node<K, V> {
-    ///Returns a new node based on whether or not it is a branch or a leaf.
-    fn clone(&self) -> Node<K, V> {
-        match *self {
-            LeafNode(ref leaf) => {
-                Node::new_leaf(leaf.elts.clone())
-            }
-            BranchNode(ref branch) => {
-                Node::new_branch(branch.elts.clone(),
-                                 branch.rightmost_child.clone())
-            }
-        }
-    }
+/// Lazy forward iterator over a map
+pub struct Entries<'a, K, V> {
+    priv stack: ~[Frame<'a, K, V>],
+    priv remaining_min: uint,
+    priv remaining_max: uint
+}
+
+/// Lazy backward iterator over a map
+pub struct RevEntries<'a, K, V> {
+    priv iter: Entries<'a, K, V>,
+}
+
+/// Lazy forward iterator over a map that allows for the mutation of
+/// the values.
+pub struct MutEntries<'a, K, V> {
+    priv stack: ~[MutFrame<'a, K, V>],
+    priv remaining_min: uint,
+    priv remaining_max: uint
+}
+
+/// Lazy backward iterator over a map
+pub struct RevMutEntries<'a, K, V> {
+    priv iter: MutEntries<'a, K, V>,
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalEq for Node<K, V> {
-    ///Returns whether two nodes are equal
-    fn equals(&self, other: &Node<K, V>) -> bool{
-        match *self{
-            BranchNode(ref branch) => {
-                match *other{
-                    BranchNode(ref branch2) => branch.cmp(branch2) == Equal,
-                    LeafNode(ref leaf) => false
+// The position of an iterator within one node of the tree: the keys and
+// values of the node which have yet to be visited, along with the edges to the
+// subtrees between them which have yet to be descended into. An iterator
+// holds one of these for each node on the path from the root to its current
+// position, and takes the edges from the same end as the keys, so iterating
+// backwards works in the same way as iterating forwards.
+struct Frame<'a, K, V> {
+    keys: vec::Items<'a, K>,
+    vals: vec::Items<'a, V>,
+    edges: vec::Items<'a, ~Node<K, V>>,
+}
+
+struct MutFrame<'a, K, V> {
+    keys: vec::Items<'a, K>,
+    vals: vec::MutItems<'a, V>,
+    edges: vec::MutItems<'a, ~Node<K, V>>,
+}
+
+// See the comment on `addr!` in treemap.rs: this forces the evaluation of a
+// `& <mutability> <operand>` token sequence as an expression.
+macro_rules! addr { ($e:expr) => { $e }}
+// putting an optional mut into type signatures
+macro_rules! item { ($i:item) => { $i }}
+
+macro_rules! define_iterator {
+    ($name:ident,
+     $rev_name:ident,
+     $frame:ident,
+
+     // the methods which slice and iterate over the values and edges of a
+     // node, with the appropriate mutability
+     slice_from = $slice_from:ident,
+     iter = $iter:ident,
+
+     // this is just an optional `mut`, but there's no support for 0-or-1
+     // repeats.
+     addr_mut = $($addr_mut:tt)*
+     ) => {
+        item!(impl<'a, K, V> $frame<'a, K, V> {
+            // The frame for `node`, from its key `start` onwards
+            fn new(node: &'a $($addr_mut)* Node<K, V>,
+                   start: uint) -> $frame<'a, K, V> {
+                let edge_start = cmp::min(start, node.edges.len());
+                $frame {
+                    keys: node.keys.slice_from(start).iter(),
+                    vals: node.vals.$slice_from(start).$iter(),
+                    edges: node.edges.$slice_from(edge_start).$iter(),
+                }
+            }
+        })
+
+        item!(impl<'a, K, V> $name<'a, K, V> {
+            // Pushes the frames for the path from `node` down to its first
+            // key (or its last key, if iterating backwards)
+            fn descend(&mut self, node: &'a $($addr_mut)* Node<K, V>,
+                       forward: bool) {
+                let mut frame = $frame::new(node, 0);
+                let edge = if forward {
+                    frame.edges.next()
+                } else {
+                    frame.edges.next_back()
+                };
+                self.stack.push(frame);
+                match edge {
+                    Some(edge) => {
+                        self.descend(addr!(& $($addr_mut)* **edge), forward)
+                    }
+                    None => {}
                 }
             }
 
-            LeafNode(ref leaf) => {
-                match *other{
-                    LeafNode(ref leaf2) => leaf.cmp(leaf2) == Equal,
-                    BranchNode(ref branch) => false
+            #[inline(always)]
+            fn next_(&mut self, forward: bool) -> Option<(&'a K, &'a $($addr_mut)* V)> {
+                while !self.stack.is_empty() {
+                    let next = {
+                        let frame = self.stack.mut_last();
+                        let key = if forward {
+                            frame.keys.next()
+                        } else {
+                            frame.keys.next_back()
+                        };
+                        match key {
+                            Some(key) => {
+                                let (val, edge) = if forward {
+                                    (frame.vals.next(), frame.edges.next())
+                                } else {
+                                    (frame.vals.next_back(), frame.edges.next_back())
+                                };
+                                Some((key, val.unwrap(), edge))
+                            }
+                            None => None
+                        }
+                    };
+                    match next {
+                        Some((key, val, edge)) => {
+                            // The subtree which follows the key is visited next
+                            match edge {
+                                Some(edge) => {
+                                    self.descend(addr!(& $($addr_mut)* **edge),
+                                                 forward)
+                                }
+                                None => {}
+                            }
+                            self.remaining_max -= 1;
+                            if self.remaining_min > 0 {
+                                self.remaining_min -= 1;
+                            }
+                            return Some((key, val));
+                        }
+                        // This node is done with, carry on with its parent
+                        None => { self.stack.pop(); }
+                    }
+                }
+                None
+            }
+        })
+
+        item!(impl<'a, K: TotalOrd, V> $name<'a, K, V> {
+            // Pushes the frames for the path from `node` down to the first key
+            // which is not less than `k` if `lower` is set, or which is
+            // greater than `k` otherwise
+            fn seek(&mut self, node: &'a $($addr_mut)* Node<K, V>, k: &K,
+                    lower: bool) {
+                let (i, found) = match node.search(k) {
+                    Found(i) if lower => (i, true),
+                    Found(i) => (i + 1, false),
+                    GoDown(i) => (i, false),
+                };
+                let mut frame = $frame::new(node, i);
+                // If `k` itself was found then the subtree before it holds
+                // smaller keys, and is skipped
+                let edge = frame.edges.next();
+                self.stack.push(frame);
+                if !found {
+                    match edge {
+                        Some(edge) => {
+                            self.seek(addr!(& $($addr_mut)* **edge), k, lower)
+                        }
+                        None => {}
+                    }
                 }
             }
+        })
+
+        // the forward Iterator impl.
+        item!(impl<'a, K, V> Iterator<(&'a K, &'a $($addr_mut)* V)> for $name<'a, K, V> {
+            /// Advance the iterator to the next entry (in order) and return a
+            /// tuple with a reference to the key and value. If there are no
+            /// more entries, return `None`.
+            fn next(&mut self) -> Option<(&'a K, &'a $($addr_mut)* V)> {
+                self.next_(true)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (uint, Option<uint>) {
+                (self.remaining_min, Some(self.remaining_max))
+            }
+        })
+
+        // the reverse Iterator impl.
+        item!(impl<'a, K, V> Iterator<(&'a K, &'a $($addr_mut)* V)> for $rev_name<'a, K, V> {
+            fn next(&mut self) -> Option<(&'a K, &'a $($addr_mut)* V)> {
+                self.iter.next_(false)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (uint, Option<uint>) {
+                self.iter.size_hint()
+            }
+        })
+    }
+} // end of define_iterator
+
+define_iterator! {
+    Entries,
+    RevEntries,
+    Frame,
+    slice_from = slice_from,
+    iter = iter,
+
+    // immutable, so no mut
+    addr_mut =
+}
+define_iterator! {
+    MutEntries,
+    RevMutEntries,
+    MutFrame,
+    slice_from = mut_slice_from,
+    iter = mut_iter,
+
+    addr_mut = mut
+}
+
+/// Lazy forward iterator over a map that consumes the map while iterating
+pub struct MoveEntries<K, V> {
+    priv stack: ~[MoveFrame<K, V>],
+    priv remaining: uint
+}
+
+// Like `Frame`, but owning the rest of the node
+struct MoveFrame<K, V> {
+    keys: vec::MoveItems<K>,
+    vals: vec::MoveItems<V>,
+    edges: vec::MoveItems<~Node<K, V>>,
+}
+
+impl<K, V> MoveEntries<K, V> {
+    // Pushes the frames for the path from `node` down to its first key
+    fn descend(&mut self, node: Node<K, V>) {
+        let Node { keys: keys, vals: vals, edges: edges } = node;
+        let mut frame = MoveFrame {
+            keys: keys.move_iter(),
+            vals: vals.move_iter(),
+            edges: edges.move_iter(),
+        };
+        let edge = frame.edges.next();
+        self.stack.push(frame);
+        match edge {
+            Some(~edge) => self.descend(edge),
+            None => {}
         }
     }
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for Node<K, V> {
-    ///Implementation of TotalOrd for Nodes.
-    fn cmp(&self, other: &Node<K, V>) -> Ordering {
-        match *self {
-            LeafNode(ref leaf) => {
-                match *other {
-                    LeafNode(ref leaf2) => leaf.cmp(leaf2),
-                    BranchNode(_) => Less
+impl<K, V> Iterator<(K, V)> for MoveEntries<K,V> {
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        while !self.stack.is_empty() {
+            let next = {
+                let frame = self.stack.mut_last();
+                match frame.keys.next() {
+                    Some(key) => {
+                        let val = frame.vals.next().unwrap();
+                        Some((key, val, frame.edges.next()))
+                    }
+                    None => None
                 }
-            }
-            BranchNode(ref branch) => {
-                match *other {
-                    BranchNode(ref branch2) => branch.cmp(branch2),
-                    LeafNode(_) => Greater
+            };
+            match next {
+                Some((key, val, edge)) => {
+                    match edge {
+                        Some(~edge) => self.descend(edge),
+                        None => {}
+                    }
+                    self.remaining -= 1;
+                    return Some((key, val));
                 }
+                None => { self.stack.pop(); }
             }
         }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
-impl<K: ToStr + TotalOrd, V: ToStr> ToStr for Node<K, V> {
-    ///Returns a string representation of a Node.
-    ///The Branch's to_str() is not implemented yet.
-    fn to_str(&self) -> ~str {
-        match *self {
-            LeafNode(ref leaf) => leaf.to_str(),
-            BranchNode(ref branch) => branch.to_str()
-        }
+impl<'a, T> Iterator<&'a T> for SetItems<'a, T> {
+    /// Advance the iterator to the next value (in order). If there are no more values, return `None`.
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next().map(|(value, _)| value)
+    }
+}
+
+impl<'a, T> Iterator<&'a T> for RevSetItems<'a, T> {
+    /// Advance the iterator to the next value (in order). If there are no more values, return `None`.
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next().map(|(value, _)| value)
     }
 }
 
+/// A implementation of the `Set` trait on top of the `BTreeMap` container. The
+/// only requirement is that the type of the elements contained ascribes to the
+/// `TotalOrd` trait.
+#[deriving(Clone)]
+pub struct BTreeSet<T> {
+    priv map: BTreeMap<T, ()>
+}
+
+impl<T: Eq + TotalOrd> Eq for BTreeSet<T> {
+    #[inline]
+    fn eq(&self, other: &BTreeSet<T>) -> bool { self.map == other.map }
+    #[inline]
+    fn ne(&self, other: &BTreeSet<T>) -> bool { self.map != other.map }
+}
 
-//A leaf is a vector with elements that contain no children.  A leaf also
-//does not contain a rightmost child.
-struct Leaf<K, V> {
-    elts: ~[LeafElt<K, V>]
+impl<T: Ord + TotalOrd> Ord for BTreeSet<T> {
+    #[inline]
+    fn lt(&self, other: &BTreeSet<T>) -> bool { self.map < other.map }
+    #[inline]
+    fn le(&self, other: &BTreeSet<T>) -> bool { self.map <= other.map }
+    #[inline]
+    fn ge(&self, other: &BTreeSet<T>) -> bool { self.map >= other.map }
+    #[inline]
+    fn gt(&self, other: &BTreeSet<T>) -> bool { self.map > other.map }
 }
 
-//Vector of values with children, plus a rightmost child (greater than all)
-struct Branch<K, V> {
-    elts: ~[BranchElt<K,V>],
-    rightmost_child: ~Node<K, V>
+impl<T: TotalOrd> Container for BTreeSet<T> {
+    /// Return the number of elements in the set
+    #[inline]
+    fn len(&self) -> uint { self.map.len() }
+
+    /// Return true if the set contains no elements
+    #[inline]
+    fn is_empty(&self) -> bool { self.map.is_empty() }
 }
 
+impl<T: TotalOrd> Mutable for BTreeSet<T> {
+    /// Clear the set, removing all values.
+    #[inline]
+    fn clear(&mut self) { self.map.clear() }
+}
 
-impl<K: TotalOrd, V> Leaf<K, V> {
-    ///Creates a new Leaf from a vector of LeafElts.
-    fn new(vec: ~[LeafElt<K, V>]) -> Leaf<K, V> {
-        Leaf {
-            elts: vec
+impl<T: TotalOrd> Set<T> for BTreeSet<T> {
+    /// Return true if the set contains a value
+    #[inline]
+    fn contains(&self, value: &T) -> bool {
+        self.map.contains_key(value)
+    }
+
+    /// Return true if the set has no elements in common with `other`.
+    /// This is equivalent to checking for an empty intersection.
+    fn is_disjoint(&self, other: &BTreeSet<T>) -> bool {
+        let mut x = self.iter();
+        let mut y = other.iter();
+        let mut a = x.next();
+        let mut b = y.next();
+        while a.is_some() && b.is_some() {
+            let a1 = a.unwrap();
+            let b1 = b.unwrap();
+
+            match a1.cmp(b1) {
+              Less => a = x.next(),
+              Greater => b = y.next(),
+              Equal => return false,
+            }
         }
+        true
     }
 
-    ///Placeholder for add method in progress.
-    ///Currently returns a new Leaf containing a single LeafElt.
-    fn add(&self, k: K, v: V) -> Node<K, V> {
-        return Node::new_leaf(~[LeafElt::new(k, v)]);
+    /// Return true if the set is a subset of another
+    #[inline]
+    fn is_subset(&self, other: &BTreeSet<T>) -> bool {
+        other.is_superset(self)
     }
 
-}
+    /// Return true if the set is a superset of another
+    fn is_superset(&self, other: &BTreeSet<T>) -> bool {
+        let mut x = self.iter();
+        let mut y = other.iter();
+        let mut a = x.next();
+        let mut b = y.next();
+        while b.is_some() {
+            if a.is_none() {
+                return false
+            }
 
-impl<K: TotalOrd, V: Clone> Leaf<K, V> {
-    ///Returns the corresponding value to the supplied key.
-    fn get(&self, k: K) -> Option<V> {
-        for s in self.elts.iter() {
-            let order = s.key.cmp(&k);
-            match order {
-                Equal => return Some(s.value.clone()),
-                _ => {}
+            let a1 = a.unwrap();
+            let b1 = b.unwrap();
+
+            match a1.cmp(b1) {
+              Less => (),
+              Greater => return false,
+              Equal => b = y.next(),
             }
+
+            a = x.next();
         }
-        return None;
+        true
     }
 }
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for Leaf<K, V> {
-    ///Returns a new Leaf with the same elts.
-    fn clone(&self) -> Leaf<K, V> {
-        Leaf::new(self.elts.clone())
-    }
+impl<T: TotalOrd> MutableSet<T> for BTreeSet<T> {
+    /// Add a value to the set. Return true if the value was not already
+    /// present in the set.
+    #[inline]
+    fn insert(&mut self, value: T) -> bool { self.map.insert(value, ()) }
+
+    /// Remove a value from the set. Return true if the value was
+    /// present in the set.
+    #[inline]
+    fn remove(&mut self, value: &T) -> bool { self.map.remove(value) }
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalEq for Leaf<K, V> {
-    ///Implementation of equals function for leaves that compares LeafElts.
-    fn equals(&self, other: &Leaf<K, V>) -> bool {
-        self.elts.equals(&other.elts)
+impl<T: TotalOrd> BTreeSet<T> {
+    /// Create an empty BTreeSet
+    #[inline]
+    pub fn new() -> BTreeSet<T> { BTreeSet{map: BTreeMap::new()} }
+
+    /// Get a lazy iterator over the values in the set.
+    /// Requires that it be frozen (immutable).
+    #[inline]
+    pub fn iter<'a>(&'a self) -> SetItems<'a, T> {
+        SetItems{iter: self.map.iter()}
+    }
+
+    /// Get a lazy iterator over the values in the set.
+    /// Requires that it be frozen (immutable).
+    #[inline]
+    pub fn rev_iter<'a>(&'a self) -> RevSetItems<'a, T> {
+        RevSetItems{iter: self.map.rev_iter()}
+    }
+
+    /// Get a lazy iterator pointing to the first value not less than `v` (greater or equal).
+    /// If all elements in the set are less than `v` empty iterator is returned.
+    #[inline]
+    pub fn lower_bound<'a>(&'a self, v: &T) -> SetItems<'a, T> {
+        SetItems{iter: self.map.lower_bound(v)}
+    }
+
+    /// Get a lazy iterator pointing to the first value greater than `v`.
+    /// If all elements in the set are not greater than `v` empty iterator is returned.
+    #[inline]
+    pub fn upper_bound<'a>(&'a self, v: &T) -> SetItems<'a, T> {
+        SetItems{iter: self.map.upper_bound(v)}
     }
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for Leaf<K, V> {
-    ///Returns an ordering based on the first element of each Leaf.
-    fn cmp(&self, other: &Leaf<K, V>) -> Ordering {
-        if self.elts.len() > other.elts.len() {
-            return Greater;
+/// Lazy forward iterator over a set
+pub struct SetItems<'a, T> {
+    priv iter: Entries<'a, T, ()>
+}
+
+/// Lazy backward iterator over a set
+pub struct RevSetItems<'a, T> {
+    priv iter: RevEntries<'a, T, ()>
+}
+
+// Nodes keep their keys and values in separate vectors, so that searching a
+// node only touches the keys
+#[deriving(Clone)]
+struct Node<K, V> {
+    keys: ~[K],
+    vals: ~[V],
+    // Empty for a leaf, and otherwise one longer than `keys`
+    edges: ~[~Node<K, V>],
+}
+
+// Where a key belongs within a node
+enum Search {
+    // The key is the node's key at this index
+    Found(uint),
+    // The key belongs in the subtree at this index, if anywhere
+    GoDown(uint),
+}
+
+impl<K, V> Node<K, V> {
+    fn new() -> Node<K, V> {
+        Node { keys: ~[], vals: ~[], edges: ~[] }
+    }
+
+    #[inline]
+    fn is_leaf(&self) -> bool { self.edges.is_empty() }
+
+    #[inline]
+    fn is_full(&self) -> bool { self.keys.len() == MAX_KEYS }
+
+    // Splits this node, which must be full, around its median key, returning
+    // the median along with a new node holding everything to its right
+    fn split(&mut self) -> (K, V, ~Node<K, V>) {
+        let keys = split_off(&mut self.keys, B);
+        let vals = split_off(&mut self.vals, B);
+        let edges = if self.is_leaf() {
+            ~[]
+        } else {
+            split_off(&mut self.edges, B)
+        };
+        let key = self.keys.pop();
+        let val = self.vals.pop();
+        (key, val, ~Node { keys: keys, vals: vals, edges: edges })
+    }
+
+    // Splits the full child at index `i` in two, moving its median up into
+    // this node, which must not be full
+    fn split_child(&mut self, i: uint) {
+        let (key, val, right) = self.edges[i].split();
+        self.keys.insert(i, key);
+        self.vals.insert(i, val);
+        self.edges.insert(i + 1, right);
+    }
+
+    // Merges the child at index `i + 1`, and the key which separates it from
+    // the one at `i`, into the child at `i`
+    fn merge_children(&mut self, i: uint) {
+        let key = self.keys.remove(i);
+        let val = self.vals.remove(i);
+        let ~Node {
+            keys: keys,
+            vals: vals,
+            edges: edges
+        } = self.edges.remove(i + 1);
+
+        let left = &mut self.edges[i];
+        left.keys.push(key);
+        left.keys.push_all_move(keys);
+        left.vals.push(val);
+        left.vals.push_all_move(vals);
+        left.edges.push_all_move(edges);
+    }
+
+    // Moves the last key of the child at index `i` up into this node, and the
+    // key which it replaces down to the start of the child at `i + 1`
+    fn rotate_right(&mut self, i: uint) {
+        let (key, val, edge) = {
+            let left = &mut self.edges[i];
+            let edge = if left.is_leaf() { None } else { Some(left.edges.pop()) };
+            (left.keys.pop(), left.vals.pop(), edge)
+        };
+        let key = replace(&mut self.keys[i], key);
+        let val = replace(&mut self.vals[i], val);
+
+        let right = &mut self.edges[i + 1];
+        right.keys.unshift(key);
+        right.vals.unshift(val);
+        match edge {
+            Some(edge) => right.edges.unshift(edge),
+            None => {}
         }
-        if self.elts.len() < other.elts.len() {
-            return Less;
+    }
+
+    // Moves the first key of the child at index `i + 1` up into this node, and
+    // the key which it replaces down to the end of the child at `i`
+    fn rotate_left(&mut self, i: uint) {
+        let (key, val, edge) = {
+            let right = &mut self.edges[i + 1];
+            let edge = if right.is_leaf() { None } else { Some(right.edges.shift()) };
+            (right.keys.shift(), right.vals.shift(), edge)
+        };
+        let key = replace(&mut self.keys[i], key);
+        let val = replace(&mut self.vals[i], val);
+
+        let left = &mut self.edges[i];
+        left.keys.push(key);
+        left.vals.push(val);
+        match edge {
+            Some(edge) => left.edges.push(edge),
+            None => {}
         }
-        self.elts[0].cmp(&other.elts[0])
     }
-}
 
+    // Makes sure that the child at index `i` has a key to spare, by taking
+    // one from a sibling or else merging it with one, so that a key can be
+    // removed from beneath it. Returns the index of the child afterwards.
+    fn fill_child(&mut self, i: uint) -> uint {
+        let last = self.edges.len() - 1;
+        if self.edges[i].keys.len() > MIN_KEYS {
+            i
+        } else if i > 0 && self.edges[i - 1].keys.len() > MIN_KEYS {
+            self.rotate_right(i - 1);
+            i
+        } else if i < last && self.edges[i + 1].keys.len() > MIN_KEYS {
+            self.rotate_left(i);
+            i
+        } else if i < last {
+            self.merge_children(i);
+            i
+        } else {
+            self.merge_children(i - 1);
+            i - 1
+        }
+    }
 
-impl<K: ToStr + TotalOrd, V: ToStr> ToStr for Leaf<K, V> {
-    ///Returns a string representation of a Leaf.
-    fn to_str(&self) -> ~str {
-        self.elts.iter().map(|s| s.to_str()).to_owned_vec().connect(" // ")
+    // Removes the smallest key in this subtree
+    fn pop_min(&mut self) -> (K, V) {
+        if self.is_leaf() {
+            (self.keys.shift(), self.vals.shift())
+        } else {
+            let i = self.fill_child(0);
+            self.edges[i].pop_min()
+        }
     }
-}
 
+    // Removes the largest key in this subtree
+    fn pop_max(&mut self) -> (K, V) {
+        if self.is_leaf() {
+            (self.keys.pop(), self.vals.pop())
+        } else {
+            let last = self.edges.len() - 1;
+            let i = self.fill_child(last);
+            self.edges[i].pop_max()
+        }
+    }
+}
 
-impl<K: TotalOrd, V> Branch<K, V> {
-    ///Creates a new Branch from a vector of BranchElts and a rightmost child (a node).
-    fn new(vec: ~[BranchElt<K, V>], right: ~Node<K, V>) -> Branch<K, V> {
-        Branch {
-            elts: vec,
-            rightmost_child: right
+impl<K: TotalOrd, V> Node<K, V> {
+    // Finds where `key` belongs among the keys of this node. Nodes are small
+    // enough that a linear scan beats a binary search.
+    fn search(&self, key: &K) -> Search {
+        for (i, k) in self.keys.iter().enumerate() {
+            match key.cmp(k) {
+                Less => return GoDown(i),
+                Equal => return Found(i),
+                Greater => {}
+            }
         }
+        GoDown(self.keys.len())
     }
 
-    ///Placeholder for add method in progress
-    fn add(&self, k: K, v: V) -> Node<K, V> {
-        return Node::new_leaf(~[LeafElt::new(k, v)]);
+    // Inserts a key-value pair into this subtree, whose root must not be full
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.search(&key) {
+            Found(i) => Some(replace(&mut self.vals[i], value)),
+            GoDown(i) if self.is_leaf() => {
+                self.keys.insert(i, key);
+                self.vals.insert(i, value);
+                None
+            }
+            GoDown(i) => {
+                let mut i = i;
+                if self.edges[i].is_full() {
+                    self.split_child(i);
+                    match key.cmp(&self.keys[i]) {
+                        Less => {}
+                        Equal => return Some(replace(&mut self.vals[i], value)),
+                        Greater => i += 1,
+                    }
+                }
+                self.edges[i].insert(key, value)
+            }
+        }
     }
-}
 
-impl<K: TotalOrd, V: Clone> Branch<K, V> {
-    ///Returns the corresponding value to the supplied key.
-    ///If the key is not there, find the child that might hold it.
-    fn get(&self, k: K) -> Option<V> {
-        for s in self.elts.iter() {
-            let order = s.key.cmp(&k);
-            match order {
-                Less => return s.left.get(k),
-                Equal => return Some(s.value.clone()),
-                _ => {}
+    // Removes a key from this subtree, whose root must have a key to spare
+    // unless it's the root of the whole tree
+    fn remove(&mut self, key: &K) -> Option<V> {
+        match self.search(key) {
+            Found(i) if self.is_leaf() => {
+                self.keys.remove(i);
+                Some(self.vals.remove(i))
+            }
+            Found(i) => {
+                // Replace the key with its predecessor or successor, taken
+                // from whichever neighbouring child can spare one, or failing
+                // that merge the two children and remove it from the result
+                if self.edges[i].keys.len() > MIN_KEYS {
+                    let (k, v) = self.edges[i].pop_max();
+                    self.keys[i] = k;
+                    Some(replace(&mut self.vals[i], v))
+                } else if self.edges[i + 1].keys.len() > MIN_KEYS {
+                    let (k, v) = self.edges[i + 1].pop_min();
+                    self.keys[i] = k;
+                    Some(replace(&mut self.vals[i], v))
+                } else {
+                    self.merge_children(i);
+                    self.edges[i].remove(key)
+                }
+            }
+            GoDown(_) if self.is_leaf() => None,
+            GoDown(i) => {
+                let i = self.fill_child(i);
+                self.edges[i].remove(key)
             }
         }
-        self.rightmost_child.get(k)
     }
 }
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for Branch<K, V> {
-    ///Returns a new branch using the clone methods of the Branch's internal variables.
-    fn clone(&self) -> Branch<K, V> {
-        Branch::new(self.elts.clone(), self.rightmost_child.clone())
+fn find_mut<'r, K: TotalOrd, V>(node: &'r mut Node<K, V>,
+                                key: &K)
+                             -> Option<&'r mut V> {
+    match node.search(key) {
+        Found(i) => Some(&mut node.vals[i]),
+        GoDown(_) if node.is_leaf() => None,
+        GoDown(i) => find_mut(&mut *node.edges[i], key),
     }
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalEq for Branch<K, V> {
-    ///Equals function for Branches--compares all the elements in each branch
-    fn equals(&self, other: &Branch<K, V>) -> bool {
-        self.elts.equals(&other.elts)
+// Removes the elements of `v` from index `at` onwards, returning them
+fn split_off<T>(v: &mut ~[T], at: uint) -> ~[T] {
+    let mut tail = vec::with_capacity(v.len() - at);
+    while v.len() > at {
+        tail.push(v.pop());
     }
+    tail.reverse();
+    tail
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for Branch<K, V> {
-    ///Compares the first elements of two branches to determine an ordering
-    fn cmp(&self, other: &Branch<K, V>) -> Ordering {
-        if self.elts.len() > other.elts.len() {
-            return Greater;
-        }
-        if self.elts.len() < other.elts.len() {
-            return Less;
-        }
-        self.elts[0].cmp(&other.elts[0])
+impl<K: TotalOrd, V> FromIterator<(K, V)> for BTreeMap<K, V> {
+    fn from_iterator<T: Iterator<(K, V)>>(iter: &mut T) -> BTreeMap<K, V> {
+        let mut map = BTreeMap::new();
+        map.extend(iter);
+        map
     }
 }
 
-impl<K: ToStr + TotalOrd, V: ToStr> ToStr for Branch<K, V> {
-    ///Returns a string representation of a Branch.
-    fn to_str(&self) -> ~str {
-        let mut ret = self.elts.iter().map(|s| s.to_str()).to_owned_vec().connect(" // ");
-        ret.push_str(" // ");
-        ret.push_str(self.rightmost_child.to_str());
-        ret
+impl<K: TotalOrd, V> Extendable<(K, V)> for BTreeMap<K, V> {
+    #[inline]
+    fn extend<T: Iterator<(K, V)>>(&mut self, iter: &mut T) {
+        for (k, v) in *iter {
+            self.insert(k, v);
+        }
     }
 }
 
-//A LeafElt containts no left child, but a key-value pair.
-struct LeafElt<K, V> {
-    key: K,
-    value: V
+impl<T: TotalOrd> FromIterator<T> for BTreeSet<T> {
+    fn from_iterator<Iter: Iterator<T>>(iter: &mut Iter) -> BTreeSet<T> {
+        let mut set = BTreeSet::new();
+        set.extend(iter);
+        set
+    }
 }
 
-//A BranchElt has a left child in addition to a key-value pair.
-struct BranchElt<K, V> {
-    left: Node<K, V>,
-    key: K,
-    value: V
+impl<T: TotalOrd> Extendable<T> for BTreeSet<T> {
+    #[inline]
+    fn extend<Iter: Iterator<T>>(&mut self, iter: &mut Iter) {
+        for elem in *iter {
+            self.insert(elem);
+        }
+    }
 }
 
-impl<K: TotalOrd, V> LeafElt<K, V> {
-    ///Creates a new LeafElt from a supplied key-value pair.
-    fn new(k: K, v: V) -> LeafElt<K, V> {
-        LeafElt {
-            key: k,
-            value: v
+#[cfg(test)]
+mod test_btree {
+
+    use super::{BTreeMap, Node, MIN_KEYS, MAX_KEYS};
+
+    use std::rand::Rng;
+    use std::rand;
+
+    #[test]
+    fn find_empty() {
+        let m: BTreeMap<int,int> = BTreeMap::new();
+        assert!(m.find(&5) == None);
+    }
+
+    #[test]
+    fn find_not_found() {
+        let mut m = BTreeMap::new();
+        assert!(m.insert(1, 2));
+        assert!(m.insert(5, 3));
+        assert!(m.insert(9, 3));
+        assert_eq!(m.find(&2), None);
+    }
+
+    #[test]
+    fn test_find_mut() {
+        let mut m = BTreeMap::new();
+        for i in range(0, 100) {
+            assert!(m.insert(i, i));
+        }
+        match m.find_mut(&55) {
+          None => fail!(), Some(x) => *x = 100
         }
+        assert_eq!(m.find(&55), Some(&100));
+        assert_eq!(m.find_mut(&100), None);
     }
 
-    ///Compares another LeafElt against itself and determines whether
-    ///the original LeafElt's key is less than the other one's key.
-    fn less_than(&self, other: LeafElt<K, V>) -> bool {
-        let order = self.key.cmp(&other.key);
-        match order {
-            Less => true,
-            _ => false
+    #[test]
+    fn insert_replace() {
+        let mut m = BTreeMap::new();
+        assert!(m.insert(5, 2));
+        assert!(m.insert(2, 9));
+        assert!(!m.insert(2, 11));
+        assert_eq!(m.find(&2).unwrap(), &11);
+        assert_eq!(m.swap(5, 3), Some(2));
+        assert_eq!(m.len(), 2);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut m = BTreeMap::new();
+        m.clear();
+        for i in range(0, 100) {
+            assert!(m.insert(i, -i));
         }
+        m.clear();
+        assert!(m.find(&5).is_none());
+        assert!(m.find(&50).is_none());
+        assert!(m.is_empty());
+        assert!(m.iter().next().is_none());
     }
 
-    ///Compares another LeafElt against itself and determines whether
-    ///the original LeafElt's key is greater than the other one's key.
-    fn greater_than(&self, other: LeafElt<K, V>) -> bool {
-        let order = self.key.cmp(&other.key);
-        match order {
-            Greater => true,
-            _ => false
+    fn check_equal<K: Eq + TotalOrd, V: Eq>(ctrl: &[(K, V)],
+                                            map: &BTreeMap<K, V>) {
+        assert_eq!(ctrl.len(), map.len());
+        assert_eq!(ctrl.is_empty(), map.is_empty());
+        for x in ctrl.iter() {
+            let &(ref k, ref v) = x;
+            assert!(map.find(k).unwrap() == v)
+        }
+        for (map_k, map_v) in map.iter() {
+            let mut found = false;
+            for x in ctrl.iter() {
+                let &(ref ctrl_k, ref ctrl_v) = x;
+                if *map_k == *ctrl_k {
+                    assert!(*map_v == *ctrl_v);
+                    found = true;
+                    break;
+                }
+            }
+            assert!(found);
         }
     }
 
-    ///Takes a key and determines whether its own key and the supplied key
-    ///are the same.
-    fn has_key(&self, other: K) -> bool {
-        let order = self.key.cmp(&other);
-        match order {
-            Equal => true,
-            _ => false
+    // Checks the invariants of the subtree `node` at depth `depth`, returning
+    // the depth of its leaves
+    fn check_node<K: TotalOrd, V>(node: &Node<K, V>, is_root: bool,
+                                  min: Option<&K>, max: Option<&K>,
+                                  depth: uint) -> uint {
+        assert!(node.keys.len() <= MAX_KEYS);
+        assert!(is_root || node.keys.len() >= MIN_KEYS);
+        assert_eq!(node.keys.len(), node.vals.len());
+        for w in node.keys.windows(2) {
+            assert_eq!(w[0].cmp(&w[1]), Less);
+        }
+        for k in min.iter() {
+            assert_eq!(node.keys[0].cmp(*k), Greater);
+        }
+        for k in max.iter() {
+            assert_eq!(node.keys.last().cmp(*k), Less);
+        }
+
+        if node.is_leaf() {
+            return depth;
         }
+        assert_eq!(node.edges.len(), node.keys.len() + 1);
+        let mut leaves = None;
+        for (i, edge) in node.edges.iter().enumerate() {
+            let lo = if i == 0 { min } else { Some(&node.keys[i - 1]) };
+            let hi = if i == node.keys.len() { max } else { Some(&node.keys[i]) };
+            let d = check_node(&**edge, false, lo, hi, depth + 1);
+            // all of the leaves are at the same depth
+            assert!(leaves.is_none() || leaves == Some(d));
+            leaves = Some(d);
+        }
+        leaves.unwrap()
     }
-}
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for LeafElt<K, V> {
-    ///Returns a new LeafElt by cloning the key and value.
-    fn clone(&self) -> LeafElt<K, V> {
-        LeafElt::new(self.key.clone(), self.value.clone())
+    fn check_structure<K: TotalOrd, V>(map: &BTreeMap<K, V>) {
+        if map.is_empty() {
+            assert!(map.root.is_leaf() && map.root.keys.is_empty());
+        } else {
+            check_node(&*map.root, true, None, None, 0);
+        }
     }
-}
 
-impl<K: TotalOrd, V: TotalEq> TotalEq for LeafElt<K, V> {
-    ///TotalEq for LeafElts
-    fn equals(&self, other: &LeafElt<K, V>) -> bool {
-        self.key.equals(&other.key) && self.value.equals(&other.value)
+    #[test]
+    fn test_rand_int() {
+        let mut map: BTreeMap<int,int> = BTreeMap::new();
+        let mut ctrl = ~[];
+
+        check_equal(ctrl, &map);
+        assert!(map.find(&5).is_none());
+
+        let mut rng: rand::IsaacRng = rand::SeedableRng::from_seed(&[42]);
+
+        5.times(|| {
+            300.times(|| {
+                let k = rng.gen_range(0, 1000);
+                let v = rng.gen();
+                if !ctrl.iter().any(|&(key, _)| key == k) {
+                    assert!(map.insert(k, v));
+                    ctrl.push((k, v));
+                    check_structure(&map);
+                    check_equal(ctrl, &map);
+                }
+            });
+
+            200.times(|| {
+                let r = rng.gen_range(0, ctrl.len());
+                let (key, value) = ctrl.remove(r);
+                assert_eq!(map.pop(&key), Some(value));
+                assert!(map.pop(&key).is_none());
+                check_structure(&map);
+                check_equal(ctrl, &map);
+            });
+        })
     }
-}
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for LeafElt<K, V> {
-    ///Returns an ordering based on the keys of the LeafElts.
-    fn cmp(&self, other: &LeafElt<K, V>) -> Ordering {
-        self.key.cmp(&other.key)
+    #[test]
+    fn test_remove_all() {
+        let mut m = BTreeMap::new();
+        for i in range(0, 1000) {
+            assert!(m.insert(i, i * 2));
+        }
+        check_structure(&m);
+        for i in range(0, 1000) {
+            let i = if i % 2 == 0 { i / 2 } else { 999 - i / 2 };
+            assert_eq!(m.pop(&i), Some(i * 2));
+            check_structure(&m);
+        }
+        assert!(m.is_empty());
+        assert!(m.iter().next().is_none());
     }
-}
 
-impl<K: ToStr + TotalOrd, V: ToStr> ToStr for LeafElt<K, V> {
-    ///Returns a string representation of a LeafElt.
-    fn to_str(&self) -> ~str {
-        format!("Key: {}, value: {};",
-            self.key.to_str(), self.value.to_str())
+    #[test]
+    fn test_len() {
+        let mut m = BTreeMap::new();
+        assert!(m.insert(3, 6));
+        assert_eq!(m.len(), 1);
+        assert!(m.insert(0, 0));
+        assert_eq!(m.len(), 2);
+        assert!(m.insert(4, 8));
+        assert_eq!(m.len(), 3);
+        assert!(m.remove(&3));
+        assert_eq!(m.len(), 2);
+        assert!(!m.remove(&5));
+        assert_eq!(m.len(), 2);
+        assert!(m.insert(2, 4));
+        assert_eq!(m.len(), 3);
+        assert!(m.insert(1, 2));
+        assert_eq!(m.len(), 4);
     }
-}
 
-impl<K: TotalOrd, V> BranchElt<K, V> {
-    ///Creates a new BranchElt from a supplied key, value, and left child.
-    fn new(k: K, v: V, n: Node<K, V>) -> BranchElt<K, V> {
-        BranchElt {
-            left: n,
-            key: k,
-            value: v
+    #[test]
+    fn test_iterator() {
+        let mut m = BTreeMap::new();
+        for i in range(0, 200).invert() {
+            assert!(m.insert(i, i * 2));
+        }
+
+        let mut n = 0;
+        for (k, v) in m.iter() {
+            assert_eq!(*k, n);
+            assert_eq!(*v, n * 2);
+            n += 1;
         }
+        assert_eq!(n, 200);
+        assert_eq!(m.iter().size_hint(), (200, Some(200)));
     }
 
-    ///Placeholder for add method in progress.
-    ///Overall implementation will determine the actual return value of this method.
-    fn add(&self, k: K, v: V) -> LeafElt<K, V> {
-        return LeafElt::new(k, v);
+    #[test]
+    fn test_rev_iter() {
+        let mut m = BTreeMap::new();
+        for i in range(0, 200) {
+            assert!(m.insert(i, i * 2));
+        }
+
+        let mut n = 200;
+        for (k, v) in m.rev_iter() {
+            n -= 1;
+            assert_eq!(*k, n);
+            assert_eq!(*v, n * 2);
+        }
+        assert_eq!(n, 0);
     }
-}
 
+    #[test]
+    fn test_mut_iter() {
+        let mut m = BTreeMap::new();
+        for i in range(0u, 200) {
+            assert!(m.insert(i, 100 * i));
+        }
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for BranchElt<K, V> {
-    ///Returns a new BranchElt by cloning the key, value, and left child.
-    fn clone(&self) -> BranchElt<K, V> {
-        BranchElt::new(self.key.clone(),
-                       self.value.clone(),
-                       self.left.clone())
+        for (i, (&k, v)) in m.mut_iter().enumerate() {
+            assert_eq!(k, i);
+            *v -= k;
+        }
+        for (i, (&k, v)) in m.mut_rev_iter().enumerate() {
+            assert_eq!(k, 199 - i);
+            *v += 1;
+        }
+
+        assert!(m.iter().all(|(&k, &v)| v == 99 * k + 1));
     }
-}
 
-impl<K: TotalOrd, V: TotalEq> TotalEq for BranchElt<K, V>{
-    ///TotalEq for BranchElts
-    fn equals(&self, other: &BranchElt<K, V>) -> bool {
-        self.key.equals(&other.key)&&self.value.equals(&other.value)
+    #[test]
+    fn test_move_iter() {
+        let mut m = BTreeMap::new();
+        for i in range(0u, 200) {
+            assert!(m.insert(i, ~(i * 3)));
+        }
+
+        let mut n = 0;
+        for (k, v) in m.move_iter() {
+            assert_eq!(k, n);
+            assert_eq!(v, ~(n * 3));
+            n += 1;
+        }
+        assert_eq!(n, 200);
     }
-}
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for BranchElt<K, V> {
-    ///Fulfills TotalOrd for BranchElts
-    fn cmp(&self, other: &BranchElt<K, V>) -> Ordering {
-        self.key.cmp(&other.key)
+    #[test]
+    fn test_interval_iteration() {
+        let mut m = BTreeMap::new();
+        for i in range(1, 100) {
+            assert!(m.insert(i * 2, i * 4));
+        }
+
+        for i in range(1, 198) {
+            let mut lb_it = m.lower_bound(&i);
+            let (&k, &v) = lb_it.next().unwrap();
+            let lb = i + i % 2;
+            assert_eq!(lb, k);
+            assert_eq!(lb * 2, v);
+
+            let mut ub_it = m.upper_bound(&i);
+            let (&k, &v) = ub_it.next().unwrap();
+            let ub = i + 2 - i % 2;
+            assert_eq!(ub, k);
+            assert_eq!(ub * 2, v);
+
+            // the rest of the map follows in order
+            assert_eq!(lb_it.count(|_| true), ((198 - lb) / 2) as uint);
+            assert_eq!(ub_it.count(|_| true), ((198 - ub) / 2) as uint);
+        }
+        let mut end_it = m.lower_bound(&199);
+        assert_eq!(end_it.next(), None);
+        let mut end_it = m.upper_bound(&198);
+        assert_eq!(end_it.next(), None);
     }
-}
 
-impl<K: ToStr + TotalOrd, V: ToStr> ToStr for BranchElt<K, V> {
-    ///Returns string containing key, value, and child (which should recur to a leaf)
-    ///Consider changing in future to be more readable.
-    fn to_str(&self) -> ~str {
-        format!("Key: {}, value: {}, child: {};",
-            self.key.to_str(), self.value.to_str(), self.left.to_str())
+    #[test]
+    fn test_mut_interval_iter() {
+        let mut m = BTreeMap::new();
+        for i in range(1, 100) {
+            assert!(m.insert(i * 2, i * 4));
+        }
+
+        for i in range(1, 199) {
+            for (&k, v) in m.mut_lower_bound(&i).take(1) {
+                assert_eq!(k, i + i % 2);
+                *v += 1;
+            }
+        }
+        for i in range(0, 198) {
+            for (&k, v) in m.mut_upper_bound(&i).take(1) {
+                assert_eq!(k, i + 2 - i % 2);
+                *v -= 1;
+            }
+        }
+        assert!(m.iter().all(|(&k, &v)| v == k * 2));
+    }
+
+    #[test]
+    fn test_eq() {
+        let mut a = BTreeMap::new();
+        let mut b = BTreeMap::new();
+
+        assert!(a == b);
+        assert!(a.insert(0, 5));
+        assert!(a != b);
+        assert!(b.insert(0, 4));
+        assert!(a != b);
+        assert!(a.insert(5, 19));
+        assert!(a != b);
+        assert!(!b.insert(0, 5));
+        assert!(a != b);
+        assert!(b.insert(5, 19));
+        assert!(a == b);
+    }
+
+    #[test]
+    fn test_lt() {
+        let mut a = BTreeMap::new();
+        let mut b = BTreeMap::new();
+
+        assert!(!(a < b) && !(b < a));
+        assert!(b.insert(0, 5));
+        assert!(a < b);
+        assert!(a.insert(0, 7));
+        assert!(!(a < b) && b < a);
+        assert!(b.insert(-2, 0));
+        assert!(b < a);
+        assert!(a.insert(-5, 2));
+        assert!(a < b);
+        assert!(a.insert(6, 2));
+        assert!(a < b && !(b < a));
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut a = BTreeMap::new();
+        for i in range(0, 100) {
+            assert!(a.insert(i, i));
+        }
+        let mut b = a.clone();
+        assert!(a == b);
+        assert!(b.remove(&50));
+        assert!(a != b);
+        assert_eq!(a.find(&50), Some(&50));
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let xs = ~[(1, 1), (2, 2), (3, 3), (4, 4), (5, 5), (6, 6)];
+
+        let map: BTreeMap<int, int> = xs.iter().map(|&x| x).collect();
+
+        for &(k, v) in xs.iter() {
+            assert_eq!(map.find(&k), Some(&v));
+        }
     }
 }
 
 #[cfg(test)]
-mod test_btree {
+mod bench {
 
-    use super::{BTree, LeafElt};
+    use super::*;
+    use test::BenchHarness;
+    use container::bench::*;
 
-    //Tests the functionality of the add methods (which are unfinished).
-    /*#[test]
-    fn add_test(){
-        let b = BTree::new(1, ~"abc", 2);
-        let is_add = b.add(2, ~"xyz");
-        assert!(is_add);
-    }*/
+    // Find seq
+    #[bench]
+    pub fn insert_rand_100(bh: &mut BenchHarness) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        insert_rand_n(100, &mut m, bh);
+    }
 
-    //Tests the functionality of the get method.
-    #[test]
-    fn get_test() {
-        let b = BTree::new(1, ~"abc", 2);
-        let val = b.get(1);
-        assert_eq!(val, Some(~"abc"));
+    #[bench]
+    pub fn insert_rand_10_000(bh: &mut BenchHarness) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        insert_rand_n(10_000, &mut m, bh);
     }
 
-    //Tests the LeafElt's less_than() method.
-    #[test]
-    fn leaf_lt() {
-        let l1 = LeafElt::new(1, ~"abc");
-        let l2 = LeafElt::new(2, ~"xyz");
-        assert!(l1.less_than(l2));
+    // Insert seq
+    #[bench]
+    pub fn insert_seq_100(bh: &mut BenchHarness) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        insert_seq_n(100, &mut m, bh);
     }
 
+    #[bench]
+    pub fn insert_seq_10_000(bh: &mut BenchHarness) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        insert_seq_n(10_000, &mut m, bh);
+    }
 
-    //Tests the LeafElt's greater_than() method.
-    #[test]
-    fn leaf_gt() {
-        let l1 = LeafElt::new(1, ~"abc");
-        let l2 = LeafElt::new(2, ~"xyz");
-        assert!(l2.greater_than(l1));
+    // Find rand
+    #[bench]
+    pub fn find_rand_100(bh: &mut BenchHarness) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        find_rand_n(100, &mut m, bh);
+    }
+
+    #[bench]
+    pub fn find_rand_10_000(bh: &mut BenchHarness) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        find_rand_n(10_000, &mut m, bh);
+    }
+
+    // Find seq
+    #[bench]
+    pub fn find_seq_100(bh: &mut BenchHarness) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        find_seq_n(100, &mut m, bh);
+    }
+
+    #[bench]
+    pub fn find_seq_10_000(bh: &mut BenchHarness) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        find_seq_n(10_000, &mut m, bh);
     }
+}
+
+#[cfg(test)]
+mod test_set {
+
+    use super::*;
 
-    //Tests the LeafElt's has_key() method.
     #[test]
-    fn leaf_hk() {
-        let l1 = LeafElt::new(1, ~"abc");
-        assert!(l1.has_key(1));
+    fn test_clear() {
+        let mut s = BTreeSet::new();
+        s.clear();
+        assert!(s.insert(5));
+        assert!(s.insert(12));
+        assert!(s.insert(19));
+        s.clear();
+        assert!(!s.contains(&5));
+        assert!(!s.contains(&12));
+        assert!(!s.contains(&19));
+        assert!(s.is_empty());
     }
 
-    //Tests the BTree's clone() method.
     #[test]
-    fn btree_clone_test() {
-        let b = BTree::new(1, ~"abc", 2);
-        let b2 = b.clone();
-        assert!(b.root.equals(&b2.root))
+    fn test_disjoint() {
+        let mut xs = BTreeSet::new();
+        let mut ys = BTreeSet::new();
+        assert!(xs.is_disjoint(&ys));
+        assert!(ys.is_disjoint(&xs));
+        assert!(xs.insert(5));
+        assert!(ys.insert(11));
+        assert!(xs.is_disjoint(&ys));
+        assert!(ys.is_disjoint(&xs));
+        assert!(xs.insert(7));
+        assert!(xs.insert(19));
+        assert!(xs.insert(4));
+        assert!(ys.insert(2));
+        assert!(ys.insert(-11));
+        assert!(xs.is_disjoint(&ys));
+        assert!(ys.is_disjoint(&xs));
+        assert!(ys.insert(7));
+        assert!(!xs.is_disjoint(&ys));
+        assert!(!ys.is_disjoint(&xs));
     }
 
-    //Tests the BTree's cmp() method when one node is "less than" another.
     #[test]
-    fn btree_cmp_test_less() {
-        let b = BTree::new(1, ~"abc", 2);
-        let b2 = BTree::new(2, ~"bcd", 2);
-        assert!(&b.cmp(&b2) == &Less)
+    fn test_subset_and_superset() {
+        let mut a = BTreeSet::new();
+        assert!(a.insert(0));
+        assert!(a.insert(5));
+        assert!(a.insert(11));
+        assert!(a.insert(7));
+
+        let mut b = BTreeSet::new();
+        assert!(b.insert(0));
+        assert!(b.insert(7));
+        assert!(b.insert(19));
+        assert!(b.insert(250));
+        assert!(b.insert(11));
+        assert!(b.insert(200));
+
+        assert!(!a.is_subset(&b));
+        assert!(!a.is_superset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(!b.is_superset(&a));
+
+        assert!(b.insert(5));
+
+        assert!(a.is_subset(&b));
+        assert!(!a.is_superset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(b.is_superset(&a));
     }
 
-    //Tests the BTree's cmp() method when two nodes are equal.
     #[test]
-    fn btree_cmp_test_eq() {
-        let b = BTree::new(1, ~"abc", 2);
-        let b2 = BTree::new(1, ~"bcd", 2);
-        assert!(&b.cmp(&b2) == &Equal)
+    fn test_iterator() {
+        let mut m = BTreeSet::new();
+
+        assert!(m.insert(3));
+        assert!(m.insert(0));
+        assert!(m.insert(4));
+        assert!(m.insert(2));
+        assert!(m.insert(1));
+
+        let mut n = 0;
+        for x in m.iter() {
+            assert_eq!(*x, n);
+            n += 1
+        }
+        assert_eq!(n, 5);
     }
 
-    //Tests the BTree's cmp() method when one node is "greater than" another.
     #[test]
-    fn btree_cmp_test_greater() {
-        let b = BTree::new(1, ~"abc", 2);
-        let b2 = BTree::new(2, ~"bcd", 2);
-        assert!(&b2.cmp(&b) == &Greater)
+    fn test_rev_iter() {
+        let mut m = BTreeSet::new();
+
+        assert!(m.insert(3));
+        assert!(m.insert(0));
+        assert!(m.insert(4));
+        assert!(m.insert(2));
+        assert!(m.insert(1));
+
+        let mut n = 4;
+        for x in m.rev_iter() {
+            assert_eq!(*x, n);
+            n -= 1;
+        }
     }
 
-    //Tests the BTree's to_str() method.
     #[test]
-    fn btree_tostr_test() {
-        let b = BTree::new(1, ~"abc", 2);
-        assert_eq!(b.to_str(), ~"Key: 1, value: abc;")
+    fn test_bounds() {
+        let set: BTreeSet<int> = range(0, 100).map(|x| x * 3).collect();
+        assert_eq!(set.lower_bound(&30).next(), Some(&30));
+        assert_eq!(set.upper_bound(&30).next(), Some(&33));
+        assert_eq!(set.lower_bound(&31).next(), Some(&33));
+        assert_eq!(set.upper_bound(&297).next(), None);
     }
 
-}
+    #[test]
+    fn test_from_iter() {
+        let xs = ~[1, 2, 3, 4, 5, 6, 7, 8, 9];
+
+        let set: BTreeSet<int> = xs.iter().map(|&x| x).collect();
 
+        for x in xs.iter() {
+            assert!(set.contains(x));
+        }
+    }
+}
@@ -0,0 +1,850 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * A work-stealing fork-join pool
+ *
+ * A `ForkJoinPool` runs closures on a fixed number of worker tasks. Code
+ * running in the pool is handed a `Scope`, through which it can `join` two
+ * closures, potentially running them in parallel, or `map`, `reduce` or
+ * `for_each` over a vector slice in parallel. These all return once the work
+ * which they forked is finished, so the closures may borrow anything from the
+ * stack of their caller, including the data of the caller of
+ * `ForkJoinPool::scope`.
+ *
+ * Each worker keeps the jobs which it forks on a `std::sync::deque`, from
+ * which idle workers steal them. A job which isn't stolen is simply run by the
+ * worker which forked it once it's done with its own half of the work, so
+ * forking is cheap, and work is split up into as many jobs as is convenient.
+ *
+ * `ForkJoinPool::scope` is unsafe, because nothing checks what the closures
+ * which it runs capture. The closure passed to it and those passed to its
+ * `Scope` may be run on any task in the pool, and those passed to `map`,
+ * `reduce` and `for_each` may be run on several tasks at once, so they mustn't
+ * capture managed boxes, mutate anything which they share, or otherwise share
+ * anything which isn't safe to share between tasks. A `Scope` can only be
+ * obtained inside of `scope`, so its methods are safe to call.
+ *
+ * If a closure fails, the failure is propagated to the task which joins it
+ * and from there all the way back out of `ForkJoinPool::scope`. Other work
+ * which is running in the pool at the time isn't affected.
+ *
+ * # Example
+ *
+ * ```rust
+ * use extra::fork_join::{ForkJoinPool, Scope};
+ *
+ * fn fib(s: &Scope, n: uint) -> uint {
+ *     if n < 2 { return n }
+ *     let (a, b) = s.join(|s| fib(s, n - 1), |s| fib(s, n - 2));
+ *     a + b
+ * }
+ *
+ * let pool = ForkJoinPool::new(4);
+ * let xs = range(0u, 1000).collect::<~[uint]>();
+ * unsafe {
+ *     assert_eq!(pool.scope(|s| fib(s, 20)), 6765);
+ *
+ *     let sum = pool.scope(|s| {
+ *         let squares = s.map(xs, |&x| x * x);
+ *         s.reduce(squares, |a, b| a + b)
+ *     });
+ *     assert_eq!(sum, Some(332833500));
+ * }
+ * ```
+ */
+
+use std::cast;
+use std::local_data;
+use std::sync::arc::UnsafeArc;
+use std::sync::atomics::{AtomicBool, AtomicUint, SeqCst};
+use std::sync::deque::{BufferPool, Worker, Stealer, Data, Empty, Abort};
+use std::task;
+use std::uint;
+use std::util;
+use std::vec;
+
+use sync::Mutex;
+
+/// A pool of worker tasks which run fork-join computations. The workers are
+/// shut down when the pool is dropped.
+pub struct ForkJoinPool {
+    priv shared: UnsafeArc<Shared>,
+    // Disconnected once all of the workers have exited
+    priv exited: Port<()>,
+}
+
+/// The handle through which a closure running in a `ForkJoinPool` forks more
+/// work. A scope can only be used by the task to which it was handed, so the
+/// closures passed to `map`, `reduce` and `for_each`, which may be run on
+/// other tasks, mustn't use the scope which they were passed to.
+#[no_send]
+pub struct Scope {
+    priv shared: *mut Shared,
+    priv index: uint,
+}
+
+// The pool (as a `*mut Shared`) and index of the worker which the current task
+// is running as, if any
+local_data_key!(CURRENT_WORKER: (uint, uint))
+
+// A job which can be run by any worker in the pool. The job's data lives on
+// the stack of the task which created it, which waits for it to finish.
+struct Job {
+    data: uint,
+    run: fn(uint, &Scope),
+    // The number of the job which the worker that forked this one was running
+    // at the time, see `WorkerState`
+    forked_in: uint,
+}
+
+struct Shared {
+    workers: ~[WorkerState],
+    // How many workers are asleep, or are about to go to sleep
+    sleeping: AtomicUint,
+
+    // Protects the fields below, and is waited on by sleeping workers
+    lock: Mutex,
+    // Jobs which were started from outside of the pool
+    injected: ~[Job],
+    // Bumped every time that sleeping workers are woken up
+    epoch: uint,
+    quit: bool,
+}
+
+// The state of one worker, which is only touched by the task running it
+struct WorkerState {
+    deque: Worker<Job>,
+    // One for each worker in the pool, including this one
+    stealers: ~[Stealer<Job>],
+    // The worker numbers the jobs which it runs in the order in which it
+    // starts them. This is how many it's started, and the number of the
+    // innermost one which it's still running.
+    started: uint,
+    running: uint,
+}
+
+impl ForkJoinPool {
+    /// Spawns a new pool with `n_workers` worker tasks.
+    pub fn new(n_workers: uint) -> ForkJoinPool {
+        assert!(n_workers >= 1);
+
+        let mut buffers = BufferPool::new();
+        let mut deques = ~[];
+        let mut stealers = ~[];
+        n_workers.times(|| {
+            let (deque, stealer) = buffers.deque();
+            deques.push(deque);
+            stealers.push(stealer);
+        });
+        let workers = deques.move_iter().map(|deque| {
+            WorkerState {
+                deque: deque,
+                stealers: stealers.clone(),
+                started: 0,
+                running: 0,
+            }
+        }).collect();
+
+        let shared = UnsafeArc::new(Shared {
+            workers: workers,
+            sleeping: AtomicUint::new(0),
+            lock: Mutex::new(),
+            injected: ~[],
+            epoch: 0,
+            quit: false,
+        });
+        let (exited, chan) = SharedChan::new();
+        for index in range(0, n_workers) {
+            let shared = shared.clone();
+            let chan = chan.clone();
+            task::spawn(proc() {
+                supervise(shared, index);
+                drop(chan);
+            });
+        }
+        ForkJoinPool { shared: shared, exited: exited }
+    }
+
+    /// Runs `f` in the pool, blocking until it and all of the work which it
+    /// forks have finished, and returns its result.
+    ///
+    /// If this is called by a closure which is already running in this pool
+    /// then `f` is simply run on the current worker. Calling it from inside a
+    /// different pool blocks the calling worker until `f` is done, which can
+    /// deadlock if the two pools end up waiting on each other.
+    ///
+    /// # Failure
+    ///
+    /// This function fails if `f` or anything which it forks fails.
+    ///
+    /// # Safety
+    ///
+    /// This is the only way into the pool, and nothing checks what `f`, or
+    /// the closures which are passed to its `Scope`, capture. They're run on
+    /// other tasks, possibly several at once, so they must only share what
+    /// is safe to share between tasks. See the module documentation.
+    pub unsafe fn scope<R: Send>(&self, f: |&Scope| -> R) -> R {
+        // Waiting for another worker to pick up `f` could deadlock if all of
+        // the workers did the same
+        let current = local_data::get(CURRENT_WORKER, |w| w.map(|&w| w));
+        match current {
+            Some((shared, index)) if shared == self.shared.get() as uint => {
+                return f(&Scope { shared: shared as *mut Shared, index: index });
+            }
+            _ => {}
+        }
+
+        let (port, chan) = Chan::new();
+        let mut job = RootJob {
+            f: cast::transmute(&f),
+            result: None,
+            chan: Some(chan),
+        };
+        let data = cast::transmute(&mut job);
+        (*self.shared.get()).inject(Job {
+            data: data,
+            run: run_root::<R>,
+            forked_in: 0,
+        });
+
+        match port.recv_opt() {
+            Some(()) => job.result.take_unwrap(),
+            None => fail!("a job failed in a ForkJoinPool"),
+        }
+    }
+}
+
+impl Drop for ForkJoinPool {
+    fn drop(&mut self) {
+        unsafe {
+            let shared = self.shared.get();
+            (*shared).lock.lock_cond(|cond| {
+                (*shared).quit = true;
+                (*shared).epoch += 1;
+                cond.broadcast();
+            });
+        }
+        self.exited.recv_opt();
+    }
+}
+
+impl Scope {
+    /// Runs `a` and `b`, potentially in parallel, returning both of their
+    /// results once they have finished.
+    ///
+    /// `b` is made available for other workers to steal before `a` is run on
+    /// the current task. If no other worker has stolen `b` by the time that
+    /// `a` returns, then it's run on the current task as well.
+    ///
+    /// # Failure
+    ///
+    /// This function fails if either `a` or `b` fails, but not before both
+    /// have stopped running.
+    pub fn join<A, B: Send>(&self, a: |&Scope| -> A,
+                            b: |&Scope| -> B) -> (A, B) {
+        let mut job = StackJob {
+            f: unsafe { cast::transmute(&b) },
+            result: None,
+            failed: false,
+            thief: AtomicUint::new(NO_THIEF),
+            first: 0,
+            last: 0,
+            done: AtomicBool::new(false),
+        };
+        let data = unsafe { cast::transmute(&mut job) };
+        self.state().deque.push(Job {
+            data: data,
+            run: run_stolen::<B>,
+            forked_in: self.state().running,
+        });
+        unsafe { (*self.shared).notify(); }
+
+        let mut guard = JoinGuard {
+            scope: self,
+            data: data,
+            done: &job.done as *AtomicBool,
+            armed: true,
+        };
+        let ra = a(self);
+
+        let rb = match self.state().deque.pop() {
+            Some(popped) => {
+                // Nobody stole `b`, and anything forked by `a` has been
+                // joined already, so it's still on top of the deque
+                assert!(popped.data == data);
+                guard.armed = false;
+                b(self)
+            }
+            None => {
+                self.wait_for(&job);
+                guard.armed = false;
+                if job.failed {
+                    fail!("a job forked by join failed");
+                }
+                job.result.take_unwrap()
+            }
+        };
+        (ra, rb)
+    }
+
+    /// Applies `f` to each element of `xs` in parallel, returning the
+    /// results in order.
+    pub fn map<T: Freeze + Send, U: Send>(&self, xs: &[T],
+                                          f: |&T| -> U) -> ~[U] {
+        let mut out = vec::from_fn(xs.len(), |_| None);
+        map_into(self, xs, out, self.grain(xs.len()), &f);
+        out.move_iter().map(|x| x.unwrap()).collect()
+    }
+
+    /// Combines the elements of `xs` with `f` in parallel, or returns `None`
+    /// if there aren't any. The elements are combined in an unspecified
+    /// grouping, but always in order, so `f` must be associative but needn't
+    /// be commutative.
+    pub fn reduce<T: Freeze + Send + Clone>(&self, xs: &[T],
+                                            f: |T, T| -> T) -> Option<T> {
+        if xs.is_empty() {
+            None
+        } else {
+            Some(reduce(self, xs, self.grain(xs.len()), &f))
+        }
+    }
+
+    /// Calls `f` on each element of `xs` in parallel.
+    pub fn for_each<T: Freeze + Send>(&self, xs: &[T], f: |&T|) {
+        for_each(self, xs, self.grain(xs.len()), &f)
+    }
+
+    // The number of elements of a slice of `len` elements which are worth
+    // handing out to a worker at once. This aims for a few jobs per worker,
+    // so that work can be rebalanced if some elements are slower than others.
+    fn grain(&self, len: uint) -> uint {
+        let workers = unsafe { (*self.shared).workers.len() };
+        let grain = len / (4 * workers);
+        if grain == 0 { 1 } else { grain }
+    }
+
+    fn state<'a>(&'a self) -> &'a mut WorkerState {
+        unsafe { &mut (*self.shared).workers[self.index] }
+    }
+
+    fn run(&self, job: Job) {
+        let outer = {
+            let state = self.state();
+            state.started += 1;
+            util::replace(&mut state.running, state.started)
+        };
+        (job.run)(job.data, self);
+        self.state().running = outer;
+    }
+
+    // Looks for a job to run, first on this worker's own deque, then on the
+    // other workers' deques, and finally among the jobs started from outside
+    // of the pool.
+    fn find_work(&self) -> Option<Job> {
+        match self.state().deque.pop() {
+            Some(job) => return Some(job),
+            None => {}
+        }
+        let n = self.state().stealers.len();
+        for i in range(1, n) {
+            match self.steal_from((self.index + i) % n) {
+                Some(job) => return Some(job),
+                None => {}
+            }
+        }
+        unsafe {
+            let shared = self.shared;
+            (*shared).lock.lock(|| (*shared).injected.shift_opt())
+        }
+    }
+
+    fn steal_from(&self, victim: uint) -> Option<Job> {
+        loop {
+            match self.state().stealers[victim].steal() {
+                Data(job) => return Some(job),
+                Empty => return None,
+                // Lost a race with another stealer, try again
+                Abort => {}
+            }
+        }
+    }
+
+    // Waits for a job forked by this worker which has been stolen to finish.
+    // In the meantime this worker helps out the thief with work forked by the
+    // stolen job.
+    fn wait_for<R>(&self, job: &StackJob<R>) {
+        self.wait_until(&job.done, || self.steal_from_thief(job));
+    }
+
+    // Blocks until `done` is set, running any work which `find` turns up in
+    // the meantime, and sleeping while there isn't any.
+    fn wait_until(&self, done: &AtomicBool, find: || -> Option<Job>) {
+        let shared = self.shared;
+        while !done.load(SeqCst) {
+            match find() {
+                Some(work) => { self.run(work); continue }
+                None => {}
+            }
+
+            // The same protocol as a worker going to sleep in `run_worker`.
+            // Whoever sets `done` wakes up sleepers afterwards.
+            unsafe {
+                let epoch = (*shared).lock.lock(|| (*shared).epoch);
+                (*shared).sleeping.fetch_add(1, SeqCst);
+                let work = if done.load(SeqCst) { None } else { find() };
+                match work {
+                    Some(work) => {
+                        (*shared).sleeping.fetch_sub(1, SeqCst);
+                        self.run(work);
+                        continue
+                    }
+                    None => {}
+                }
+
+                (*shared).lock.lock_cond(|cond| {
+                    while (*shared).epoch == epoch && !done.load(SeqCst) {
+                        cond.wait();
+                    }
+                });
+                (*shared).sleeping.fetch_sub(1, SeqCst);
+            }
+        }
+    }
+
+    // Steals work forked by `job` from the worker which stole it. Any work
+    // which is stolen is run on top of this worker's own, so if it fails then
+    // so does everything further up this worker's stack, which is only right
+    // if it was forked by `job`.
+    fn steal_from_thief<R>(&self, job: &StackJob<R>) -> Option<Job> {
+        let thief = job.thief.load(SeqCst);
+        if thief == NO_THIEF { return None }
+        match self.steal_from(thief) {
+            Some(work) => {
+                if job.forked(&work) { return Some(work) }
+                // The thief has already moved on to unrelated work, so leave
+                // this to a worker which isn't in the middle of anything
+                unsafe { (*self.shared).inject(work); }
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+impl Shared {
+    // Makes a job available to whichever worker runs out of work first.
+    unsafe fn inject(&mut self, job: Job) {
+        let this = self as *mut Shared;
+        self.lock.lock(|| (*this).injected.push(job));
+        self.notify();
+    }
+
+    // Wakes up any sleeping workers after a job has been made available.
+    unsafe fn notify(&mut self) {
+        if self.sleeping.load(SeqCst) > 0 {
+            let this = self as *mut Shared;
+            self.lock.lock_cond(|cond| {
+                (*this).epoch += 1;
+                cond.broadcast();
+            });
+        }
+    }
+}
+
+// Runs the worker with the given index, restarting it in a new task whenever
+// a job which it runs fails, until the pool is shut down
+fn supervise(shared: UnsafeArc<Shared>, index: uint) {
+    loop {
+        let shared = shared.clone();
+        match task::try(proc() run_worker(shared, index)) {
+            Ok(()) => break,
+            Err(_) => {}
+        }
+    }
+}
+
+fn run_worker(shared: UnsafeArc<Shared>, index: uint) {
+    local_data::set(CURRENT_WORKER, (shared.get() as uint, index));
+    let scope = Scope { shared: shared.get(), index: index };
+    let shared = shared.get();
+    loop {
+        match scope.find_work() {
+            Some(job) => { scope.run(job); continue }
+            None => {}
+        }
+
+        // Nothing to do, so go to sleep. Anyone who makes a job available
+        // after this worker is counted as sleeping will wake it up, so have
+        // one last look for work which was made available before that.
+        unsafe {
+            let epoch = (*shared).lock.lock(|| (*shared).epoch);
+            (*shared).sleeping.fetch_add(1, SeqCst);
+            match scope.find_work() {
+                Some(job) => {
+                    (*shared).sleeping.fetch_sub(1, SeqCst);
+                    scope.run(job);
+                    continue
+                }
+                None => {}
+            }
+
+            let quit = (*shared).lock.lock_cond(|cond| {
+                while (*shared).epoch == epoch && !(*shared).quit {
+                    cond.wait();
+                }
+                (*shared).quit
+            });
+            (*shared).sleeping.fetch_sub(1, SeqCst);
+            if quit { break }
+        }
+    }
+}
+
+static NO_THIEF: uint = uint::max_value;
+
+// The half of a `join` which is made available to other workers. It lives on
+// the stack of the worker which forked it.
+struct StackJob<R> {
+    f: *(), // &|&Scope| -> R
+    result: Option<R>,
+    failed: bool,
+    // The index of the worker which stole the job, once it's started running
+    thief: AtomicUint,
+    // The range of the thief's numbers for the jobs which it runs as part of
+    // this one. `last` is set once the job is done.
+    first: uint,
+    last: uint,
+    done: AtomicBool,
+}
+
+impl<R> StackJob<R> {
+    // Whether `work`, which was stolen from this job's thief, was forked by
+    // this job. Everything which the thief forks while it's running this job
+    // is forked in one of the jobs which it numbers from `first` to `last`.
+    fn forked(&self, work: &Job) -> bool {
+        work.forked_in >= self.first &&
+            (!self.done.load(SeqCst) || work.forked_in <= self.last)
+    }
+}
+
+fn run_stolen<R: Send>(data: uint, scope: &Scope) {
+    unsafe {
+        let job: &mut StackJob<R> = cast::transmute(data);
+        job.first = scope.state().running;
+        job.thief.store(scope.index, SeqCst);
+        let f: &|&Scope| -> R = cast::transmute(job.f);
+
+        let mut finish = Finish {
+            scope: scope,
+            failed: &mut job.failed,
+            last: &mut job.last,
+            done: &mut job.done,
+            ok: false,
+        };
+        job.result = Some((*f)(scope));
+        finish.ok = true;
+    }
+}
+
+// Marks a stolen job as done once it's stopped running, and as failed if it
+// didn't return, then wakes up the worker which forked it in case it's asleep.
+// The job mustn't be touched after it's marked as done, as the worker which
+// forked it may have moved on.
+struct Finish<'a> {
+    scope: &'a Scope,
+    failed: &'a mut bool,
+    last: &'a mut uint,
+    done: &'a mut AtomicBool,
+    ok: bool,
+}
+
+#[unsafe_destructor]
+impl<'a> Drop for Finish<'a> {
+    fn drop(&mut self) {
+        if !self.ok { *self.failed = true; }
+        *self.last = self.scope.state().started;
+        self.done.store(true, SeqCst);
+        unsafe { (*self.scope.shared).notify(); }
+    }
+}
+
+// Makes sure that nothing can run a job forked by `join` once `join` has
+// returned, even if it's returning by failing. The job is taken back if it's
+// still on the deque, or else waited for.
+struct JoinGuard<'a> {
+    scope: &'a Scope,
+    data: uint,
+    done: *AtomicBool,
+    armed: bool,
+}
+
+#[unsafe_destructor]
+impl<'a> Drop for JoinGuard<'a> {
+    fn drop(&mut self) {
+        if !self.armed { return }
+        match self.scope.state().deque.pop() {
+            Some(job) => { assert!(job.data == self.data); }
+            None => unsafe { self.scope.wait_until(&*self.done, || None) }
+        }
+    }
+}
+
+// The closure passed to `ForkJoinPool::scope`, which lives on the stack of
+// the task which called it.
+struct RootJob<R> {
+    f: *(), // &|&Scope| -> R
+    result: Option<R>,
+    // Sent on once the job is done, and dropped without sending if it fails
+    chan: Option<Chan<()>>,
+}
+
+fn run_root<R: Send>(data: uint, scope: &Scope) {
+    unsafe {
+        let job: &mut RootJob<R> = cast::transmute(data);
+        let chan = job.chan.take_unwrap();
+        let f: &|&Scope| -> R = cast::transmute(job.f);
+        job.result = Some((*f)(scope));
+        chan.send(());
+    }
+}
+
+fn map_into<T: Freeze + Send, U: Send>(s: &Scope, xs: &[T],
+                                       out: &mut [Option<U>], grain: uint,
+                                       f: &|&T| -> U) {
+    if xs.len() <= grain {
+        for (x, slot) in xs.iter().zip(out.mut_iter()) {
+            *slot = Some((*f)(x));
+        }
+    } else {
+        let mid = xs.len() / 2;
+        let (left, right) = (xs.slice_to(mid), xs.slice_from(mid));
+        let (out_left, out_right) = out.mut_split_at(mid);
+        s.join(|s| map_into(s, left, out_left, grain, f),
+               |s| map_into(s, right, out_right, grain, f));
+    }
+}
+
+fn reduce<T: Freeze + Send + Clone>(s: &Scope, xs: &[T], grain: uint,
+                                    f: &|T, T| -> T) -> T {
+    if xs.len() <= grain {
+        let mut acc = xs[0].clone();
+        for x in xs.slice_from(1).iter() {
+            acc = (*f)(acc, x.clone());
+        }
+        acc
+    } else {
+        let mid = xs.len() / 2;
+        let (left, right) = (xs.slice_to(mid), xs.slice_from(mid));
+        let (a, b) = s.join(|s| reduce(s, left, grain, f),
+                            |s| reduce(s, right, grain, f));
+        (*f)(a, b)
+    }
+}
+
+fn for_each<T: Freeze + Send>(s: &Scope, xs: &[T], grain: uint, f: &|&T|) {
+    if xs.len() <= grain {
+        for x in xs.iter() {
+            (*f)(x);
+        }
+    } else {
+        let mid = xs.len() / 2;
+        let (left, right) = (xs.slice_to(mid), xs.slice_from(mid));
+        s.join(|s| for_each(s, left, grain, f),
+               |s| for_each(s, right, grain, f));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ForkJoinPool, Scope, StackJob, Job, NO_THIEF};
+
+    use std::io::timer;
+    use std::ptr;
+    use std::sync::arc::UnsafeArc;
+    use std::sync::atomics::{AtomicBool, AtomicUint, SeqCst};
+    use std::task;
+
+    fn fib(s: &Scope, n: uint) -> uint {
+        if n < 2 { return n }
+        let (a, b) = s.join(|s| fib(s, n - 1), |s| fib(s, n - 2));
+        a + b
+    }
+
+    #[test]
+    fn smoke() {
+        let pool = ForkJoinPool::new(4);
+        unsafe {
+            assert_eq!(pool.scope(|s| s.join(|_| 1, |_| ~"two")), (1, ~"two"));
+            assert_eq!(pool.scope(|_| 3), 3);
+        }
+    }
+
+    #[test]
+    fn nested_join() {
+        let pool = ForkJoinPool::new(4);
+        assert_eq!(unsafe { pool.scope(|s| fib(s, 25)) }, 75025);
+    }
+
+    #[test]
+    fn one_worker() {
+        let pool = ForkJoinPool::new(1);
+        assert_eq!(unsafe { pool.scope(|s| fib(s, 15)) }, 610);
+    }
+
+    #[test]
+    fn nested_scope() {
+        // with only one worker, this would deadlock if the inner scope
+        // waited for a worker to pick it up
+        let pool = ForkJoinPool::new(1);
+        let p = &pool;
+        assert_eq!(unsafe { pool.scope(|_| p.scope(|s| fib(s, 10))) }, 55);
+    }
+
+    #[test]
+    fn borrows_from_the_stack() {
+        let pool = ForkJoinPool::new(4);
+        let xs = ~[1, 2, 3, 4];
+        let ys = ~[5, 6, 7, 8];
+        let (a, b) = unsafe {
+            pool.scope(|s| s.join(|_| xs.iter().fold(0, |a, &b| a + b),
+                                  |_| ys.iter().fold(0, |a, &b| a + b)))
+        };
+        assert_eq!((a, b), (10, 26));
+    }
+
+    #[test]
+    fn map() {
+        let pool = ForkJoinPool::new(4);
+        let xs = range(0, 10000).collect::<~[int]>();
+        let ys = unsafe { pool.scope(|s| s.map(xs, |&x| x * 2)) };
+        assert_eq!(ys.len(), xs.len());
+        for (x, y) in xs.iter().zip(ys.iter()) {
+            assert_eq!(*x * 2, *y);
+        }
+
+        let empty: ~[int] = ~[];
+        assert!(unsafe { pool.scope(|s| s.map(empty, |&x| x)) }.is_empty());
+    }
+
+    #[test]
+    fn reduce() {
+        let pool = ForkJoinPool::new(4);
+        let xs = range(1u, 10001).collect::<~[uint]>();
+        unsafe {
+            assert_eq!(pool.scope(|s| s.reduce(xs, |a, b| a + b)),
+                       Some(50005000));
+
+            // the elements are combined in order
+            let strs = xs.map(|x| x.to_str());
+            assert_eq!(pool.scope(|s| s.reduce(strs, |a, b| a.append(b))),
+                       Some(strs.concat()));
+
+            let empty: ~[uint] = ~[];
+            assert_eq!(pool.scope(|s| s.reduce(empty, |a, b| a + b)), None);
+        }
+    }
+
+    #[test]
+    fn for_each() {
+        let pool = ForkJoinPool::new(4);
+        let xs = range(0u, 10000).collect::<~[uint]>();
+        let mut sum = AtomicUint::new(0);
+        unsafe {
+            pool.scope(|s| s.for_each(xs, |&x| { sum.fetch_add(x, SeqCst); }));
+        }
+        assert_eq!(sum.load(SeqCst), 49995000);
+    }
+
+    #[test]
+    fn nested_slices() {
+        let pool = ForkJoinPool::new(4);
+        let xs = range(0u, 1000).collect::<~[uint]>();
+        let (ys, sum) = unsafe {
+            pool.scope(|s| {
+                s.join(|s| s.map(xs, |&x| x + 1),
+                       |s| s.reduce(xs, |a, b| a + b))
+            })
+        };
+        assert_eq!(ys, range(1u, 1001).collect::<~[uint]>());
+        assert_eq!(sum, Some(499500));
+    }
+
+    #[test]
+    #[should_fail]
+    fn failure_propagates() {
+        let pool = ForkJoinPool::new(4);
+        unsafe {
+            pool.scope(|s| {
+                s.join(|s| fib(s, 20), |_| fail!());
+            });
+        }
+    }
+
+    #[test]
+    fn failure_is_contained() {
+        // jobs keep failing in one scope while another one runs on the same
+        // workers, which mustn't be affected
+        let pool = UnsafeArc::new(ForkJoinPool::new(2));
+        let (port, chan) = Chan::new();
+        let p = pool.clone();
+        do spawn {
+            for _ in range(0, 100) {
+                let p = p.clone();
+                let res = task::try(proc() unsafe {
+                    (*p.get()).scope(|s| { s.join(|s| fib(s, 10), |_| fail!()); })
+                });
+                assert!(res.is_err());
+            }
+            chan.send(());
+        }
+        for _ in range(0, 100) {
+            assert_eq!(unsafe { (*pool.get()).scope(|s| fib(s, 15)) }, 610);
+        }
+        port.recv();
+    }
+
+    #[test]
+    fn helps_only_with_forks_of_the_stolen_job() {
+        fn nop(_: uint, _: &Scope) {}
+        let job = |n| Job { data: 0, run: nop, forked_in: n };
+
+        // the thief numbered the stolen job 5
+        let mut stolen = StackJob::<()> {
+            f: ptr::null(),
+            result: None,
+            failed: false,
+            thief: AtomicUint::new(NO_THIEF),
+            first: 5,
+            last: 0,
+            done: AtomicBool::new(false),
+        };
+        assert!(!stolen.forked(&job(4)));
+        assert!(stolen.forked(&job(5)));
+        assert!(stolen.forked(&job(7)));
+
+        // and ran the jobs up to 8 as part of it
+        stolen.last = 8;
+        stolen.done.store(true, SeqCst);
+        assert!(!stolen.forked(&job(4)));
+        assert!(stolen.forked(&job(8)));
+        assert!(!stolen.forked(&job(9)));
+    }
+
+    #[test]
+    fn drop_while_asleep() {
+        // dropping the pool waits for the workers to exit, so this hangs
+        // unless the sleeping workers are woken up
+        let pool = ForkJoinPool::new(4);
+        assert_eq!(unsafe { pool.scope(|s| fib(s, 10)) }, 55);
+        timer::sleep(50);
+        drop(pool);
+    }
+}
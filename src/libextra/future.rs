@@ -22,11 +22,31 @@
  * make_a_sandwich();
  * println!("fib(5000) = {}", delayed_fib.get())
  * ```
+ *
+ * Futures can be chained with `map` and `and_then`, collected with
+ * `join_all`, and raced against each other with `select_any`. If the task
+ * computing a future fails, then so does anything which forces the future
+ * with `get` or `unwrap`, while `get_ref_opt` and `unwrap_opt` return `None`
+ * instead.
+ *
+ * ```rust
+ * use extra::future::Future;
+ * let squares = range(0, 10).map(|i| {
+ *     Future::spawn(proc() i * i).map(proc(sq) sq + 1)
+ * }).collect();
+ * let mut all = Future::join_all(squares);
+ * assert_eq!(all.get()[3], 10);
+ *
+ * let failed: Future<int> = Future::spawn(proc() fail!());
+ * assert_eq!(failed.unwrap_opt(), None);
+ * ```
  */
 
 #[allow(missing_doc)];
 
+use std::task;
 use std::util::replace;
+use std::vec;
 
 /// A type encapsulating the result of a computation which may not be complete
 pub struct Future<A> {
@@ -34,9 +54,11 @@ pub struct Future<A> {
 }
 
 enum FutureState<A> {
-    Pending(proc() -> A),
+    // Returns None if the task computing the value failed
+    Pending(proc() -> Option<A>),
     Evaluating,
-    Forced(A)
+    Forced(A),
+    Failed,
 }
 
 /// Methods on the `future` type
@@ -49,11 +71,21 @@ impl<A:Clone> Future<A> {
 
 impl<A> Future<A> {
     /// Gets the value from this future, forcing evaluation.
-    pub fn unwrap(mut self) -> A {
-        self.get_ref();
+    pub fn unwrap(self) -> A {
+        match self.unwrap_opt() {
+            Some(v) => v,
+            None => fail!("The task computing a future failed."),
+        }
+    }
+
+    /// Gets the value from this future, forcing evaluation, or returns
+    /// `None` if the task computing it failed.
+    pub fn unwrap_opt(mut self) -> Option<A> {
+        self.get_ref_opt();
         let state = replace(&mut self.state, Evaluating);
         match state {
-            Forced(v) => v,
+            Forced(v) => Some(v),
+            Failed => None,
             _ => fail!( "Logic error." ),
         }
     }
@@ -64,21 +96,44 @@ impl<A> Future<A> {
         * to the result.  The reference lasts as long as
         * the future.
         */
+        match self.get_ref_opt() {
+            Some(v) => v,
+            None => fail!("The task computing a future failed."),
+        }
+    }
+
+    pub fn get_ref_opt<'a>(&'a mut self) -> Option<&'a A> {
+        /*!
+        * Like `get_ref`, but returns `None` instead of failing if the task
+        * computing the future failed.
+        */
         match self.state {
-            Forced(ref v) => return v,
+            Forced(ref v) => return Some(v),
+            Failed => return None,
             Evaluating => fail!("Recursive forcing of future!"),
             Pending(_) => {
                 match replace(&mut self.state, Evaluating) {
-                    Forced(_) | Evaluating => fail!("Logic error."),
+                    Forced(_) | Failed | Evaluating => fail!("Logic error."),
                     Pending(f) => {
-                        self.state = Forced(f());
-                        self.get_ref()
+                        self.state = match f() {
+                            Some(v) => Forced(v),
+                            None => Failed,
+                        };
+                        self.get_ref_opt()
                     }
                 }
             }
         }
     }
 
+    // Whether the future has been evaluated, successfully or not
+    fn is_ready(&self) -> bool {
+        match self.state {
+            Forced(_) | Failed => true,
+            Pending(_) | Evaluating => false,
+        }
+    }
+
     pub fn from_value(val: A) -> Future<A> {
         /*!
          * Create a future from a value.
@@ -99,7 +154,7 @@ impl<A> Future<A> {
          * function. It is not spawned into another task.
          */
 
-        Future {state: Pending(f)}
+        Future {state: Pending(proc() Some(f()))}
     }
 }
 
@@ -112,9 +167,7 @@ impl<A:Send> Future<A> {
          * waiting for the result to be received on the port.
          */
 
-        do Future::from_fn {
-            port.recv()
-        }
+        Future {state: Pending(proc() port.recv_opt())}
     }
 
     pub fn spawn(blk: proc() -> A) -> Future<A> {
@@ -133,6 +186,122 @@ impl<A:Send> Future<A> {
 
         Future::from_port(port)
     }
+
+    pub fn map<B>(self, f: proc(A) -> B) -> Future<B> {
+        /*!
+         * Create a future of the result of applying a function to the value
+         * of this future.
+         *
+         * The function is called in the task which forces the new future.
+         * If the task computing this future fails, then the function isn't
+         * called, and the new future fails as well.
+         */
+
+        let this = self;
+        Future {state: Pending(proc() {
+            match this.unwrap_opt() {
+                Some(v) => Some(f(v)),
+                None => None,
+            }
+        })}
+    }
+
+    pub fn and_then<B>(self, f: proc(A) -> Future<B>) -> Future<B> {
+        /*!
+         * Create a future of the value of the future which a function
+         * returns when applied to the value of this future.
+         *
+         * This is like `map`, except that the function can start some more
+         * work of its own, such as by spawning another future.
+         */
+
+        let this = self;
+        Future {state: Pending(proc() {
+            match this.unwrap_opt() {
+                Some(v) => f(v).unwrap_opt(),
+                None => None,
+            }
+        })}
+    }
+
+    pub fn join_all(futures: ~[Future<A>]) -> Future<~[A]> {
+        /*!
+         * Create a future of the values of all of the given futures, in
+         * order.
+         *
+         * If any of the futures fails, then so does the new future.
+         */
+
+        Future {state: Pending(proc() {
+            let mut values = vec::with_capacity(futures.len());
+            for future in futures.move_iter() {
+                match future.unwrap_opt() {
+                    Some(v) => values.push(v),
+                    None => return None,
+                }
+            }
+            Some(values)
+        })}
+    }
+
+    pub fn select_any(futures: ~[Future<A>])
+                      -> (uint, Future<A>, ~[Future<A>]) {
+        /*!
+         * Wait for the first of the given futures to finish being computed.
+         *
+         * Returns the index of the future which finished first, that future
+         * (whose value is now available without blocking, unless its task
+         * failed), and the rest of the futures in their original order.
+         *
+         * Each future which hasn't been evaluated yet is forced in a task
+         * of its own, so the rest of the futures carry on being computed in
+         * the background.
+         *
+         * # Failure
+         *
+         * Fails if `futures` is empty.
+         */
+
+        assert!(!futures.is_empty(), "select_any: no futures to select from");
+
+        let mut futures = futures;
+        match futures.iter().position(|f| f.is_ready()) {
+            Some(i) => {
+                let future = futures.remove(i);
+                return (i, future, futures);
+            }
+            None => {}
+        }
+
+        let (done_port, done_chan) = SharedChan::new();
+        let ports = futures.move_iter().enumerate().map(|(i, future)| {
+            let (port, chan) = Chan::new();
+            let done_chan = done_chan.clone();
+            do spawn {
+                let result = match task::try(proc() future.unwrap_opt()) {
+                    Ok(result) => result,
+                    Err(_) => None,
+                };
+                // The result is sent before the index so that it's
+                // available by the time that the index is received
+                chan.try_send(result);
+                done_chan.try_send(i);
+            }
+            port
+        }).collect::<~[Port<Option<A>>]>();
+
+        let i = done_port.recv();
+        let mut futures = ports.move_iter().map(|port| {
+            Future {state: Pending(proc() {
+                match port.recv_opt() {
+                    Some(result) => result,
+                    None => None,
+                }
+            })}
+        }).collect::<~[Future<A>]>();
+        let future = futures.remove(i);
+        (i, future, futures)
+    }
 }
 
 #[cfg(test)]
@@ -192,6 +361,115 @@ mod test {
         let _x: ~str = f.get();
     }
 
+    #[test]
+    fn test_unwrap_opt() {
+        assert_eq!(Future::from_value(1).unwrap_opt(), Some(1));
+        let f: Future<int> = Future::spawn(proc() fail!());
+        assert_eq!(f.unwrap_opt(), None);
+    }
+
+    #[test]
+    fn test_get_ref_opt_failed() {
+        let mut f: Future<int> = Future::spawn(proc() fail!());
+        assert!(f.get_ref_opt().is_none());
+        assert!(f.get_ref_opt().is_none());
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_from_port_closed() {
+        let (po, ch) = Chan::<int>::new();
+        drop(ch);
+        Future::from_port(po).unwrap();
+    }
+
+    #[test]
+    fn test_map() {
+        let f = Future::spawn(proc() 2).map(proc(x) x * 3).map(proc(x) x.to_str());
+        assert_eq!(f.unwrap(), ~"6");
+    }
+
+    #[test]
+    fn test_map_failed() {
+        let f: Future<int> = Future::spawn(proc() fail!());
+        let f = f.map(proc(_: int) -> int { fail!("map shouldn't be called") });
+        assert_eq!(f.unwrap_opt(), None);
+    }
+
+    #[test]
+    fn test_and_then() {
+        let f = Future::spawn(proc() 4).and_then(proc(x) {
+            Future::spawn(proc() x + 1)
+        });
+        assert_eq!(f.unwrap(), 5);
+
+        let f: Future<int> = Future::from_value(4).and_then(proc(_) {
+            Future::spawn(proc() fail!())
+        });
+        assert_eq!(f.unwrap_opt(), None);
+    }
+
+    #[test]
+    fn test_join_all() {
+        let futures = range(0, 10).map(|i| Future::spawn(proc() i * 2)).collect();
+        let mut f = Future::join_all(futures);
+        assert_eq!(f.get(), range(0, 10).map(|i| i * 2).collect::<~[int]>());
+
+        let empty: ~[Future<int>] = ~[];
+        assert_eq!(Future::join_all(empty).unwrap(), ~[]);
+    }
+
+    #[test]
+    fn test_join_all_failed() {
+        let futures = ~[Future::from_value(1),
+                        Future::spawn(proc() fail!()),
+                        Future::from_value(3)];
+        assert_eq!(Future::join_all(futures).unwrap_opt(), None);
+    }
+
+    #[test]
+    fn test_select_any() {
+        let (p1, c1) = Chan::new();
+        let (p2, c2) = Chan::new();
+        let futures = ~[Future::from_port(p1), Future::from_port(p2)];
+        c2.send(~"second");
+
+        let (i, winner, rest) = Future::select_any(futures);
+        assert_eq!(i, 1);
+        assert_eq!(winner.unwrap(), ~"second");
+        assert_eq!(rest.len(), 1);
+
+        c1.send(~"first");
+        let (i, winner, rest) = Future::select_any(rest);
+        assert_eq!(i, 0);
+        assert_eq!(winner.unwrap(), ~"first");
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_select_any_ready() {
+        let (p, _c) = Chan::new();
+        let mut done = Future::from_value(2);
+        done.get();
+        let futures = ~[Future::from_port(p), done];
+        let (i, winner, rest) = Future::select_any(futures);
+        assert_eq!(i, 1);
+        assert_eq!(winner.unwrap(), 2);
+        assert_eq!(rest.len(), 1);
+    }
+
+    #[test]
+    fn test_select_any_failed() {
+        let (p, c) = Chan::new();
+        let futures = ~[Future::from_port(p), Future::spawn(proc() fail!())];
+        let (i, winner, mut rest) = Future::select_any(futures);
+        assert_eq!(i, 1);
+        assert_eq!(winner.unwrap_opt(), None);
+
+        c.send(1);
+        assert_eq!(rest.pop().unwrap(), 1);
+    }
+
     #[test]
     fn test_sendable_future() {
         let expected = "schlorf";
@@ -26,7 +26,7 @@ use std::num;
 use std::str;
 use std::to_str;
 
-use serialize::Encodable;
+use serialize::{Encodable, Decodable};
 use serialize;
 use treemap::TreeMap;
 
@@ -857,6 +857,313 @@ pub fn from_str(s: &str) -> Result<Json, Error> {
     parser.parse()
 }
 
+/// The events produced by a `StreamingParser`
+#[deriving(Eq)]
+pub enum JsonEvent {
+    ObjectStart,
+    ObjectEnd,
+    /// A key of an object. The events which follow make up its value.
+    ObjectKey(~str),
+    ListStart,
+    ListEnd,
+    BooleanValue(bool),
+    NumberValue(f64),
+    StringValue(~str),
+    NullValue,
+    /// The input is not valid JSON. No more events are produced after this.
+    ParseError(Error),
+}
+
+/// One step along the path from the root of a JSON document to a value
+#[deriving(Eq, Clone)]
+pub enum StackElement {
+    /// An element of a list, by position
+    Index(uint),
+    /// A value in an object, by key
+    Key(~str),
+}
+
+/// The path from the root of a JSON document to the value described by the
+/// last event a `StreamingParser` produced.
+pub struct Stack {
+    priv elems: ~[StackElement],
+}
+
+impl Stack {
+    /// Returns the number of steps in the path, which is zero at the root
+    pub fn len(&self) -> uint { self.elems.len() }
+
+    /// Returns true if the path is at the root of the document
+    pub fn is_empty(&self) -> bool { self.elems.is_empty() }
+
+    /// Returns the step at the given depth, where 0 is the outermost one
+    pub fn get<'a>(&'a self, idx: uint) -> &'a StackElement { &self.elems[idx] }
+
+    /// Returns the innermost step of the path, if there is one
+    pub fn top<'a>(&'a self) -> Option<&'a StackElement> {
+        self.elems.last_opt()
+    }
+
+    /// Returns true if the path is exactly `path`
+    pub fn is_equal_to(&self, path: &[StackElement]) -> bool {
+        self.elems.as_slice() == path
+    }
+
+    /// Returns true if the path begins with `path`, i.e. the current value is
+    /// `path` or is nested somewhere inside of it.
+    pub fn starts_with(&self, path: &[StackElement]) -> bool {
+        self.elems.starts_with(path)
+    }
+
+    /// Returns true if the path ends with `path`
+    pub fn ends_with(&self, path: &[StackElement]) -> bool {
+        self.elems.ends_with(path)
+    }
+
+    fn bump_index(&mut self) {
+        match *self.elems.mut_last() {
+            Index(ref mut i) => *i += 1,
+            Key(..) => fail!("not parsing a list"),
+        }
+    }
+}
+
+// What a `StreamingParser` expects to find next in the input
+enum ParserState {
+    // The root value of the document
+    ParseStart,
+    // An element of a list, or the end of the list if this is the first
+    ParseList(bool),
+    // A `,` or `]` following an element of a list
+    ParseListComma,
+    // A key of an object, or the end of the object if this is the first
+    ParseObject(bool),
+    // The value for the key which was just parsed
+    ParseObjectValue,
+    // A `,` or `}` following a value in an object
+    ParseObjectComma,
+    // The end of the input, now that the root value is complete
+    ParseBeforeFinish,
+    // Nothing, either the input is exhausted or it was invalid
+    ParseFinished,
+}
+
+/// A pull parser which produces a sequence of `JsonEvent`s instead of
+/// building a `Json` value, so arbitrarily large documents can be processed
+/// without holding them in memory. Alongside each event, `stack` describes
+/// where in the document the event occurred.
+///
+/// # Example
+///
+/// ```rust
+/// use extra::json;
+///
+/// // Print every number in the list under the "a" key
+/// let s = "{\"a\": [1, 2], \"b\": 3}";
+/// let mut parser = json::StreamingParser::new(s.chars());
+/// loop {
+///     match parser.next() {
+///         Some(json::NumberValue(n)) => {
+///             if parser.stack().starts_with([json::Key(~"a")]) {
+///                 println!("{}", n);
+///             }
+///         }
+///         Some(json::ParseError(e)) => fail!("invalid json: {}", e.to_str()),
+///         Some(..) => {}
+///         None => break,
+///     }
+/// }
+/// ```
+///
+/// To parse a file incrementally, wrap it in a `BufferedReader` and hand its
+/// `chars()` iterator to the parser.
+pub struct StreamingParser<T> {
+    priv parser: Parser<T>,
+    priv stack: Stack,
+    priv state: ParserState,
+}
+
+impl<T: Iterator<char>> StreamingParser<T> {
+    /// Creates a parser for the JSON document contained in `rdr`
+    pub fn new(rdr: T) -> StreamingParser<T> {
+        StreamingParser {
+            parser: Parser::new(rdr),
+            stack: Stack { elems: ~[] },
+            state: ParseStart,
+        }
+    }
+
+    /// Returns the path to the value described by the last event. For an
+    /// `ObjectKey` event this includes the key itself, and for the start or
+    /// end of a list or object this is the path to the list or object.
+    pub fn stack<'a>(&'a self) -> &'a Stack { &self.stack }
+}
+
+impl<T: Iterator<char>> Iterator<JsonEvent> for StreamingParser<T> {
+    fn next(&mut self) -> Option<JsonEvent> {
+        match self.state {
+            ParseFinished => None,
+            ParseBeforeFinish => {
+                // Make sure there are no trailing characters.
+                self.parser.parse_whitespace();
+                if self.parser.eof() {
+                    self.state = ParseFinished;
+                    None
+                } else {
+                    Some(self.error(~"trailing characters"))
+                }
+            }
+            _ => Some(self.parse_event()),
+        }
+    }
+}
+
+impl<T: Iterator<char>> StreamingParser<T> {
+    fn error(&mut self, msg: ~str) -> JsonEvent {
+        let err = Error { line: self.parser.line, col: self.parser.col, msg: msg };
+        self.fatal(err)
+    }
+
+    fn fatal(&mut self, err: Error) -> JsonEvent {
+        self.state = ParseFinished;
+        ParseError(err)
+    }
+
+    fn parse_event(&mut self) -> JsonEvent {
+        // The only way around this loop is after a `,` has been parsed, which
+        // always moves to a state that returns an event.
+        loop {
+            match self.state {
+                ParseStart | ParseObjectValue => return self.parse_value(),
+                ParseList(first) => {
+                    self.parser.parse_whitespace();
+                    if first {
+                        if self.parser.ch == ']' {
+                            self.parser.bump();
+                            return self.end_value(ListEnd);
+                        }
+                        self.stack.elems.push(Index(0));
+                    } else {
+                        self.stack.bump_index();
+                    }
+                    return self.parse_value();
+                }
+                ParseListComma => {
+                    self.parser.parse_whitespace();
+                    if self.parser.eof() {
+                        return self.error(~"EOF while parsing list");
+                    }
+                    match self.parser.ch {
+                        ',' => {
+                            self.parser.bump();
+                            self.state = ParseList(false);
+                        }
+                        ']' => {
+                            self.parser.bump();
+                            self.stack.elems.pop();
+                            return self.end_value(ListEnd);
+                        }
+                        _ => return self.error(~"expected `,` or `]`"),
+                    }
+                }
+                ParseObject(first) => return self.parse_key(first),
+                ParseObjectComma => {
+                    self.parser.parse_whitespace();
+                    if self.parser.eof() {
+                        return self.error(~"EOF while parsing object");
+                    }
+                    match self.parser.ch {
+                        ',' => {
+                            self.parser.bump();
+                            self.stack.elems.pop();
+                            self.state = ParseObject(false);
+                        }
+                        '}' => {
+                            self.parser.bump();
+                            self.stack.elems.pop();
+                            return self.end_value(ObjectEnd);
+                        }
+                        _ => return self.error(~"expected `,` or `}`"),
+                    }
+                }
+                ParseBeforeFinish | ParseFinished => fail!("no more events"),
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> JsonEvent {
+        self.parser.parse_whitespace();
+        match self.parser.ch {
+            '[' => {
+                self.parser.bump();
+                self.state = ParseList(true);
+                ListStart
+            }
+            '{' => {
+                self.parser.bump();
+                self.state = ParseObject(true);
+                ObjectStart
+            }
+            _ => {
+                // Scalars are small enough to be parsed in one go, exactly as
+                // the tree-building parser does.
+                let event = match self.parser.parse_value() {
+                    Ok(Number(n)) => NumberValue(n),
+                    Ok(String(s)) => StringValue(s),
+                    Ok(Boolean(b)) => BooleanValue(b),
+                    Ok(Null) => NullValue,
+                    Ok(List(..)) | Ok(Object(..)) => fail!("unexpected container"),
+                    Err(e) => return self.fatal(e),
+                };
+                self.end_value(event)
+            }
+        }
+    }
+
+    fn parse_key(&mut self, first: bool) -> JsonEvent {
+        self.parser.parse_whitespace();
+        if self.parser.eof() {
+            return self.error(~"EOF while parsing object");
+        }
+        if first && self.parser.ch == '}' {
+            self.parser.bump();
+            return self.end_value(ObjectEnd);
+        }
+        if self.parser.ch != '\"' {
+            return self.error(~"key must be a string");
+        }
+
+        let key = match self.parser.parse_str() {
+            Ok(key) => key,
+            Err(e) => return self.fatal(e),
+        };
+
+        self.parser.parse_whitespace();
+        if self.parser.ch != ':' {
+            if self.parser.eof() {
+                return self.error(~"EOF while parsing object");
+            }
+            return self.error(~"expected `:`");
+        }
+        self.parser.bump();
+
+        self.stack.elems.push(Key(key.clone()));
+        self.state = ParseObjectValue;
+        ObjectKey(key)
+    }
+
+    // Called once a complete value has been parsed, moving on to whatever is
+    // expected after a value in the enclosing list or object.
+    fn end_value(&mut self, event: JsonEvent) -> JsonEvent {
+        self.state = match self.stack.top() {
+            None => ParseBeforeFinish,
+            Some(&Index(..)) => ParseListComma,
+            Some(&Key(..)) => ParseObjectComma,
+        };
+        event
+    }
+}
+
 /// A structure to decode JSON to values in rust.
 pub struct Decoder {
     priv stack: ~[Json],
@@ -1136,6 +1443,431 @@ impl serialize::Decoder for Decoder {
     }
 }
 
+/// A structure to decode values in rust directly from the events of a
+/// `StreamingParser`, without building a `Json` value first.
+///
+/// The fields of a struct are decoded as they are encountered in the input.
+/// Fields which appear before the ones asked for first are buffered until
+/// they're needed. Lists and maps are buffered in full to learn their length
+/// before any elements are decoded, so `each_elt` should be used to decode the
+/// elements of a large list one at a time.
+pub struct StreamingDecoder<T> {
+    priv parser: StreamingParser<T>,
+    // Events to produce before pulling any more from the parser, with the
+    // next one at the end.
+    priv pending: ~[JsonEvent],
+    // For each struct being decoded, the fields which were skipped over while
+    // looking for another field, along with the events making up their values.
+    priv skipped: ~[~[(~str, ~[JsonEvent])]],
+}
+
+impl<T: Iterator<char>> StreamingDecoder<T> {
+    /// Creates a new decoder which decodes values from `parser`
+    pub fn new(parser: StreamingParser<T>) -> StreamingDecoder<T> {
+        StreamingDecoder {
+            parser: parser,
+            pending: ~[],
+            skipped: ~[],
+        }
+    }
+
+    /// Decodes each element of the list at the current position of the input,
+    /// handing them to `f` one at a time. Unlike decoding a `~[D]`, the list
+    /// is never held in memory in its entirety.
+    pub fn each_elt<D: Decodable<StreamingDecoder<T>>>(&mut self, f: |D|) {
+        match self.next_event() {
+            ListStart => {}
+            event => self.expected("list", &event),
+        }
+        loop {
+            match self.next_event() {
+                ListEnd => break,
+                event => {
+                    self.pending.push(event);
+                    f(Decodable::decode(self));
+                }
+            }
+        }
+    }
+}
+
+impl<T: Iterator<char>> StreamingDecoder<T> {
+    fn err(&self, msg: &str) -> ! {
+        fail!("JSON decode error: {}", msg);
+    }
+    fn missing_field(&self, field: &str) -> ! {
+        self.err(format!("missing required '{}' field in object", field))
+    }
+    fn expected(&self, expected: &str, found: &JsonEvent) -> ! {
+        let found_s = match *found {
+            ObjectStart => "object",
+            ObjectEnd => "end of object",
+            ObjectKey(..) => "object key",
+            ListStart => "list",
+            ListEnd => "end of list",
+            BooleanValue(..) => "boolean",
+            NumberValue(..) => "number",
+            StringValue(..) => "string",
+            NullValue => "null",
+            ParseError(..) => "invalid json",
+        };
+        self.err(format!("expected {} but found {}", expected, found_s))
+    }
+
+    fn next_event(&mut self) -> JsonEvent {
+        match self.pending.pop_opt() {
+            Some(event) => return event,
+            None => {}
+        }
+        match self.parser.next() {
+            Some(ParseError(e)) => self.err(e.to_str()),
+            Some(event) => event,
+            None => self.err("EOF while decoding value"),
+        }
+    }
+
+    // Arranges for `events` to be produced before any others
+    fn replay(&mut self, events: ~[JsonEvent]) {
+        for event in events.move_rev_iter() {
+            self.pending.push(event);
+        }
+    }
+
+    // Pulls all of the events making up the next value
+    fn read_value_events(&mut self) -> ~[JsonEvent] {
+        let mut events = ~[];
+        let mut depth = 0u;
+        loop {
+            let event = self.next_event();
+            match event {
+                ListStart | ObjectStart => depth += 1,
+                ListEnd | ObjectEnd => depth -= 1,
+                _ => {}
+            }
+            events.push(event);
+            if depth == 0 {
+                return events
+            }
+        }
+    }
+
+    // Pulls the events up to the end of the list or object which was just
+    // started, returning them (without the closing event) along with the
+    // number of values they contain. Keys of an object are turned into
+    // strings so that they can be decoded as the keys of a map.
+    fn read_container(&mut self) -> (~[JsonEvent], uint) {
+        let mut events = ~[];
+        let mut len = 0u;
+        let mut depth = 0u;
+        loop {
+            let mut event = self.next_event();
+            match event {
+                ListEnd | ObjectEnd if depth == 0 => return (events, len),
+                ListEnd | ObjectEnd => depth -= 1,
+                ListStart | ObjectStart => {
+                    if depth == 0 { len += 1 }
+                    depth += 1;
+                }
+                ObjectKey(..) => {}
+                _ => if depth == 0 { len += 1 },
+            }
+            if depth == 0 {
+                event = match event {
+                    ObjectKey(key) => StringValue(key),
+                    event => event,
+                };
+            }
+            events.push(event);
+        }
+    }
+}
+
+impl<T: Iterator<char>> serialize::Decoder for StreamingDecoder<T> {
+    fn read_nil(&mut self) -> () {
+        debug!("read_nil");
+        match self.next_event() {
+            NullValue => (),
+            event => self.expected("null", &event)
+        }
+    }
+
+    fn read_u64(&mut self)  -> u64  { self.read_f64() as u64 }
+    fn read_u32(&mut self)  -> u32  { self.read_f64() as u32 }
+    fn read_u16(&mut self)  -> u16  { self.read_f64() as u16 }
+    fn read_u8 (&mut self)  -> u8   { self.read_f64() as u8 }
+    fn read_uint(&mut self) -> uint { self.read_f64() as uint }
+
+    fn read_i64(&mut self) -> i64 { self.read_f64() as i64 }
+    fn read_i32(&mut self) -> i32 { self.read_f64() as i32 }
+    fn read_i16(&mut self) -> i16 { self.read_f64() as i16 }
+    fn read_i8 (&mut self) -> i8  { self.read_f64() as i8 }
+    fn read_int(&mut self) -> int { self.read_f64() as int }
+
+    fn read_bool(&mut self) -> bool {
+        debug!("read_bool");
+        match self.next_event() {
+            BooleanValue(b) => b,
+            event => self.expected("boolean", &event)
+        }
+    }
+
+    fn read_f64(&mut self) -> f64 {
+        debug!("read_f64");
+        match self.next_event() {
+            NumberValue(f) => f,
+            event => self.expected("number", &event)
+        }
+    }
+    fn read_f32(&mut self) -> f32 { self.read_f64() as f32 }
+
+    fn read_char(&mut self) -> char {
+        let s = self.read_str();
+        {
+            let mut it = s.chars();
+            match (it.next(), it.next()) {
+                // exactly one character
+                (Some(c), None) => return c,
+                _ => ()
+            }
+        }
+        self.expected("single character string", &StringValue(s))
+    }
+
+    fn read_str(&mut self) -> ~str {
+        debug!("read_str");
+        match self.next_event() {
+            StringValue(s) => s,
+            event => self.expected("string", &event)
+        }
+    }
+
+    fn read_enum<U>(&mut self,
+                    name: &str,
+                    f: |&mut StreamingDecoder<T>| -> U)
+                    -> U {
+        debug!("read_enum({})", name);
+        f(self)
+    }
+
+    fn read_enum_variant<U>(&mut self,
+                            names: &[&str],
+                            f: |&mut StreamingDecoder<T>, uint| -> U)
+                            -> U {
+        debug!("read_enum_variant(names={:?})", names);
+        let name = match self.next_event() {
+            StringValue(s) => s,
+            ObjectStart => {
+                // The fields are buffered as "variant" may come after them
+                let mut variant = None;
+                let mut fields = None;
+                loop {
+                    match self.next_event() {
+                        ObjectKey(key) => {
+                            if key.as_slice() == "variant" {
+                                match self.next_event() {
+                                    StringValue(s) => variant = Some(s),
+                                    event => self.expected("string", &event)
+                                }
+                            } else if key.as_slice() == "fields" {
+                                match self.next_event() {
+                                    ListStart => {}
+                                    event => self.expected("list", &event)
+                                }
+                                let (events, _) = self.read_container();
+                                fields = Some(events);
+                            } else {
+                                self.read_value_events();
+                            }
+                        }
+                        ObjectEnd => break,
+                        event => self.expected("object key", &event)
+                    }
+                }
+                let n = match variant {
+                    Some(n) => n,
+                    None => self.missing_field("variant")
+                };
+                match fields {
+                    Some(events) => self.replay(events),
+                    None => self.missing_field("fields")
+                }
+                n
+            }
+            event => self.expected("string or object", &event)
+        };
+        let idx = match names.iter().position(|n| str::eq_slice(*n, name)) {
+            Some(idx) => idx,
+            None => self.err(format!("unknown variant name: {}", name))
+        };
+        f(self, idx)
+    }
+
+    fn read_enum_variant_arg<U>(&mut self,
+                                idx: uint,
+                                f: |&mut StreamingDecoder<T>| -> U)
+                                -> U {
+        debug!("read_enum_variant_arg(idx={})", idx);
+        f(self)
+    }
+
+    fn read_enum_struct_variant<U>(&mut self,
+                                   names: &[&str],
+                                   f: |&mut StreamingDecoder<T>, uint| -> U)
+                                   -> U {
+        debug!("read_enum_struct_variant(names={:?})", names);
+        self.read_enum_variant(names, f)
+    }
+
+    fn read_enum_struct_variant_field<U>(&mut self,
+                                         name: &str,
+                                         idx: uint,
+                                         f: |&mut StreamingDecoder<T>| -> U)
+                                         -> U {
+        debug!("read_enum_struct_variant_field(name={}, idx={})", name, idx);
+        self.read_enum_variant_arg(idx, f)
+    }
+
+    fn read_struct<U>(&mut self,
+                      name: &str,
+                      len: uint,
+                      f: |&mut StreamingDecoder<T>| -> U)
+                      -> U {
+        debug!("read_struct(name={}, len={})", name, len);
+        match self.next_event() {
+            ObjectStart => {}
+            event => self.expected("object", &event)
+        }
+        self.skipped.push(~[]);
+        let value = f(self);
+        self.skipped.pop();
+
+        // Skip over any fields which weren't asked for
+        loop {
+            match self.next_event() {
+                ObjectKey(..) => { self.read_value_events(); }
+                ObjectEnd => break,
+                event => self.expected("object key", &event)
+            }
+        }
+        value
+    }
+
+    fn read_struct_field<U>(&mut self,
+                            name: &str,
+                            idx: uint,
+                            f: |&mut StreamingDecoder<T>| -> U)
+                            -> U {
+        debug!("read_struct_field(name={}, idx={})", name, idx);
+        let pos = self.skipped.last().iter().position(|&(ref key, _)| {
+            name == key.as_slice()
+        });
+        match pos {
+            Some(i) => {
+                let (_, events) = self.skipped.mut_last().remove(i);
+                self.replay(events);
+                return f(self);
+            }
+            None => {}
+        }
+
+        loop {
+            match self.next_event() {
+                ObjectKey(key) => {
+                    if name == key.as_slice() {
+                        return f(self);
+                    }
+                    let events = self.read_value_events();
+                    self.skipped.mut_last().push((key, events));
+                }
+                ObjectEnd => self.missing_field(name),
+                event => self.expected("object key", &event)
+            }
+        }
+    }
+
+    fn read_tuple<U>(&mut self, f: |&mut StreamingDecoder<T>, uint| -> U) -> U {
+        debug!("read_tuple()");
+        self.read_seq(f)
+    }
+
+    fn read_tuple_arg<U>(&mut self,
+                         idx: uint,
+                         f: |&mut StreamingDecoder<T>| -> U)
+                         -> U {
+        debug!("read_tuple_arg(idx={})", idx);
+        self.read_seq_elt(idx, f)
+    }
+
+    fn read_tuple_struct<U>(&mut self,
+                            name: &str,
+                            f: |&mut StreamingDecoder<T>, uint| -> U)
+                            -> U {
+        debug!("read_tuple_struct(name={})", name);
+        self.read_tuple(f)
+    }
+
+    fn read_tuple_struct_arg<U>(&mut self,
+                                idx: uint,
+                                f: |&mut StreamingDecoder<T>| -> U)
+                                -> U {
+        debug!("read_tuple_struct_arg(idx={})", idx);
+        self.read_tuple_arg(idx, f)
+    }
+
+    fn read_option<U>(&mut self, f: |&mut StreamingDecoder<T>, bool| -> U) -> U {
+        match self.next_event() {
+            NullValue => f(self, false),
+            event => { self.pending.push(event); f(self, true) }
+        }
+    }
+
+    fn read_seq<U>(&mut self, f: |&mut StreamingDecoder<T>, uint| -> U) -> U {
+        debug!("read_seq()");
+        match self.next_event() {
+            ListStart => {}
+            event => self.expected("list", &event)
+        }
+        let (events, len) = self.read_container();
+        self.replay(events);
+        f(self, len)
+    }
+
+    fn read_seq_elt<U>(&mut self,
+                       idx: uint,
+                       f: |&mut StreamingDecoder<T>| -> U)
+                       -> U {
+        debug!("read_seq_elt(idx={})", idx);
+        f(self)
+    }
+
+    fn read_map<U>(&mut self, f: |&mut StreamingDecoder<T>, uint| -> U) -> U {
+        debug!("read_map()");
+        match self.next_event() {
+            ObjectStart => {}
+            event => self.expected("object", &event)
+        }
+        let (events, len) = self.read_container();
+        self.replay(events);
+        f(self, len)
+    }
+
+    fn read_map_elt_key<U>(&mut self,
+                           idx: uint,
+                           f: |&mut StreamingDecoder<T>| -> U)
+                           -> U {
+        debug!("read_map_elt_key(idx={})", idx);
+        f(self)
+    }
+
+    fn read_map_elt_val<U>(&mut self,
+                           idx: uint,
+                           f: |&mut StreamingDecoder<T>| -> U)
+                           -> U {
+        debug!("read_map_elt_val(idx={})", idx);
+        f(self)
+    }
+}
+
 /// Test if two json values are less than one another
 impl Ord for Json {
     fn lt(&self, other: &Json) -> bool {
@@ -1335,6 +2067,7 @@ mod tests {
     use super::*;
 
     use std::io;
+    use std::str;
     use serialize::{Encodable, Decodable};
     use treemap::TreeMap;
 
@@ -2032,4 +2765,144 @@ mod tests {
         check_err::<DecodeEnum>("{\"variant\": \"C\", \"fields\": []}",
                                 "unknown variant name");
     }
+
+    fn stream_events(s: &str) -> ~[(JsonEvent, ~[StackElement])] {
+        let mut parser = StreamingParser::new(s.chars());
+        let mut events = ~[];
+        loop {
+            match parser.next() {
+                Some(event) => events.push((event, parser.stack().elems.clone())),
+                None => return events,
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_scalars() {
+        assert_eq!(stream_events("null"), ~[(NullValue, ~[])]);
+        assert_eq!(stream_events(" true "), ~[(BooleanValue(true), ~[])]);
+        assert_eq!(stream_events("-1.5e1"), ~[(NumberValue(-15.0), ~[])]);
+        assert_eq!(stream_events("\"\\u12ab\""),
+                   ~[(StringValue(~"\u12ab"), ~[])]);
+    }
+
+    #[test]
+    fn test_streaming_containers() {
+        assert_eq!(stream_events("[]"), ~[(ListStart, ~[]), (ListEnd, ~[])]);
+        assert_eq!(stream_events("{}"),
+                   ~[(ObjectStart, ~[]), (ObjectEnd, ~[])]);
+
+        let s = "{ \"a\": [1, { \"b\": null }, []], \"c\": true }";
+        assert_eq!(stream_events(s), ~[
+            (ObjectStart, ~[]),
+            (ObjectKey(~"a"), ~[Key(~"a")]),
+            (ListStart, ~[Key(~"a")]),
+            (NumberValue(1.0), ~[Key(~"a"), Index(0)]),
+            (ObjectStart, ~[Key(~"a"), Index(1)]),
+            (ObjectKey(~"b"), ~[Key(~"a"), Index(1), Key(~"b")]),
+            (NullValue, ~[Key(~"a"), Index(1), Key(~"b")]),
+            (ObjectEnd, ~[Key(~"a"), Index(1)]),
+            (ListStart, ~[Key(~"a"), Index(2)]),
+            (ListEnd, ~[Key(~"a"), Index(2)]),
+            (ListEnd, ~[Key(~"a")]),
+            (ObjectKey(~"c"), ~[Key(~"c")]),
+            (BooleanValue(true), ~[Key(~"c")]),
+            (ObjectEnd, ~[]),
+        ]);
+    }
+
+    #[test]
+    fn test_streaming_stack() {
+        let mut parser = StreamingParser::new("[{\"a\": 1}]".chars());
+        parser.next();
+        parser.next();
+        parser.next();
+        assert_eq!(parser.next(), Some(NumberValue(1.0)));
+        let stack = parser.stack();
+        assert_eq!(stack.len(), 2);
+        assert!(stack.is_equal_to([Index(0), Key(~"a")]));
+        assert!(stack.starts_with([Index(0)]));
+        assert!(stack.ends_with([Key(~"a")]));
+        assert_eq!(stack.get(0), &Index(0));
+        assert_eq!(stack.top(), Some(&Key(~"a")));
+    }
+
+    #[test]
+    fn test_streaming_errors_match_tree_parser() {
+        let inputs = ["", "[1,", "[1 2]", "[1,]", "{\"a\" 1}", "{\"a\": 1,}",
+                      "{\"a\": 1", "{1: 2}", "[1] x", "nul", "\"abc",
+                      "{\n  \"foo\":\n \"bar\""];
+        for s in inputs.iter() {
+            let expected = from_str(*s).unwrap_err();
+            let events = stream_events(*s);
+            match events.last_opt() {
+                Some(&(ParseError(ref e), _)) => assert_eq!(e, &expected),
+                _ => fail!("no error parsing `{}`", *s),
+            }
+        }
+    }
+
+    fn stream_decoder<'a>(s: &'a str) -> StreamingDecoder<str::Chars<'a>> {
+        StreamingDecoder::new(StreamingParser::new(s.chars()))
+    }
+
+    #[test]
+    fn test_streaming_decode_struct() {
+        let s = "{
+            \"inner\": [
+                { \"c\": [\"abc\", \"xyz\"], \"extra\": {\"x\": [1]}, \"b\": 2,
+                  \"a\": null }
+            ]
+        }";
+        let v: Outer = Decodable::decode(&mut stream_decoder(s));
+        assert_eq!(
+            v,
+            Outer {
+                inner: ~[
+                    Inner { a: (), b: 2, c: ~[~"abc", ~"xyz"] }
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_streaming_decode_enum_and_option() {
+        let value: Animal = Decodable::decode(&mut stream_decoder("\"Dog\""));
+        assert_eq!(value, Dog);
+
+        let s = "{\"fields\":[\"Henry\",349],\"variant\":\"Frog\"}";
+        let value: Animal = Decodable::decode(&mut stream_decoder(s));
+        assert_eq!(value, Frog(~"Henry", 349));
+
+        let s = "[null, \"jodhpurs\"]";
+        let value: ~[Option<~str>] = Decodable::decode(&mut stream_decoder(s));
+        assert_eq!(value, ~[None, Some(~"jodhpurs")]);
+    }
+
+    #[test]
+    fn test_streaming_decode_map() {
+        let s = "{\"a\": \"Dog\", \"b\": {\"variant\":\"Frog\",\"fields\":[\"Henry\", 349]}}";
+        let mut map: TreeMap<~str, Animal> =
+            Decodable::decode(&mut stream_decoder(s));
+
+        assert_eq!(map.pop(&~"a"), Some(Dog));
+        assert_eq!(map.pop(&~"b"), Some(Frog(~"Henry", 349)));
+    }
+
+    #[test]
+    fn test_streaming_each_elt() {
+        let s = "[\"Dog\", \"Dog\",
+                  {\"variant\":\"Frog\",\"fields\":[\"Henry\", 349]}]";
+        let mut decoder = stream_decoder(s);
+        let mut animals = ~[];
+        decoder.each_elt(|animal: Animal| animals.push(animal));
+        assert_eq!(animals, ~[Dog, Dog, Frog(~"Henry", 349)]);
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_streaming_decode_missing_field() {
+        let s = "{\"x\": 1, \"y\": true, \"z\": \"\"}";
+        let _: DecodeStruct = Decodable::decode(&mut stream_decoder(s));
+    }
 }
@@ -46,6 +46,7 @@ pub mod arc;
 pub mod comm;
 pub mod future;
 pub mod task_pool;
+pub mod fork_join;
 
 // Collections
 
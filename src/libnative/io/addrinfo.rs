@@ -0,0 +1,320 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Blocking DNS resolution via the system's getaddrinfo()
+
+use ai = std::io::net::addrinfo;
+use std::cast;
+use std::io::IoError;
+use std::libc::c_int;
+use std::libc;
+use std::ptr::null;
+
+use super::IoResult;
+use super::net::sockaddr_to_addr;
+
+pub struct GetAddrInfoRequest;
+
+impl GetAddrInfoRequest {
+    pub fn run(host: Option<&str>, servname: Option<&str>,
+               hint: Option<ai::Hint>) -> IoResult<~[ai::Info]> {
+        assert!(host.is_some() || servname.is_some());
+
+        let c_host = host.map(|x| x.to_c_str());
+        let c_serv = servname.map(|x| x.to_c_str());
+        let c_host_ptr = c_host.as_ref().map_or(null(), |c| c.with_ref(|p| p));
+        let c_serv_ptr = c_serv.as_ref().map_or(null(), |c| c.with_ref(|p| p));
+
+        let hint = hint.map(|hint| {
+            let mut flags = 0;
+            each_ai_flag(|cval, aival| {
+                if hint.flags & (1 << (aival as uint)) != 0 {
+                    flags |= cval;
+                }
+            });
+            imp::addrinfo {
+                ai_flags: flags,
+                ai_family: hint.family as c_int,
+                ai_socktype: hint.socktype.map_or(0, socktype_to_c),
+                ai_protocol: hint.protocol.map_or(0, protocol_to_c),
+                ai_addrlen: 0,
+                ai_canonname: null(),
+                ai_addr: null(),
+                ai_next: null(),
+            }
+        });
+        let hint_ptr = hint.as_ref().map_or(null(), |x| x as *imp::addrinfo);
+        let mut res = null();
+
+        // Make the call
+        let s = unsafe {
+            imp::getaddrinfo(c_host_ptr, c_serv_ptr, hint_ptr, &mut res)
+        };
+
+        // Error?
+        if s != 0 {
+            return Err(get_error(s));
+        }
+
+        // Collect all the results we found
+        let mut addrs = ~[];
+        let mut rp = res;
+        while rp.is_not_null() {
+            unsafe {
+                let storage: &libc::sockaddr_storage =
+                    cast::transmute((*rp).ai_addr);
+                let addr = match sockaddr_to_addr(storage,
+                                                  (*rp).ai_addrlen as uint) {
+                    Ok(addr) => addr,
+                    Err(e) => {
+                        imp::freeaddrinfo(res);
+                        return Err(e)
+                    }
+                };
+
+                let mut flags = 0;
+                each_ai_flag(|cval, aival| {
+                    if (*rp).ai_flags & cval != 0 {
+                        flags |= 1 << (aival as uint);
+                    }
+                });
+
+                addrs.push(ai::Info {
+                    address: addr,
+                    family: (*rp).ai_family as uint,
+                    socktype: socktype_from_c((*rp).ai_socktype),
+                    protocol: protocol_from_c((*rp).ai_protocol),
+                    flags: flags,
+                });
+
+                rp = (*rp).ai_next;
+            }
+        }
+
+        unsafe { imp::freeaddrinfo(res); }
+
+        Ok(addrs)
+    }
+}
+
+static SOCK_RAW: c_int = 3;
+static IPPROTO_UDP: c_int = 17;
+
+fn socktype_to_c(ty: ai::SocketType) -> c_int {
+    match ty {
+        ai::Stream => libc::SOCK_STREAM,
+        ai::Datagram => libc::SOCK_DGRAM,
+        ai::Raw => SOCK_RAW,
+    }
+}
+
+fn socktype_from_c(ty: c_int) -> Option<ai::SocketType> {
+    match ty {
+        libc::SOCK_STREAM => Some(ai::Stream),
+        libc::SOCK_DGRAM => Some(ai::Datagram),
+        SOCK_RAW => Some(ai::Raw),
+        _ => None,
+    }
+}
+
+fn protocol_to_c(proto: ai::Protocol) -> c_int {
+    match proto {
+        ai::TCP => libc::IPPROTO_TCP,
+        ai::UDP => IPPROTO_UDP,
+    }
+}
+
+fn protocol_from_c(proto: c_int) -> Option<ai::Protocol> {
+    match proto {
+        libc::IPPROTO_TCP => Some(ai::TCP),
+        IPPROTO_UDP => Some(ai::UDP),
+        _ => None,
+    }
+}
+
+fn each_ai_flag(f: |c_int, ai::Flag|) {
+    f(imp::AI_ADDRCONFIG, ai::AddrConfig);
+    f(imp::AI_ALL, ai::All);
+    f(imp::AI_CANONNAME, ai::CanonName);
+    f(imp::AI_NUMERICHOST, ai::NumericHost);
+    f(imp::AI_NUMERICSERV, ai::NumericServ);
+    f(imp::AI_PASSIVE, ai::Passive);
+    f(imp::AI_V4MAPPED, ai::V4Mapped);
+}
+
+#[cfg(windows)]
+fn get_error(_: c_int) -> IoError {
+    use super::translate_error;
+
+    unsafe {
+        translate_error(imp::WSAGetLastError() as i32, true)
+    }
+}
+
+#[cfg(not(windows))]
+fn get_error(s: c_int) -> IoError {
+    use std::io;
+    use std::str::raw::from_c_str;
+
+    let err_str = unsafe { from_c_str(imp::gai_strerror(s)) };
+    IoError {
+        kind: io::OtherIoError,
+        desc: "unable to resolve host",
+        detail: Some(err_str),
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[cfg(target_os = "android")]
+mod imp {
+    use std::libc::{c_char, c_int, sockaddr, socklen_t};
+
+    pub static AI_PASSIVE: c_int = 0x0001;
+    pub static AI_CANONNAME: c_int = 0x0002;
+    pub static AI_NUMERICHOST: c_int = 0x0004;
+    pub static AI_V4MAPPED: c_int = 0x0008;
+    pub static AI_ALL: c_int = 0x0010;
+    pub static AI_ADDRCONFIG: c_int = 0x0020;
+    pub static AI_NUMERICSERV: c_int = 0x0400;
+
+    pub struct addrinfo {
+        ai_flags: c_int,
+        ai_family: c_int,
+        ai_socktype: c_int,
+        ai_protocol: c_int,
+        ai_addrlen: socklen_t,
+        ai_addr: *sockaddr,
+        ai_canonname: *c_char,
+        ai_next: *addrinfo,
+    }
+
+    extern {
+        pub fn getaddrinfo(node: *c_char, service: *c_char,
+                           hints: *addrinfo, res: *mut *addrinfo) -> c_int;
+        pub fn freeaddrinfo(res: *addrinfo);
+        pub fn gai_strerror(errcode: c_int) -> *c_char;
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[cfg(target_os = "freebsd")]
+mod imp {
+    use std::libc::{c_char, c_int, sockaddr, socklen_t};
+
+    pub static AI_PASSIVE: c_int = 0x0001;
+    pub static AI_CANONNAME: c_int = 0x0002;
+    pub static AI_NUMERICHOST: c_int = 0x0004;
+    pub static AI_ALL: c_int = 0x0100;
+    pub static AI_ADDRCONFIG: c_int = 0x0400;
+    pub static AI_V4MAPPED: c_int = 0x0800;
+    #[cfg(target_os = "macos")]
+    pub static AI_NUMERICSERV: c_int = 0x1000;
+    #[cfg(target_os = "freebsd")]
+    pub static AI_NUMERICSERV: c_int = 0x0008;
+
+    pub struct addrinfo {
+        ai_flags: c_int,
+        ai_family: c_int,
+        ai_socktype: c_int,
+        ai_protocol: c_int,
+        ai_addrlen: socklen_t,
+        ai_canonname: *c_char,
+        ai_addr: *sockaddr,
+        ai_next: *addrinfo,
+    }
+
+    extern {
+        pub fn getaddrinfo(node: *c_char, service: *c_char,
+                           hints: *addrinfo, res: *mut *addrinfo) -> c_int;
+        pub fn freeaddrinfo(res: *addrinfo);
+        pub fn gai_strerror(errcode: c_int) -> *c_char;
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::libc::{c_char, c_int, sockaddr, size_t};
+
+    pub static AI_PASSIVE: c_int = 0x0001;
+    pub static AI_CANONNAME: c_int = 0x0002;
+    pub static AI_NUMERICHOST: c_int = 0x0004;
+    pub static AI_NUMERICSERV: c_int = 0x0008;
+    pub static AI_ALL: c_int = 0x0100;
+    pub static AI_ADDRCONFIG: c_int = 0x0400;
+    pub static AI_V4MAPPED: c_int = 0x0800;
+
+    pub struct addrinfo {
+        ai_flags: c_int,
+        ai_family: c_int,
+        ai_socktype: c_int,
+        ai_protocol: c_int,
+        ai_addrlen: size_t,
+        ai_canonname: *c_char,
+        ai_addr: *sockaddr,
+        ai_next: *addrinfo,
+    }
+
+    #[link(name = "ws2_32")]
+    extern "system" {
+        pub fn getaddrinfo(node: *c_char, service: *c_char,
+                           hints: *addrinfo, res: *mut *addrinfo) -> c_int;
+        pub fn freeaddrinfo(res: *addrinfo);
+        pub fn WSAGetLastError() -> c_int;
+    }
+}
+
+#[cfg(test, not(target_os = "android"))]
+mod test {
+    use ai = std::io::net::addrinfo;
+    use std::io::net::ip::{SocketAddr, Ipv4Addr};
+    use super::GetAddrInfoRequest;
+
+    #[test]
+    fn localhost() {
+        let infos = GetAddrInfoRequest::run(Some("localhost"), None,
+                                            None).unwrap();
+        let local_addr = SocketAddr { ip: Ipv4Addr(127, 0, 0, 1), port: 0 };
+        assert!(infos.iter().any(|info| info.address == local_addr));
+    }
+
+    #[test]
+    fn numeric_host_and_service() {
+        let hint = ai::Hint {
+            family: 0,
+            socktype: Some(ai::Stream),
+            protocol: None,
+            flags: (1 << (ai::NumericHost as uint)) |
+                   (1 << (ai::NumericServ as uint)),
+        };
+        let infos = GetAddrInfoRequest::run(Some("127.0.0.1"), Some("8080"),
+                                            Some(hint)).unwrap();
+        assert!(infos.len() > 0);
+        for info in infos.iter() {
+            assert_eq!(info.address,
+                       SocketAddr { ip: Ipv4Addr(127, 0, 0, 1), port: 8080 });
+            match info.socktype {
+                Some(ai::Stream) => {}
+                _ => fail!("expected a stream socket"),
+            }
+        }
+    }
+
+    #[test]
+    fn numeric_host_rejects_names() {
+        let hint = ai::Hint {
+            family: 0,
+            socktype: None,
+            protocol: None,
+            flags: 1 << (ai::NumericHost as uint),
+        };
+        assert!(GetAddrInfoRequest::run(Some("localhost"), None,
+                                        Some(hint)).is_err());
+    }
+}
@@ -294,6 +294,14 @@ impl rtio::RtioPipe for FileDesc {
     fn write(&mut self, buf: &[u8]) -> Result<(), IoError> {
         self.inner_write(buf)
     }
+    // Each end of an anonymous pipe is only ever readable or writable, so
+    // there's nothing to half-close.
+    fn close_write(&mut self) -> Result<(), IoError> {
+        Err(super::unimpl())
+    }
+    fn close_read(&mut self) -> Result<(), IoError> {
+        Err(super::unimpl())
+    }
 }
 
 impl rtio::RtioTTY for FileDesc {
@@ -303,13 +311,23 @@ impl rtio::RtioTTY for FileDesc {
     fn write(&mut self, buf: &[u8]) -> Result<(), IoError> {
         self.inner_write(buf)
     }
+    #[cfg(unix)]
+    fn set_raw(&mut self, raw: bool) -> Result<(), IoError> {
+        super::tty::set_raw(self.fd, raw)
+    }
+    #[cfg(windows)]
     fn set_raw(&mut self, _raw: bool) -> Result<(), IoError> {
         Err(super::unimpl())
     }
+    #[cfg(unix)]
+    fn get_winsize(&mut self) -> Result<(int, int), IoError> {
+        super::tty::get_winsize(self.fd)
+    }
+    #[cfg(windows)]
     fn get_winsize(&mut self) -> Result<(int, int), IoError> {
         Err(super::unimpl())
     }
-    fn isatty(&self) -> bool { false }
+    fn isatty(&self) -> bool { unsafe { libc::isatty(self.fd) != 0 } }
 }
 
 impl Drop for FileDesc {
@@ -954,6 +972,22 @@ mod tests {
         }
     }
 
+    #[ignore(cfg(windows))]
+    #[test]
+    fn test_tty_ops_on_pipe() {
+        use std::rt::rtio::RtioTTY;
+
+        let os::Pipe { input, out } = os::pipe();
+        let mut reader = FileDesc::new(input, true);
+        let _writer = FileDesc::new(out, true);
+
+        assert!(!reader.isatty());
+        assert!(reader.get_winsize().is_err());
+        assert!(reader.set_raw(true).is_err());
+        // leaving raw mode is a no-op if raw mode was never entered
+        assert!(reader.set_raw(false).is_ok());
+    }
+
     #[ignore(cfg(windows))] // apparently windows doesn't like tmpfile
     #[test]
     fn test_cfile() {
@@ -42,10 +42,24 @@ pub use self::file::FileDesc;
 pub use self::process::Process;
 
 // Native I/O implementations
+pub mod addrinfo;
 pub mod file;
 pub mod process;
 pub mod net;
 
+#[cfg(unix)]
+#[path = "timer_unix.rs"]
+pub mod timer;
+
+#[cfg(unix)]
+mod timer_helper;
+
+#[cfg(unix)]
+pub mod signal;
+
+#[cfg(unix)]
+mod tty;
+
 type IoResult<T> = Result<T, IoError>;
 
 fn unimpl() -> IoError {
@@ -70,6 +84,7 @@ fn translate_error(errno: i32, detail: bool) -> IoError {
             libc::WSAECONNABORTED => (io::ConnectionAborted, "connection aborted"),
             libc::WSAEADDRNOTAVAIL => (io::ConnectionRefused, "address not available"),
             libc::WSAEADDRINUSE => (io::ConnectionRefused, "address in use"),
+            libc::WSAETIMEDOUT => (io::TimedOut, "operation timed out"),
 
             x => {
                 debug!("ignoring {}: {}", x, os::last_os_error());
@@ -85,6 +100,7 @@ fn translate_error(errno: i32, detail: bool) -> IoError {
             libc::EOF => (io::EndOfFile, "end of file"),
             libc::ECONNREFUSED => (io::ConnectionRefused, "connection refused"),
             libc::ECONNRESET => (io::ConnectionReset, "connection reset"),
+            libc::ENOENT => (io::FileNotFound, "no such file or directory"),
             libc::EPERM | libc::EACCES =>
                 (io::PermissionDenied, "permission denied"),
             libc::EPIPE => (io::BrokenPipe, "broken pipe"),
@@ -92,6 +108,7 @@ fn translate_error(errno: i32, detail: bool) -> IoError {
             libc::ECONNABORTED => (io::ConnectionAborted, "connection aborted"),
             libc::EADDRNOTAVAIL => (io::ConnectionRefused, "address not available"),
             libc::EADDRINUSE => (io::ConnectionRefused, "address in use"),
+            libc::ETIMEDOUT => (io::TimedOut, "operation timed out"),
 
             // These two constants can have the same value on some systems, but
             // different values on others, so we can't use a match clause
@@ -180,15 +197,25 @@ impl rtio::IoFactory for IoFactory {
     fn udp_bind(&mut self, addr: SocketAddr) -> IoResult<~RtioUdpSocket> {
         net::UdpSocket::bind(addr).map(|u| ~u as ~RtioUdpSocket)
     }
+    #[cfg(unix)]
+    fn unix_bind(&mut self, path: &CString) -> IoResult<~RtioUnixListener> {
+        net::unix::UnixListener::bind(path).map(|s| ~s as ~RtioUnixListener)
+    }
+    #[cfg(unix)]
+    fn unix_connect(&mut self, path: &CString) -> IoResult<~RtioPipe> {
+        net::unix::UnixStream::connect(path).map(|s| ~s as ~RtioPipe)
+    }
+    #[cfg(windows)]
     fn unix_bind(&mut self, _path: &CString) -> IoResult<~RtioUnixListener> {
         Err(unimpl())
     }
+    #[cfg(windows)]
     fn unix_connect(&mut self, _path: &CString) -> IoResult<~RtioPipe> {
         Err(unimpl())
     }
-    fn get_host_addresses(&mut self, _host: Option<&str>, _servname: Option<&str>,
-                          _hint: Option<ai::Hint>) -> IoResult<~[ai::Info]> {
-        Err(unimpl())
+    fn get_host_addresses(&mut self, host: Option<&str>, servname: Option<&str>,
+                          hint: Option<ai::Hint>) -> IoResult<~[ai::Info]> {
+        addrinfo::GetAddrInfoRequest::run(host, servname, hint)
     }
 
     // filesystem operations
@@ -248,6 +275,11 @@ impl rtio::IoFactory for IoFactory {
     }
 
     // misc
+    #[cfg(unix)]
+    fn timer_init(&mut self) -> IoResult<~RtioTimer> {
+        timer::Timer::new().map(|t| ~t as ~RtioTimer)
+    }
+    #[cfg(windows)]
     fn timer_init(&mut self) -> IoResult<~RtioTimer> {
         Err(unimpl())
     }
@@ -272,6 +304,12 @@ impl rtio::IoFactory for IoFactory {
             })
         }
     }
+    #[cfg(unix)]
+    fn signal(&mut self, signum: Signum, channel: SharedChan<Signum>)
+        -> IoResult<~RtioSignal> {
+        signal::Signal::new(signum, channel).map(|s| ~s as ~RtioSignal)
+    }
+    #[cfg(windows)]
     fn signal(&mut self, _signal: Signum, _channel: SharedChan<Signum>)
         -> IoResult<~RtioSignal> {
         Err(unimpl())
@@ -9,6 +9,7 @@
 // except according to those terms.
 
 use std::cast;
+use std::cmp;
 use std::io::net::ip;
 use std::io;
 use std::libc;
@@ -112,6 +113,94 @@ fn setsockopt<T>(fd: sock_t, opt: libc::c_int, val: libc::c_int,
     }
 }
 
+// Blocks until `fd` has data available to be read (or a connection to be
+// accepted), failing with `TimedOut` if that doesn't happen in time. This is a
+// no-op if there's no timeout.
+#[cfg(unix)]
+fn await_readable(fd: sock_t, timeout_ms: Option<u64>) -> IoResult<()> {
+    use std::i32;
+
+    let timeout_ms = match timeout_ms { Some(ms) => ms, None => return Ok(()) };
+    let mut pfd = imp::pollfd { fd: fd, events: imp::POLLIN, revents: 0 };
+    let timeout = cmp::min(timeout_ms, i32::max_value as u64) as libc::c_int;
+    match retry(|| unsafe { imp::poll(&mut pfd, 1, timeout) }) {
+        -1 => Err(super::last_error()),
+        0 => Err(io::standard_error(io::TimedOut)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(windows)]
+fn await_readable(fd: sock_t, timeout_ms: Option<u64>) -> IoResult<()> {
+    let timeout_ms = match timeout_ms { Some(ms) => ms, None => return Ok(()) };
+    let mut set = imp::fd_set {
+        fd_count: 1,
+        fd_array: [0, ..imp::FD_SETSIZE],
+    };
+    set.fd_array[0] = fd;
+    let null = 0 as *mut imp::fd_set;
+    let tv = imp::timeval {
+        tv_sec: (timeout_ms / 1000) as libc::c_long,
+        tv_usec: ((timeout_ms % 1000) * 1000) as libc::c_long,
+    };
+    match unsafe {
+        imp::select(1, &mut set, null, null, &tv)
+    } {
+        -1 => Err(super::last_error()),
+        0 => Err(io::standard_error(io::TimedOut)),
+        _ => Ok(()),
+    }
+}
+
+// Writes are bounded with SO_SNDTIMEO rather than polling because a blocking
+// send() can still block after poll() reports the socket as writable (if the
+// buffer being sent is larger than the space available).
+#[cfg(unix)]
+fn set_send_timeout(fd: sock_t, timeout_ms: Option<u64>) -> IoResult<()> {
+    // A zeroed timeval means "no timeout", so a timeout of zero is rounded up
+    // to the smallest one that can be expressed.
+    let tv = match timeout_ms {
+        None => libc::timeval { tv_sec: 0, tv_usec: 0 },
+        Some(0) => libc::timeval { tv_sec: 0, tv_usec: 1 },
+        Some(ms) => libc::timeval {
+            tv_sec: (ms / 1000) as libc::time_t,
+            tv_usec: ((ms % 1000) * 1000) as libc::suseconds_t,
+        },
+    };
+    setsockopt(fd, libc::SOL_SOCKET, libc::SO_SNDTIMEO, tv)
+}
+
+#[cfg(windows)]
+fn set_send_timeout(fd: sock_t, timeout_ms: Option<u64>) -> IoResult<()> {
+    // Windows takes the timeout in milliseconds, where zero means no timeout.
+    let ms = match timeout_ms {
+        None => 0,
+        Some(ms) => cmp::max(cmp::min(ms, 0xffffffff), 1) as libc::DWORD,
+    };
+    setsockopt(fd, libc::SOL_SOCKET, libc::SO_SNDTIMEO, ms)
+}
+
+// A send which hits SO_SNDTIMEO fails with EAGAIN on unix (windows reports
+// WSAETIMEDOUT instead), so that's translated to a timeout here.
+fn send_error(timeout_ms: Option<u64>) -> io::IoError {
+    let err = super::last_error();
+    match timeout_ms {
+        Some(..) if err.kind == io::ResourceUnavailable => {
+            io::standard_error(io::TimedOut)
+        }
+        _ => err,
+    }
+}
+
+// These are called SD_RECEIVE and SD_SEND on windows, but the values are the
+// same everywhere.
+static SHUT_RD: libc::c_int = 0;
+static SHUT_WR: libc::c_int = 1;
+
+fn shutdown(fd: sock_t, how: libc::c_int) -> IoResult<()> {
+    super::mkerr_libc(unsafe { libc::shutdown(fd, how) })
+}
+
 #[cfg(windows)] unsafe fn close(sock: sock_t) { libc::closesocket(sock); }
 #[cfg(unix)]    unsafe fn close(sock: sock_t) { libc::close(sock); }
 
@@ -134,8 +223,8 @@ fn sockname(fd: sock_t,
     return sockaddr_to_addr(&storage, len as uint);
 }
 
-fn sockaddr_to_addr(storage: &libc::sockaddr_storage,
-                    len: uint) -> IoResult<ip::SocketAddr> {
+pub fn sockaddr_to_addr(storage: &libc::sockaddr_storage,
+                        len: uint) -> IoResult<ip::SocketAddr> {
     match storage.ss_family as libc::c_int {
         libc::AF_INET => {
             assert!(len as uint >= mem::size_of::<libc::sockaddr_in>());
@@ -212,12 +301,61 @@ pub fn init() {
     }
 }
 
+#[cfg(unix)]
+mod imp {
+    use std::libc::{c_int, c_short};
+
+    #[cfg(target_os = "linux")]
+    #[cfg(target_os = "android")]
+    pub type nfds_t = ::std::libc::c_ulong;
+    #[cfg(target_os = "macos")]
+    #[cfg(target_os = "freebsd")]
+    pub type nfds_t = ::std::libc::c_uint;
+
+    pub static POLLIN: c_short = 0x0001;
+
+    pub struct pollfd {
+        fd: c_int,
+        events: c_short,
+        revents: c_short,
+    }
+
+    extern {
+        pub fn poll(fds: *mut pollfd, nfds: nfds_t, timeout: c_int) -> c_int;
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::libc::{c_int, c_long, c_uint, SOCKET};
+
+    pub static FD_SETSIZE: uint = 64;
+
+    pub struct fd_set {
+        fd_count: c_uint,
+        fd_array: [SOCKET, ..FD_SETSIZE],
+    }
+
+    pub struct timeval {
+        tv_sec: c_long,
+        tv_usec: c_long,
+    }
+
+    #[link(name = "ws2_32")]
+    extern "system" {
+        pub fn select(nfds: c_int, readfds: *mut fd_set, writefds: *mut fd_set,
+                      exceptfds: *mut fd_set, timeout: *timeval) -> c_int;
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // TCP streams
 ////////////////////////////////////////////////////////////////////////////////
 
 pub struct TcpStream {
     priv fd: sock_t,
+    priv read_timeout: Option<u64>,
+    priv write_timeout: Option<u64>,
 }
 
 impl TcpStream {
@@ -226,7 +364,7 @@ impl TcpStream {
             socket(addr, libc::SOCK_STREAM).and_then(|fd| {
                 let (addr, len) = addr_to_sockaddr(addr);
                 let addrp = &addr as *libc::sockaddr_storage;
-                let ret = TcpStream { fd: fd };
+                let ret = TcpStream::new(fd);
                 match retry(|| {
                     libc::connect(fd, addrp as *libc::sockaddr,
                                   len as libc::socklen_t)
@@ -238,6 +376,10 @@ impl TcpStream {
         }
     }
 
+    fn new(fd: sock_t) -> TcpStream {
+        TcpStream { fd: fd, read_timeout: None, write_timeout: None }
+    }
+
     pub fn fd(&self) -> sock_t { self.fd }
 
     fn set_nodelay(&mut self, nodelay: bool) -> IoResult<()> {
@@ -275,6 +417,9 @@ impl TcpStream {
 
 impl rtio::RtioTcpStream for TcpStream {
     fn read(&mut self, buf: &mut [u8]) -> IoResult<uint> {
+        match await_readable(self.fd, self.read_timeout) {
+            Ok(()) => {}, Err(e) => return Err(e),
+        }
         let ret = retry(|| {
             unsafe {
                 libc::recv(self.fd,
@@ -301,7 +446,7 @@ impl rtio::RtioTcpStream for TcpStream {
             }
         });
         if ret < 0 {
-            Err(super::last_error())
+            Err(send_error(self.write_timeout))
         } else {
             Ok(())
         }
@@ -321,6 +466,23 @@ impl rtio::RtioTcpStream for TcpStream {
     fn letdie(&mut self) -> IoResult<()> {
         self.set_keepalive(None)
     }
+    fn set_read_timeout(&mut self, timeout_ms: Option<u64>) -> IoResult<()> {
+        self.read_timeout = timeout_ms;
+        Ok(())
+    }
+    fn set_write_timeout(&mut self, timeout_ms: Option<u64>) -> IoResult<()> {
+        let ret = set_send_timeout(self.fd, timeout_ms);
+        if ret.is_ok() {
+            self.write_timeout = timeout_ms;
+        }
+        ret
+    }
+    fn close_write(&mut self) -> IoResult<()> {
+        shutdown(self.fd, SHUT_WR)
+    }
+    fn close_read(&mut self) -> IoResult<()> {
+        shutdown(self.fd, SHUT_RD)
+    }
 }
 
 impl rtio::RtioSocket for TcpStream {
@@ -362,7 +524,7 @@ impl TcpListener {
     pub fn native_listen(self, backlog: int) -> IoResult<TcpAcceptor> {
         match unsafe { libc::listen(self.fd, backlog as libc::c_int) } {
             -1 => Err(super::last_error()),
-            _ => Ok(TcpAcceptor { listener: self })
+            _ => Ok(TcpAcceptor { listener: self, timeout: None })
         }
     }
 }
@@ -385,12 +547,16 @@ impl Drop for TcpListener {
 
 pub struct TcpAcceptor {
     priv listener: TcpListener,
+    priv timeout: Option<u64>,
 }
 
 impl TcpAcceptor {
     pub fn fd(&self) -> sock_t { self.listener.fd }
 
     pub fn native_accept(&mut self) -> IoResult<TcpStream> {
+        match await_readable(self.fd(), self.timeout) {
+            Ok(()) => {}, Err(e) => return Err(e),
+        }
         unsafe {
             let mut storage: libc::sockaddr_storage = intrinsics::init();
             let storagep = &mut storage as *mut libc::sockaddr_storage;
@@ -402,7 +568,7 @@ impl TcpAcceptor {
                              &mut size as *mut libc::socklen_t) as libc::c_int
             }) as sock_t {
                 -1 => Err(super::last_error()),
-                fd => Ok(TcpStream { fd: fd })
+                fd => Ok(TcpStream::new(fd))
             }
         }
     }
@@ -421,6 +587,11 @@ impl rtio::RtioTcpAcceptor for TcpAcceptor {
 
     fn accept_simultaneously(&mut self) -> IoResult<()> { Ok(()) }
     fn dont_accept_simultaneously(&mut self) -> IoResult<()> { Ok(()) }
+
+    fn set_timeout(&mut self, timeout_ms: Option<u64>) -> IoResult<()> {
+        self.timeout = timeout_ms;
+        Ok(())
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -429,6 +600,8 @@ impl rtio::RtioTcpAcceptor for TcpAcceptor {
 
 pub struct UdpSocket {
     priv fd: sock_t,
+    priv read_timeout: Option<u64>,
+    priv write_timeout: Option<u64>,
 }
 
 impl UdpSocket {
@@ -437,7 +610,11 @@ impl UdpSocket {
             socket(addr, libc::SOCK_DGRAM).and_then(|fd| {
                 let (addr, len) = addr_to_sockaddr(addr);
                 let addrp = &addr as *libc::sockaddr_storage;
-                let ret = UdpSocket { fd: fd };
+                let ret = UdpSocket {
+                    fd: fd,
+                    read_timeout: None,
+                    write_timeout: None,
+                };
                 match libc::bind(fd, addrp as *libc::sockaddr,
                                  len as libc::socklen_t) {
                     -1 => Err(super::last_error()),
@@ -492,6 +669,9 @@ impl rtio::RtioSocket for UdpSocket {
 
 impl rtio::RtioUdpSocket for UdpSocket {
     fn recvfrom(&mut self, buf: &mut [u8]) -> IoResult<(uint, ip::SocketAddr)> {
+        match await_readable(self.fd, self.read_timeout) {
+            Ok(()) => {}, Err(e) => return Err(e),
+        }
         unsafe {
             let mut storage: libc::sockaddr_storage = intrinsics::init();
             let storagep = &mut storage as *mut libc::sockaddr_storage;
@@ -524,7 +704,7 @@ impl rtio::RtioUdpSocket for UdpSocket {
                              len as libc::socklen_t) as libc::c_int
             });
             match ret {
-                -1 => Err(super::last_error()),
+                -1 => Err(send_error(self.write_timeout)),
                 n if n as uint != buf.len() => {
                     Err(io::IoError {
                         kind: io::OtherIoError,
@@ -579,8 +759,261 @@ impl rtio::RtioUdpSocket for UdpSocket {
     fn ignore_broadcasts(&mut self) -> IoResult<()> {
         self.set_broadcast(false)
     }
+
+    fn set_read_timeout(&mut self, timeout_ms: Option<u64>) -> IoResult<()> {
+        self.read_timeout = timeout_ms;
+        Ok(())
+    }
+    fn set_write_timeout(&mut self, timeout_ms: Option<u64>) -> IoResult<()> {
+        let ret = set_send_timeout(self.fd, timeout_ms);
+        if ret.is_ok() {
+            self.write_timeout = timeout_ms;
+        }
+        ret
+    }
 }
 
 impl Drop for UdpSocket {
     fn drop(&mut self) { unsafe { close(self.fd) } }
 }
+
+////////////////////////////////////////////////////////////////////////////////
+// Unix domain sockets
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(unix)]
+pub mod unix {
+    use std::c_str::CString;
+    use std::cast;
+    use std::io;
+    use std::libc;
+    use std::mem;
+    use std::os;
+    use std::rt::rtio;
+    use std::unstable::intrinsics;
+
+    use io::{IoResult, retry};
+    use io::file::keep_going;
+    use super::{sock_t, wrlen, close, shutdown, SHUT_RD, SHUT_WR};
+
+    static AF_UNIX: libc::c_int = 1;
+
+    #[cfg(target_os = "linux")]
+    #[cfg(target_os = "android")]
+    struct sockaddr_un {
+        sun_family: libc::sa_family_t,
+        sun_path: [libc::c_char, ..108],
+    }
+
+    #[cfg(target_os = "macos")]
+    #[cfg(target_os = "freebsd")]
+    struct sockaddr_un {
+        sun_len: u8,
+        sun_family: libc::sa_family_t,
+        sun_path: [libc::c_char, ..104],
+    }
+
+    fn unix_socket(ty: libc::c_int) -> IoResult<sock_t> {
+        match unsafe { libc::socket(AF_UNIX, ty, 0) } {
+            -1 => Err(super::super::last_error()),
+            fd => Ok(fd),
+        }
+    }
+
+    fn addr_to_sockaddr_un(addr: &CString)
+        -> IoResult<(libc::sockaddr_storage, uint)>
+    {
+        // the sockaddr_un structure must fit inside of a sockaddr_storage
+        assert!(mem::size_of::<libc::sockaddr_storage>() >=
+                mem::size_of::<sockaddr_un>());
+        let mut storage: libc::sockaddr_storage = unsafe { intrinsics::init() };
+        let s: &mut sockaddr_un = unsafe { cast::transmute(&mut storage) };
+
+        // the path, including its nul terminator, must fit into sun_path
+        let bytes = addr.as_bytes();
+        if bytes.len() > s.sun_path.len() {
+            return Err(io::IoError {
+                kind: io::InvalidInput,
+                desc: "path must be smaller than SUN_LEN",
+                detail: None,
+            })
+        }
+        s.sun_family = AF_UNIX as libc::sa_family_t;
+        for (slot, &value) in s.sun_path.mut_iter().zip(bytes.iter()) {
+            *slot = value as libc::c_char;
+        }
+
+        let len = mem::size_of::<sockaddr_un>() - s.sun_path.len() +
+                  bytes.len();
+        return Ok((storage, len));
+    }
+
+    fn connect(addr: &CString, ty: libc::c_int) -> IoResult<sock_t> {
+        addr_to_sockaddr_un(addr).and_then(|(addr, len)| {
+            unix_socket(ty).and_then(|fd| {
+                let addrp = &addr as *libc::sockaddr_storage;
+                match retry(|| unsafe {
+                    libc::connect(fd, addrp as *libc::sockaddr,
+                                  len as libc::socklen_t)
+                }) {
+                    -1 => {
+                        let err = super::super::last_error();
+                        unsafe { close(fd); }
+                        Err(err)
+                    }
+                    _ => Ok(fd),
+                }
+            })
+        })
+    }
+
+    fn bind(addr: &CString, ty: libc::c_int) -> IoResult<sock_t> {
+        addr_to_sockaddr_un(addr).and_then(|(addr, len)| {
+            unix_socket(ty).and_then(|fd| {
+                let addrp = &addr as *libc::sockaddr_storage;
+                match unsafe {
+                    libc::bind(fd, addrp as *libc::sockaddr,
+                               len as libc::socklen_t)
+                } {
+                    -1 => {
+                        // libuv reports a missing parent directory as
+                        // EACCES for compatibility with windows, so do the
+                        // same thing here to behave the same under both
+                        // runtimes.
+                        let errno = match os::errno() as libc::c_int {
+                            libc::ENOENT => libc::EACCES,
+                            n => n,
+                        };
+                        let err = super::super::translate_error(errno, true);
+                        unsafe { close(fd); }
+                        Err(err)
+                    }
+                    _ => Ok(fd),
+                }
+            })
+        })
+    }
+
+    ////////////////////////////////////////////////////////////////////////////
+    // Unix Streams
+    ////////////////////////////////////////////////////////////////////////////
+
+    pub struct UnixStream {
+        priv fd: sock_t,
+    }
+
+    impl UnixStream {
+        pub fn connect(addr: &CString) -> IoResult<UnixStream> {
+            connect(addr, libc::SOCK_STREAM).map(|fd| UnixStream { fd: fd })
+        }
+
+        pub fn fd(&self) -> sock_t { self.fd }
+    }
+
+    impl rtio::RtioPipe for UnixStream {
+        fn read(&mut self, buf: &mut [u8]) -> IoResult<uint> {
+            let ret = retry(|| unsafe {
+                libc::recv(self.fd,
+                           buf.as_ptr() as *mut libc::c_void,
+                           buf.len() as wrlen,
+                           0) as libc::c_int
+            });
+            if ret == 0 {
+                Err(io::standard_error(io::EndOfFile))
+            } else if ret < 0 {
+                Err(super::super::last_error())
+            } else {
+                Ok(ret as uint)
+            }
+        }
+
+        fn write(&mut self, buf: &[u8]) -> IoResult<()> {
+            let ret = keep_going(buf, |buf, len| unsafe {
+                libc::send(self.fd,
+                           buf as *mut libc::c_void,
+                           len as wrlen,
+                           0) as i64
+            });
+            if ret < 0 {
+                Err(super::super::last_error())
+            } else {
+                Ok(())
+            }
+        }
+
+        fn close_write(&mut self) -> IoResult<()> {
+            shutdown(self.fd, SHUT_WR)
+        }
+
+        fn close_read(&mut self) -> IoResult<()> {
+            shutdown(self.fd, SHUT_RD)
+        }
+    }
+
+    impl Drop for UnixStream {
+        fn drop(&mut self) { unsafe { close(self.fd); } }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////
+    // Unix Listener
+    ////////////////////////////////////////////////////////////////////////////
+
+    pub struct UnixListener {
+        priv fd: sock_t,
+    }
+
+    impl UnixListener {
+        pub fn bind(addr: &CString) -> IoResult<UnixListener> {
+            bind(addr, libc::SOCK_STREAM).map(|fd| UnixListener { fd: fd })
+        }
+
+        pub fn fd(&self) -> sock_t { self.fd }
+
+        pub fn native_listen(self, backlog: int) -> IoResult<UnixAcceptor> {
+            match unsafe { libc::listen(self.fd, backlog as libc::c_int) } {
+                -1 => Err(super::super::last_error()),
+                _ => Ok(UnixAcceptor { listener: self })
+            }
+        }
+    }
+
+    impl rtio::RtioUnixListener for UnixListener {
+        fn listen(~self) -> IoResult<~rtio::RtioUnixAcceptor> {
+            self.native_listen(128).map(|a| ~a as ~rtio::RtioUnixAcceptor)
+        }
+    }
+
+    impl Drop for UnixListener {
+        fn drop(&mut self) { unsafe { close(self.fd); } }
+    }
+
+    pub struct UnixAcceptor {
+        priv listener: UnixListener,
+    }
+
+    impl UnixAcceptor {
+        pub fn fd(&self) -> sock_t { self.listener.fd }
+
+        pub fn native_accept(&mut self) -> IoResult<UnixStream> {
+            let mut storage: libc::sockaddr_storage =
+                unsafe { intrinsics::init() };
+            let storagep = &mut storage as *mut libc::sockaddr_storage;
+            let size = mem::size_of::<libc::sockaddr_storage>();
+            let mut size = size as libc::socklen_t;
+            match retry(|| unsafe {
+                libc::accept(self.fd(),
+                             storagep as *mut libc::sockaddr,
+                             &mut size as *mut libc::socklen_t) as libc::c_int
+            }) as sock_t {
+                -1 => Err(super::super::last_error()),
+                fd => Ok(UnixStream { fd: fd })
+            }
+        }
+    }
+
+    impl rtio::RtioUnixAcceptor for UnixAcceptor {
+        fn accept(&mut self) -> IoResult<~rtio::RtioPipe> {
+            self.native_accept().map(|s| ~s as ~rtio::RtioPipe)
+        }
+    }
+}
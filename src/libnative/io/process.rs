@@ -8,6 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::cmp;
 use std::io;
 use std::libc::{pid_t, c_void, c_int};
 use std::libc;
@@ -57,54 +58,60 @@ impl Process {
     ///         environment
     /// * cwd - an optionally specified current working directory of the child,
     ///         defaulting to the parent's current working directory
-    /// * stdin, stdout, stderr - These optionally specified file descriptors
-    ///     dictate where the stdin/out/err of the child process will go. If
-    ///     these are `None`, then this module will bind the input/output to an
-    ///     os pipe instead. This process takes ownership of these file
-    ///     descriptors, closing them upon destruction of the process.
+    /// * io - the file descriptors of the child process, starting at 0. Each
+    ///        entry may be ignored (closed in the child), inherited from a
+    ///        file descriptor of this process, or bound to a newly created os
+    ///        pipe. The returned vector contains the parent's end of each pipe
+    ///        which was created. Any of stdin/stdout/stderr which are not
+    ///        specified are closed in the child.
     pub fn spawn(config: p::ProcessConfig)
         -> Result<(Process, ~[Option<file::FileDesc>]), io::IoError>
     {
-        // right now we only handle stdin/stdout/stderr.
-        if config.io.len() > 3 {
+        // right now we only handle stdin/stdout/stderr on windows.
+        if cfg!(windows) && config.io.len() > 3 {
             return Err(super::unimpl());
         }
 
+        // Returns the file descriptor which the child should use at `idx`.
+        // The child's end of any pipe which is created is also added to
+        // `theirs` so it can be closed in the parent once the child is spawned.
         fn get_io(io: &[p::StdioContainer],
                   ret: &mut ~[Option<file::FileDesc>],
-                  idx: uint) -> (Option<os::Pipe>, c_int) {
-            if idx >= io.len() { return (None, -1); }
+                  theirs: &mut ~[c_int],
+                  idx: uint) -> c_int {
+            if idx >= io.len() { return -1; }
             ret.push(None);
             match io[idx] {
-                p::Ignored => (None, -1),
-                p::InheritFd(fd) => (None, fd),
+                p::Ignored => -1,
+                p::InheritFd(fd) => fd,
                 p::CreatePipe(readable, _writable) => {
                     let pipe = os::pipe();
-                    let (theirs, ours) = if readable {
+                    let (their_end, ours) = if readable {
                         (pipe.input, pipe.out)
                     } else {
                         (pipe.out, pipe.input)
                     };
                     ret[idx] = Some(file::FileDesc::new(ours, true));
-                    (Some(pipe), theirs)
+                    theirs.push(their_end);
+                    their_end
                 }
             }
         }
 
         let mut ret_io = ~[];
-        let (in_pipe, in_fd) = get_io(config.io, &mut ret_io, 0);
-        let (out_pipe, out_fd) = get_io(config.io, &mut ret_io, 1);
-        let (err_pipe, err_fd) = get_io(config.io, &mut ret_io, 2);
+        let mut fds = ~[];
+        let mut theirs = ~[];
+        for i in range(0, cmp::max(config.io.len(), 3)) {
+            fds.push(get_io(config.io, &mut ret_io, &mut theirs, i));
+        }
 
         let env = config.env.map(|a| a.to_owned());
         let cwd = config.cwd.map(|a| Path::new(a));
         let res = spawn_process_os(config.program, config.args, env,
-                                   cwd.as_ref(), in_fd, out_fd, err_fd);
+                                   cwd.as_ref(), fds);
 
         unsafe {
-            for pipe in in_pipe.iter() { libc::close(pipe.input); }
-            for pipe in out_pipe.iter() { libc::close(pipe.out); }
-            for pipe in err_pipe.iter() { libc::close(pipe.out); }
+            for &fd in theirs.iter() { libc::close(fd); }
         }
 
         match res {
@@ -184,8 +191,7 @@ struct SpawnProcessResult {
 fn spawn_process_os(prog: &str, args: &[~str],
                     env: Option<~[(~str, ~str)]>,
                     dir: Option<&Path>,
-                    in_fd: c_int, out_fd: c_int,
-                    err_fd: c_int) -> IoResult<SpawnProcessResult> {
+                    fds: &[c_int]) -> IoResult<SpawnProcessResult> {
     use std::libc::types::os::arch::extra::{DWORD, HANDLE, STARTUPINFO};
     use std::libc::consts::os::extra::{
         TRUE, FALSE,
@@ -203,6 +209,8 @@ fn spawn_process_os(prog: &str, args: &[~str],
 
     use std::mem;
 
+    let (in_fd, out_fd, err_fd) = (fds[0], fds[1], fds[2]);
+
     unsafe {
 
         let mut si = zeroed_startupinfo();
@@ -368,8 +376,7 @@ fn make_command_line(prog: &str, args: &[~str]) -> ~str {
 fn spawn_process_os(prog: &str, args: &[~str],
                     env: Option<~[(~str, ~str)]>,
                     dir: Option<&Path>,
-                    in_fd: c_int, out_fd: c_int,
-                    err_fd: c_int) -> IoResult<SpawnProcessResult> {
+                    fds: &[c_int]) -> IoResult<SpawnProcessResult> {
     use std::libc::funcs::posix88::unistd::{fork, dup2, close, chdir, execvp};
     use std::libc::funcs::bsd44::getdtablesize;
     use std::libc::c_ulong;
@@ -407,6 +414,19 @@ fn spawn_process_os(prog: &str, args: &[~str],
         assert_eq!(ret, 0);
     }
 
+    // Duplicates `fd` onto the lowest file descriptor which is not less than
+    // `min`.
+    unsafe fn dup_above(fd: c_int, min: c_int) -> c_int {
+        extern { fn fcntl(fd: c_int, cmd: c_int, arg: c_int) -> c_int; }
+        static F_DUPFD: c_int = 0;
+
+        let ret = retry(|| fcntl(fd, F_DUPFD, min));
+        if ret == -1 {
+            fail!("failure in fcntl(F_DUPFD): {}", os::last_os_error());
+        }
+        ret
+    }
+
     let pipe = os::pipe();
     let mut input = file::FileDesc::new(pipe.input, true);
     let mut output = file::FileDesc::new(pipe.out, true);
@@ -444,23 +464,31 @@ fn spawn_process_os(prog: &str, args: &[~str],
 
         rustrt::rust_unset_sigprocmask();
 
-        if in_fd == -1 {
-            libc::close(libc::STDIN_FILENO);
-        } else if retry(|| dup2(in_fd, 0)) == -1 {
-            fail!("failure in dup2(in_fd, 0): {}", os::last_os_error());
-        }
-        if out_fd == -1 {
-            libc::close(libc::STDOUT_FILENO);
-        } else if retry(|| dup2(out_fd, 1)) == -1 {
-            fail!("failure in dup2(out_fd, 1): {}", os::last_os_error());
-        }
-        if err_fd == -1 {
-            libc::close(libc::STDERR_FILENO);
-        } else if retry(|| dup2(err_fd, 2)) == -1 {
-            fail!("failure in dup3(err_fd, 2): {}", os::last_os_error());
+        // The file descriptors 0..n of the child are about to be overwritten,
+        // so anything that we still need which lives in that range is first
+        // moved out of the way. Otherwise a dup2 onto a low file descriptor
+        // could clobber a file descriptor that a later entry is supposed to
+        // inherit (this is the same dance that libuv does).
+        let n = fds.len() as c_int;
+        if output.fd() < n {
+            output = file::FileDesc::new(dup_above(output.fd(), n), true);
+            set_cloexec(output.fd());
+        }
+        let fds = fds.map(|&fd| {
+            if fd != -1 && fd < n { dup_above(fd, n) } else { fd }
+        });
+
+        for (i, &fd) in fds.iter().enumerate() {
+            let i = i as c_int;
+            if fd == -1 {
+                close(i);
+            } else if retry(|| dup2(fd, i)) == -1 {
+                fail!("failure in dup2({}, {}): {}", fd, i,
+                      os::last_os_error());
+            }
         }
         // close all other fds
-        for fd in range(3, getdtablesize()).invert() {
+        for fd in range(n, getdtablesize()).invert() {
             if fd != output.fd() {
                 close(fd as c_int);
             }
@@ -0,0 +1,438 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Signal handling for unix platforms
+//!
+//! Signals are delivered with the "self-pipe trick". A process-wide signal
+//! handler is installed for each signal that has at least one listener, and
+//! all the handler does is write the number of the signal that was received
+//! into a pipe (writing to a pipe is one of the few operations which are safe
+//! to perform inside of a signal handler). The write end of the pipe is
+//! nonblocking, so a burst of signals which fills the pipe drops bytes rather
+//! than blocking the handler forever.
+//!
+//! The other end of the pipe is owned by a lazily spawned helper thread which
+//! blocks reading the pipe one byte at a time. A nonzero byte is a signal
+//! number, and the helper forwards the corresponding `Signum` to every
+//! channel which is registered for it. A zero byte is written whenever a
+//! request has been enqueued for the helper. The helper drains its request
+//! port after every byte it reads, so a request is still seen if its zero
+//! byte was dropped because the pipe was full.
+//!
+//! Registering and unregistering a signal are both synchronous operations: the
+//! caller blocks until the helper has acknowledged the request. This means
+//! that a signal raised immediately after registration is guaranteed to be
+//! received, and that no more signals will be delivered to a channel once its
+//! `Signal` has been dropped.
+
+use std::cast;
+use std::comm::Data;
+use std::io::signal::Signum;
+use std::libc;
+use std::os;
+use std::rt::rtio;
+use std::rt;
+use std::sync::atomics;
+use std::unstable::intrinsics;
+use std::unstable::mutex::{Once, ONCE_INIT};
+
+use bookkeeping;
+use io::IoResult;
+use io::file::FileDesc;
+use task;
+
+pub struct Signal {
+    priv id: uint,
+}
+
+enum Req {
+    // Start delivering a signal to a channel, acking once the handler has
+    // been installed.
+    NewSignal(uint, Signum, SharedChan<Signum>, Chan<IoResult<()>>),
+
+    // Stop delivering signals for the given id, acking once the channel is
+    // no longer in use.
+    RemoveSignal(uint, Chan<()>),
+
+    // Shut down the helper thread, restoring all default signal handlers.
+    Shutdown,
+}
+
+// As with the timer helper thread, these are only written while the helper is
+// being booted or shut down, and are read-only otherwise. The write half of
+// the pipe must be a plain integer as it's read from the signal handler.
+static mut HELPER_CHAN: *mut SharedChan<Req> = 0 as *mut SharedChan<Req>;
+static mut HELPER_SIGNAL: libc::c_int = -1;
+
+impl Signal {
+    pub fn new(signum: Signum, chan: SharedChan<Signum>) -> IoResult<Signal> {
+        boot();
+
+        static mut ID: atomics::AtomicUint = atomics::INIT_ATOMIC_UINT;
+        let id = unsafe { ID.fetch_add(1, atomics::Relaxed) };
+        let (p, c) = Chan::new();
+        send(NewSignal(id, signum, chan, c));
+        p.recv().map(|()| Signal { id: id })
+    }
+}
+
+impl rtio::RtioSignal for Signal {}
+
+impl Drop for Signal {
+    fn drop(&mut self) {
+        let (p, c) = Chan::new();
+        send(RemoveSignal(self.id, c));
+        p.recv();
+    }
+}
+
+fn boot() {
+    static mut INIT: Once = ONCE_INIT;
+
+    unsafe {
+        INIT.doit(|| {
+            let (msgp, msgc) = SharedChan::new();
+            HELPER_CHAN = cast::transmute(~msgc);
+            let pipe = os::pipe();
+            set_nonblocking(pipe.out);
+            HELPER_SIGNAL = pipe.out;
+
+            let input = pipe.input;
+            do task::spawn {
+                // see the timer helper for why this doesn't count as a task
+                bookkeeping::decrement();
+                helper(input, msgp);
+            }
+
+            rt::at_exit(proc() { shutdown() });
+        })
+    }
+}
+
+fn send(req: Req) {
+    unsafe {
+        assert!(!HELPER_CHAN.is_null());
+        (*HELPER_CHAN).send(req);
+        FileDesc::new(HELPER_SIGNAL, false).inner_write([0]);
+    }
+}
+
+fn shutdown() {
+    // Same dance as the timer helper thread, wait for the helper to exit and
+    // then clean up after it.
+    bookkeeping::increment();
+    send(Shutdown);
+    bookkeeping::wait_for_other_tasks();
+
+    unsafe {
+        let _fd = FileDesc::new(HELPER_SIGNAL, true);
+        let _chan: ~SharedChan<Req> = cast::transmute(HELPER_CHAN);
+        HELPER_CHAN = 0 as *mut SharedChan<Req>;
+        HELPER_SIGNAL = -1;
+    }
+}
+
+fn set_nonblocking(fd: libc::c_int) {
+    unsafe {
+        let flags = imp::fcntl(fd, imp::F_GETFL, 0);
+        if flags == -1 ||
+           imp::fcntl(fd, imp::F_SETFL, flags | imp::O_NONBLOCK) == -1 {
+            fail!("failed to make the signal pipe nonblocking: {}",
+                  os::last_os_error());
+        }
+    }
+}
+
+extern fn signal_handler(signum: libc::c_int) {
+    let byte = signum as u8;
+    unsafe {
+        // write() may clobber errno, which the interrupted code could be
+        // about to look at.
+        let errno = *imp::errno_location();
+        libc::write(HELPER_SIGNAL, &byte as *u8 as *libc::c_void, 1);
+        *imp::errno_location() = errno;
+    }
+}
+
+fn set_handler(signum: Signum, handler: imp::sighandler_t) -> IoResult<()> {
+    let mut action: imp::sigaction = unsafe { intrinsics::init() };
+    action.sa_handler = handler;
+    // Interrupted system calls are restarted so that the rest of the program
+    // doesn't see spurious EINTR errors.
+    action.sa_flags = imp::SA_RESTART;
+    match unsafe {
+        imp::sigaction(signum as libc::c_int, &action,
+                       0 as *mut imp::sigaction)
+    } {
+        0 => Ok(()),
+        _ => Err(super::last_error()),
+    }
+}
+
+fn install(signum: Signum) -> IoResult<()> {
+    set_handler(signum, unsafe { cast::transmute(signal_handler) })
+}
+
+fn uninstall(signum: Signum) {
+    let _ = set_handler(signum, imp::SIG_DFL);
+}
+
+fn helper(input: libc::c_int, messages: Port<Req>) {
+    let mut input = FileDesc::new(input, true);
+    let mut active: ~[(uint, Signum, SharedChan<Signum>)] = ~[];
+    let mut buf = [0u8];
+
+    fn listening(active: &~[(uint, Signum, SharedChan<Signum>)],
+                 signum: Signum) -> bool {
+        active.iter().any(|&(_, s, _)| s == signum)
+    }
+
+    'outer: loop {
+        match input.inner_read(buf) {
+            Ok(1) => {}
+            // The write end is only closed once this thread has exited
+            Ok(..) => fail!("signal helper's pipe was closed"),
+            Err(e) => fail!("signal helper failed to read: {:?}", e),
+        }
+
+        // A nonzero byte is a signal which was just delivered
+        if buf[0] != 0 {
+            for &(_, signum, ref chan) in active.iter() {
+                if signum as u8 == buf[0] {
+                    chan.try_send(signum);
+                }
+            }
+        }
+
+        loop {
+            match messages.try_recv() {
+                Data(NewSignal(id, signum, chan, ack)) => {
+                    let res = if listening(&active, signum) {
+                        Ok(())
+                    } else {
+                        install(signum)
+                    };
+                    if res.is_ok() {
+                        active.push((id, signum, chan));
+                    }
+                    ack.send(res);
+                }
+
+                Data(RemoveSignal(id, ack)) => {
+                    match active.iter().position(|&(i, _, _)| i == id) {
+                        Some(i) => {
+                            let (_, signum, chan) = active.remove(i);
+                            drop(chan);
+                            if !listening(&active, signum) {
+                                uninstall(signum);
+                            }
+                        }
+                        None => {}
+                    }
+                    ack.send(());
+                }
+
+                Data(Shutdown) => {
+                    for &(_, signum, _) in active.iter() {
+                        uninstall(signum);
+                    }
+                    break 'outer
+                }
+
+                _ => break
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[cfg(target_os = "android")]
+mod imp {
+    use std::libc;
+
+    pub type sighandler_t = libc::size_t;
+
+    pub static SIG_DFL: sighandler_t = 0;
+
+    pub static F_GETFL: libc::c_int = 3;
+    pub static F_SETFL: libc::c_int = 4;
+    #[cfg(not(target_arch = "mips"))]
+    pub static O_NONBLOCK: libc::c_int = 0x800;
+    #[cfg(target_arch = "mips")]
+    pub static O_NONBLOCK: libc::c_int = 0x80;
+
+    pub static SA_RESTART: libc::c_int = 0x10000000;
+
+    #[cfg(target_os = "linux", not(target_arch = "mips"))]
+    pub struct sigaction {
+        sa_handler: sighandler_t,
+        sa_mask: sigset_t,
+        sa_flags: libc::c_int,
+        sa_restorer: *libc::c_void,
+    }
+
+    #[cfg(target_os = "linux", target_arch = "mips")]
+    pub struct sigaction {
+        sa_flags: libc::c_int,
+        sa_handler: sighandler_t,
+        sa_mask: sigset_t,
+        sa_restorer: *libc::c_void,
+        sa_resv: [libc::c_int, ..1],
+    }
+
+    #[cfg(target_os = "android")]
+    pub struct sigaction {
+        sa_handler: sighandler_t,
+        sa_mask: sigset_t,
+        sa_flags: libc::c_int,
+        sa_restorer: *libc::c_void,
+    }
+
+    // glibc reserves room for 1024 signals
+    #[cfg(target_os = "linux")]
+    pub struct sigset_t {
+        __val: [u32, ..32],
+    }
+
+    #[cfg(target_os = "android")]
+    pub type sigset_t = libc::c_ulong;
+
+    extern {
+        pub fn sigaction(signum: libc::c_int,
+                         act: *sigaction,
+                         oldact: *mut sigaction) -> libc::c_int;
+        pub fn fcntl(fd: libc::c_int, cmd: libc::c_int,
+                     arg: libc::c_int) -> libc::c_int;
+        #[link_name = "__errno_location"]
+        pub fn errno_location() -> *mut libc::c_int;
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[cfg(target_os = "freebsd")]
+mod imp {
+    use std::libc;
+
+    pub type sighandler_t = libc::size_t;
+
+    pub static SIG_DFL: sighandler_t = 0;
+
+    pub static F_GETFL: libc::c_int = 3;
+    pub static F_SETFL: libc::c_int = 4;
+    pub static O_NONBLOCK: libc::c_int = 0x4;
+
+    pub static SA_RESTART: libc::c_int = 0x0002;
+
+    #[cfg(target_os = "macos")]
+    pub struct sigaction {
+        sa_handler: sighandler_t,
+        sa_mask: sigset_t,
+        sa_flags: libc::c_int,
+    }
+
+    #[cfg(target_os = "macos")]
+    pub type sigset_t = u32;
+
+    #[cfg(target_os = "freebsd")]
+    pub struct sigaction {
+        sa_handler: sighandler_t,
+        sa_flags: libc::c_int,
+        sa_mask: sigset_t,
+    }
+
+    #[cfg(target_os = "freebsd")]
+    pub struct sigset_t {
+        bits: [u32, ..4],
+    }
+
+    extern {
+        pub fn sigaction(signum: libc::c_int,
+                         act: *sigaction,
+                         oldact: *mut sigaction) -> libc::c_int;
+        pub fn fcntl(fd: libc::c_int, cmd: libc::c_int,
+                     arg: libc::c_int) -> libc::c_int;
+        #[link_name = "__error"]
+        pub fn errno_location() -> *mut libc::c_int;
+    }
+}
+
+#[cfg(test, not(target_os = "android"))]
+mod test {
+    use std::io::signal;
+    use std::libc;
+
+    use super::Signal;
+
+    fn sigusr1() {
+        unsafe {
+            libc::funcs::posix88::signal::kill(libc::getpid(),
+                                               signal::User1 as libc::c_int);
+        }
+    }
+
+    #[test]
+    fn smoke() {
+        let (port, chan) = SharedChan::new();
+        let _signal = Signal::new(signal::User1, chan).unwrap();
+        sigusr1();
+        assert_eq!(port.recv(), signal::User1);
+    }
+
+    #[test]
+    fn two_listeners_one_signum() {
+        let (port1, chan1) = SharedChan::new();
+        let (port2, chan2) = SharedChan::new();
+        let _s1 = Signal::new(signal::User1, chan1).unwrap();
+        let _s2 = Signal::new(signal::User1, chan2).unwrap();
+        sigusr1();
+        assert_eq!(port1.recv(), signal::User1);
+        assert_eq!(port2.recv(), signal::User1);
+    }
+
+    #[test]
+    fn unregister() {
+        let (port1, chan1) = SharedChan::new();
+        let (port2, chan2) = SharedChan::new();
+        let _s1 = Signal::new(signal::User1, chan1).unwrap();
+        let s2 = Signal::new(signal::User1, chan2).unwrap();
+        drop(s2);
+        sigusr1();
+        assert_eq!(port1.recv(), signal::User1);
+        assert!(port2.recv_opt().is_none());
+    }
+
+    #[test]
+    fn burst_of_signals() {
+        // Far more signals than fit in the pipe if nobody reads from it; the
+        // handler must not block when it's full.
+        let (port, chan) = SharedChan::new();
+        let _signal = Signal::new(signal::User1, chan).unwrap();
+        for _ in range(0, 100000) {
+            sigusr1();
+        }
+        assert_eq!(port.recv(), signal::User1);
+    }
+
+    #[test]
+    fn closing_channel_during_drop_doesnt_kill_everything() {
+        let (port, chan) = SharedChan::new();
+        let signal = Signal::new(signal::User1, chan).unwrap();
+
+        let (done_port, done_chan) = Chan::new();
+        do spawn {
+            done_chan.send(port.recv_opt());
+        }
+
+        // when we drop the Signal we're going to destroy the channel, which
+        // must wake up the task on the other end
+        drop(signal);
+        assert!(done_port.recv().is_none());
+    }
+}
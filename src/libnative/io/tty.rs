@@ -0,0 +1,258 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Terminal control for unix platforms
+//!
+//! Raw mode is implemented the same way as libuv's `uv_tty_set_mode`: the
+//! terminal's attributes are saved when it's first put into raw mode, the
+//! attributes are then adjusted in the same manner as `cfmakeraw`, and the
+//! saved attributes are restored when raw mode is turned back off.
+//!
+//! The saved attributes are kept in a global table keyed by file descriptor
+//! because the same terminal is commonly opened multiple times (once for each
+//! of the standard streams), and raw mode applies to the terminal as a whole.
+
+use std::cast;
+use std::unstable::intrinsics;
+use std::unstable::mutex::{Mutex, MUTEX_INIT};
+
+use io::IoResult;
+use io::file::fd_t;
+
+static mut LOCK: Mutex = MUTEX_INIT;
+static mut SAVED: *mut ~[(fd_t, imp::termios)] =
+    0 as *mut ~[(fd_t, imp::termios)];
+
+pub fn set_raw(fd: fd_t, raw: bool) -> IoResult<()> {
+    unsafe {
+        LOCK.lock();
+        if SAVED.is_null() {
+            SAVED = cast::transmute(~(~[] as ~[(fd_t, imp::termios)]));
+        }
+        let ret = if raw {
+            enable(fd, &mut *SAVED)
+        } else {
+            disable(fd, &mut *SAVED)
+        };
+        LOCK.unlock();
+        return ret;
+    }
+
+    fn enable(fd: fd_t, saved: &mut ~[(fd_t, imp::termios)]) -> IoResult<()> {
+        let mut tmp: imp::termios = unsafe { intrinsics::init() };
+        if unsafe { imp::tcgetattr(fd, &mut tmp) } != 0 {
+            return Err(super::last_error())
+        }
+        if !saved.iter().any(|&(f, _)| f == fd) {
+            saved.push((fd, tmp));
+        }
+
+        tmp.c_iflag &= !(imp::BRKINT | imp::ICRNL | imp::INPCK | imp::ISTRIP |
+                         imp::IXON);
+        tmp.c_oflag |= imp::ONLCR;
+        tmp.c_cflag |= imp::CS8;
+        tmp.c_lflag &= !(imp::ECHO | imp::ICANON | imp::IEXTEN | imp::ISIG);
+        tmp.c_cc[imp::VMIN] = 1;
+        tmp.c_cc[imp::VTIME] = 0;
+
+        match unsafe { imp::tcsetattr(fd, imp::TCSADRAIN, &tmp) } {
+            0 => Ok(()),
+            _ => Err(super::last_error()),
+        }
+    }
+
+    fn disable(fd: fd_t, saved: &mut ~[(fd_t, imp::termios)]) -> IoResult<()> {
+        // A terminal which was never put into raw mode is already in "normal"
+        // mode, so there's nothing to do.
+        let i = match saved.iter().position(|&(f, _)| f == fd) {
+            Some(i) => i,
+            None => return Ok(()),
+        };
+        let (_, orig) = saved.remove(i);
+        match unsafe { imp::tcsetattr(fd, imp::TCSADRAIN, &orig) } {
+            0 => Ok(()),
+            _ => Err(super::last_error()),
+        }
+    }
+}
+
+/// Returns the (width, height) of the terminal attached to `fd`.
+pub fn get_winsize(fd: fd_t) -> IoResult<(int, int)> {
+    let mut size = imp::winsize {
+        ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0,
+    };
+    match unsafe { imp::ioctl(fd, imp::TIOCGWINSZ, &mut size) } {
+        -1 => Err(super::last_error()),
+        _ => Ok((size.ws_col as int, size.ws_row as int)),
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::libc::{c_int, c_uint, c_ulong};
+
+    pub type tcflag_t = c_uint;
+
+    pub static BRKINT: tcflag_t = 0o000002;
+    pub static INPCK: tcflag_t = 0o000020;
+    pub static ISTRIP: tcflag_t = 0o000040;
+    pub static ICRNL: tcflag_t = 0o000400;
+    pub static IXON: tcflag_t = 0o002000;
+    pub static ONLCR: tcflag_t = 0o000004;
+    pub static CS8: tcflag_t = 0o000060;
+    pub static ISIG: tcflag_t = 0o000001;
+    pub static ICANON: tcflag_t = 0o000002;
+    pub static ECHO: tcflag_t = 0o000010;
+    pub static IEXTEN: tcflag_t = 0o100000;
+
+    pub static VTIME: uint = 5;
+    pub static VMIN: uint = 6;
+    pub static TCSADRAIN: c_int = 1;
+    pub static TIOCGWINSZ: c_ulong = 0x5413;
+
+    pub struct termios {
+        c_iflag: tcflag_t,
+        c_oflag: tcflag_t,
+        c_cflag: tcflag_t,
+        c_lflag: tcflag_t,
+        c_line: u8,
+        c_cc: [u8, ..32],
+        c_ispeed: c_uint,
+        c_ospeed: c_uint,
+    }
+
+    pub struct winsize {
+        ws_row: u16,
+        ws_col: u16,
+        ws_xpixel: u16,
+        ws_ypixel: u16,
+    }
+
+    extern {
+        pub fn tcgetattr(fd: c_int, termios: *mut termios) -> c_int;
+        pub fn tcsetattr(fd: c_int, actions: c_int,
+                         termios: *termios) -> c_int;
+        pub fn ioctl(fd: c_int, req: c_ulong, ...) -> c_int;
+    }
+}
+
+// Older versions of bionic only provide tcgetattr/tcsetattr as inline
+// functions in the headers, so the corresponding ioctls are used directly
+// (along with the kernel's definition of `struct termios`).
+#[cfg(target_os = "android")]
+mod imp {
+    use std::libc::{c_int, c_uint, c_ulong};
+
+    pub type tcflag_t = c_uint;
+
+    pub static BRKINT: tcflag_t = 0o000002;
+    pub static INPCK: tcflag_t = 0o000020;
+    pub static ISTRIP: tcflag_t = 0o000040;
+    pub static ICRNL: tcflag_t = 0o000400;
+    pub static IXON: tcflag_t = 0o002000;
+    pub static ONLCR: tcflag_t = 0o000004;
+    pub static CS8: tcflag_t = 0o000060;
+    pub static ISIG: tcflag_t = 0o000001;
+    pub static ICANON: tcflag_t = 0o000002;
+    pub static ECHO: tcflag_t = 0o000010;
+    pub static IEXTEN: tcflag_t = 0o100000;
+
+    pub static VTIME: uint = 5;
+    pub static VMIN: uint = 6;
+    pub static TCSADRAIN: c_int = 1;
+    pub static TIOCGWINSZ: c_ulong = 0x5413;
+
+    static TCGETS: c_ulong = 0x5401;
+    static TCSETS: c_ulong = 0x5402;
+
+    pub struct termios {
+        c_iflag: tcflag_t,
+        c_oflag: tcflag_t,
+        c_cflag: tcflag_t,
+        c_lflag: tcflag_t,
+        c_line: u8,
+        c_cc: [u8, ..19],
+    }
+
+    pub struct winsize {
+        ws_row: u16,
+        ws_col: u16,
+        ws_xpixel: u16,
+        ws_ypixel: u16,
+    }
+
+    pub unsafe fn tcgetattr(fd: c_int, termios: *mut termios) -> c_int {
+        ioctl(fd, TCGETS, termios)
+    }
+
+    pub unsafe fn tcsetattr(fd: c_int, actions: c_int,
+                            termios: *termios) -> c_int {
+        // TCSETS, TCSETSW and TCSETSF are consecutive, as are TCSANOW,
+        // TCSADRAIN and TCSAFLUSH.
+        ioctl(fd, TCSETS + actions as c_ulong, termios)
+    }
+
+    extern {
+        pub fn ioctl(fd: c_int, req: c_ulong, ...) -> c_int;
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[cfg(target_os = "freebsd")]
+mod imp {
+    use std::libc;
+    use std::libc::{c_int, c_ulong};
+
+    #[cfg(target_os = "macos")]
+    pub type tcflag_t = libc::c_ulong;
+    #[cfg(target_os = "freebsd")]
+    pub type tcflag_t = libc::c_uint;
+
+    pub static BRKINT: tcflag_t = 0x00000002;
+    pub static INPCK: tcflag_t = 0x00000010;
+    pub static ISTRIP: tcflag_t = 0x00000020;
+    pub static ICRNL: tcflag_t = 0x00000100;
+    pub static IXON: tcflag_t = 0x00000200;
+    pub static ONLCR: tcflag_t = 0x00000002;
+    pub static CS8: tcflag_t = 0x00000300;
+    pub static ECHO: tcflag_t = 0x00000008;
+    pub static ISIG: tcflag_t = 0x00000080;
+    pub static ICANON: tcflag_t = 0x00000100;
+    pub static IEXTEN: tcflag_t = 0x00000400;
+
+    pub static VMIN: uint = 16;
+    pub static VTIME: uint = 17;
+    pub static TCSADRAIN: c_int = 1;
+    pub static TIOCGWINSZ: c_ulong = 0x40087468;
+
+    pub struct termios {
+        c_iflag: tcflag_t,
+        c_oflag: tcflag_t,
+        c_cflag: tcflag_t,
+        c_lflag: tcflag_t,
+        c_cc: [u8, ..20],
+        c_ispeed: tcflag_t,
+        c_ospeed: tcflag_t,
+    }
+
+    pub struct winsize {
+        ws_row: u16,
+        ws_col: u16,
+        ws_xpixel: u16,
+        ws_ypixel: u16,
+    }
+
+    extern {
+        pub fn tcgetattr(fd: c_int, termios: *mut termios) -> c_int;
+        pub fn tcsetattr(fd: c_int, actions: c_int,
+                         termios: *termios) -> c_int;
+        pub fn ioctl(fd: c_int, req: c_ulong, ...) -> c_int;
+    }
+}
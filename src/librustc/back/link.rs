@@ -46,7 +46,6 @@ use syntax::crateid::CrateId;
 
 #[deriving(Clone, Eq)]
 pub enum OutputType {
-    OutputTypeNone,
     OutputTypeBitcode,
     OutputTypeAssembly,
     OutputTypeLlvmAssembly,
@@ -105,17 +104,20 @@ pub mod write {
     use std::run;
     use std::str;
 
+    /// Optimizes the crate's module and writes each of `outputs`. Temporary
+    /// files, and the metadata object which is linked alongside the crate's
+    /// object file, are named after `obj_filename`.
     pub fn run_passes(sess: Session,
                       trans: &CrateTranslation,
-                      output_type: OutputType,
-                      output: &Path) {
+                      outputs: &[(OutputType, Path)],
+                      obj_filename: &Path) {
         let llmod = trans.module;
         let llcx = trans.context;
         unsafe {
             configure_llvm(sess);
 
             if sess.opts.save_temps {
-                output.with_extension("no-opt.bc").with_c_str(|buf| {
+                obj_filename.with_extension("no-opt.bc").with_c_str(|buf| {
                     llvm::LLVMWriteBitcodeToFile(llmod, buf);
                 })
             }
@@ -185,10 +187,10 @@ pub mod write {
             // Emit the bytecode if we're either saving our temporaries or
             // emitting an rlib. Whenever an rlib is created, the bytecode is
             // inserted into the archive in order to allow LTO against it.
-            let outputs = sess.outputs.borrow();
+            let crate_outputs = sess.outputs.borrow();
             if sess.opts.save_temps ||
-               outputs.get().iter().any(|&o| o == session::OutputRlib) {
-                output.with_extension("bc").with_c_str(|buf| {
+               crate_outputs.get().iter().any(|&o| o == session::OutputRlib) {
+                obj_filename.with_extension("bc").with_c_str(|buf| {
                     llvm::LLVMWriteBitcodeToFile(llmod, buf);
                 })
             }
@@ -198,7 +200,7 @@ pub mod write {
                      lto::run(sess, llmod, tm, trans.reachable));
 
                 if sess.opts.save_temps {
-                    output.with_extension("lto.bc").with_c_str(|buf| {
+                    obj_filename.with_extension("lto.bc").with_c_str(|buf| {
                         llvm::LLVMWriteBitcodeToFile(llmod, buf);
                     })
                 }
@@ -224,52 +226,71 @@ pub mod write {
             }
 
             time(sess.time_passes(), "codegen passes", (), |()| {
-                match output_type {
-                    OutputTypeNone => {}
-                    OutputTypeBitcode => {
-                        output.with_c_str(|buf| {
-                            llvm::LLVMWriteBitcodeToFile(llmod, buf);
-                        })
-                    }
-                    OutputTypeLlvmAssembly => {
-                        output.with_c_str(|output| {
-                            with_codegen(tm, llmod, |cpm| {
-                                llvm::LLVMRustPrintModule(cpm, llmod, output);
+                // Bitcode and IR are written before any codegen happens, as
+                // codegen passes modify the module which they are run on.
+                for &(output_type, ref path) in outputs.iter() {
+                    match output_type {
+                        OutputTypeBitcode => {
+                            path.with_c_str(|buf| {
+                                llvm::LLVMWriteBitcodeToFile(llmod, buf);
                             })
-                        })
-                    }
-                    OutputTypeAssembly => {
-                        with_codegen(tm, llmod, |cpm| {
-                            WriteOutputFile(sess, tm, cpm, llmod, output,
-                                            lib::llvm::AssemblyFile);
-                        });
-
-                        // If we're not using the LLVM assembler, this function
-                        // could be invoked specially with output_type_assembly,
-                        // so in this case we still want the metadata object
-                        // file.
-                        if sess.opts.output_type != OutputTypeAssembly {
-                            with_codegen(tm, trans.metadata_module, |cpm| {
-                                let out = output.with_extension("metadata.o");
-                                WriteOutputFile(sess, tm, cpm,
-                                                trans.metadata_module, &out,
-                                                lib::llvm::ObjectFile);
+                        }
+                        OutputTypeLlvmAssembly => {
+                            path.with_c_str(|output| {
+                                with_codegen(tm, llmod, |cpm| {
+                                    llvm::LLVMRustPrintModule(cpm, llmod, output);
+                                })
                             })
                         }
+                        OutputTypeAssembly | OutputTypeObject => {}
+                        OutputTypeExe => {
+                            sess.bug("linked output requested from LLVM");
+                        }
                     }
-                    OutputTypeExe | OutputTypeObject => {
+                }
+
+                // For the same reason, every assembly or object file but the
+                // last is generated from a fresh copy of the module.
+                let codegen_outputs: ~[&(OutputType, Path)] =
+                    outputs.iter().filter(|&&(ty, _)| {
+                        ty == OutputTypeAssembly || ty == OutputTypeObject
+                    }).collect();
+                let ncodegen = codegen_outputs.len();
+                for (i, &&(output_type, ref path)) in
+                        codegen_outputs.iter().enumerate() {
+                    let file_type = match output_type {
+                        OutputTypeAssembly => lib::llvm::AssemblyFile,
+                        _ => lib::llvm::ObjectFile,
+                    };
+                    if i + 1 < ncodegen {
+                        let copy = llvm::LLVMRustCloneModule(llmod);
+                        with_codegen(tm, copy, |cpm| {
+                            WriteOutputFile(sess, tm, cpm, copy, path,
+                                            file_type);
+                        });
+                        llvm::LLVMDisposeModule(copy);
+                    } else {
                         with_codegen(tm, llmod, |cpm| {
-                            WriteOutputFile(sess, tm, cpm, llmod, output,
-                                            lib::llvm::ObjectFile);
+                            WriteOutputFile(sess, tm, cpm, llmod, path,
+                                            file_type);
                         });
-                        with_codegen(tm, trans.metadata_module, |cpm| {
-                            let out = output.with_extension("metadata.o");
-                            WriteOutputFile(sess, tm, cpm,
-                                            trans.metadata_module, &out,
-                                            lib::llvm::ObjectFile);
-                        })
                     }
                 }
+
+                // The metadata object is needed whenever the crate's object
+                // file is, even if that object is later assembled from an
+                // assembly file by the system assembler.
+                let needs_metadata = sess.opts.output_types.iter().any(|&ty| {
+                    ty == OutputTypeObject || ty == OutputTypeExe
+                });
+                if needs_metadata {
+                    with_codegen(tm, trans.metadata_module, |cpm| {
+                        let out = obj_filename.with_extension("metadata.o");
+                        WriteOutputFile(sess, tm, cpm,
+                                        trans.metadata_module, &out,
+                                        lib::llvm::ObjectFile);
+                    })
+                }
             });
 
             llvm::LLVMRustDisposeTargetMachine(tm);
@@ -777,8 +798,11 @@ pub fn link_binary(sess: Session,
     }
 
     // Remove the temporary object file and metadata if we aren't saving temps
+    // and the object file wasn't requested as an output in its own right
     if !sess.opts.save_temps {
-        fs::unlink(obj_filename);
+        if !sess.opts.output_types.contains(&OutputTypeObject) {
+            fs::unlink(obj_filename);
+        }
         fs::unlink(&obj_filename.with_extension("metadata.o"));
     }
 
@@ -920,7 +944,8 @@ fn link_rlib(sess: Session,
             // into the archive.
             let bc = obj_filename.with_extension("bc");
             a.add_file(&bc, false);
-            if !sess.opts.save_temps {
+            if !sess.opts.save_temps &&
+               !sess.opts.output_types.contains(&OutputTypeBitcode) {
                 fs::unlink(&bc);
             }
 
@@ -0,0 +1,217 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The registry of stable error codes
+//!
+//! Errors which are reported with `span_err_with_code` carry one of the codes
+//! below, which is displayed alongside the message. Each code has a long-form
+//! explanation which is printed by `rustc --explain CODE`. Codes are never
+//! reused: if an error is removed from the compiler its code is retired along
+//! with it, and new errors always receive the next unused number.
+
+static DIAGNOSTICS: &'static [(&'static str, &'static str)] = &[
+
+("E0001", r##"
+A `use` declaration named a path which doesn't exist.
+
+    use std::hashmap::HashMapp; // error: unresolved import
+
+Check the spelling of each component of the path. Paths in `use` declarations
+are relative to the crate root, not to the current module, so a module declared
+next to the current one must be named as `use super::sibling::Item` or with its
+full path from the root.
+"##),
+
+("E0002", r##"
+A name was used which isn't in scope.
+
+    fn main() {
+        let x = 1;
+        println!("{}", y); // error: unresolved name `y`.
+    }
+
+The name may be misspelled, it may be declared in a module which hasn't been
+imported with `use`, or it may be a local variable which is declared later on
+or in a different block.
+"##),
+
+("E0003", r##"
+A constant expression referred to a local variable or other runtime value.
+
+    fn f(n: uint) {
+        static LIMIT: uint = n; // error
+    }
+
+The value of a `static` item is computed at compile time, so it can only refer
+to literals, other statics and enum variants. If the value is only known at
+runtime, use a `let` binding instead.
+"##),
+
+("E0004", r##"
+A function declared inside of another function tried to use one of the outer
+function's local variables.
+
+    fn outer() {
+        let x = 1;
+        fn inner() -> int { x } // error
+    }
+
+Nested `fn` items are not closures and have no access to the environment they
+are declared in. Either pass the value in as an argument, or use a closure
+instead, which can capture the variables around it:
+
+    fn outer() {
+        let x = 1;
+        let inner = || x;
+    }
+"##),
+
+("E0005", r##"
+The same identifier was bound more than once in a single pattern.
+
+    fn main() {
+        let (a, a) = (1, 2); // error
+    }
+
+Each binding in a pattern must have a distinct name. If two parts of the value
+are expected to be equal, bind them to different names and compare them with a
+guard instead:
+
+    match pair {
+        (a, b) if a == b => { /* ... */ }
+        _ => {}
+    }
+"##),
+
+("E0006", r##"
+A `break` or `loop` expression referred to a label which isn't declared by any
+enclosing loop.
+
+    fn main() {
+        loop {
+            break 'outer; // error: use of undeclared label `'outer`
+        }
+    }
+
+Labels are declared by prefixing a loop with them, e.g. `'outer: loop { ... }`,
+and are only visible inside of the body of that loop.
+"##),
+
+("E0007", r##"
+An expression's type is different from the type which it was required to have.
+
+    fn main() {
+        let x: int = "not an int"; // error: mismatched types
+    }
+
+The same error is reported when the arms of a `match` or the branches of an
+`if` produce different types, and when the signature of a method in an `impl`
+doesn't agree with its declaration in the trait. The message includes the type
+which was expected and the type which was found.
+"##),
+
+("E0008", r##"
+A function was called with the wrong number of arguments.
+
+    fn add(a: int, b: int) -> int { a + b }
+
+    fn main() {
+        add(1); // error: this function takes 2 parameters but 1 parameter
+                // was supplied
+    }
+
+Rust has no optional or default arguments. When a closure is passed with `do`
+or `for` sugar, it counts towards the function's arguments as well.
+"##),
+
+("E0009", r##"
+A value was used after it was moved somewhere else.
+
+    fn main() {
+        let a = ~1;
+        let b = a;
+        println!("{}", *a); // error: use of moved value: `a`
+    }
+
+Values of types which aren't implicitly copyable, such as owned boxes, are
+moved when they're assigned or passed by value, after which the original
+variable can no longer be used. Borrow the value instead of moving it (`let b
+= &a;`), or make an explicit copy with `a.clone()`.
+"##),
+
+("E0010", r##"
+A variable was used before it was definitely assigned a value.
+
+    fn main() {
+        let x: int;
+        println!("{}", x); // error: use of possibly uninitialized value: `x`
+    }
+
+Every path through the program which reaches a use of a variable must assign
+to it first. This commonly happens when only some branches of an `if` or
+`match` assign to the variable.
+"##),
+
+("E0011", r##"
+A value was borrowed as mutable while a previous mutable borrow of it was still
+in effect.
+
+    fn main() {
+        let mut v = ~[1, 2, 3];
+        let a = &mut v;
+        let b = &mut v; // error: cannot borrow `v` as mutable more than once
+                        // at a time
+    }
+
+A mutable borrow guarantees exclusive access for as long as it lasts, which is
+usually until the end of the block which holds the reference. Limit the scope
+of the first borrow, for example by putting it in a block of its own, so that it
+ends before the second begins.
+"##),
+
+("E0012", r##"
+A variable which wasn't declared as mutable was assigned to a second time.
+
+    fn main() {
+        let x = 1;
+        x = 2; // error: re-assignment of immutable variable `x`
+    }
+
+Declare the variable as `let mut x` if it needs to change.
+"##),
+
+];
+
+/// Returns the long-form explanation of an error code, if it's registered
+pub fn explain(code: &str) -> Option<&'static str> {
+    DIAGNOSTICS.iter().find(|&&(c, _)| c == code).map(|&(_, desc)| desc)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DIAGNOSTICS, explain};
+
+    #[test]
+    fn codes_are_well_formed_and_unique() {
+        for (i, &(code, desc)) in DIAGNOSTICS.iter().enumerate() {
+            assert_eq!(code.len(), 5);
+            assert!(code.starts_with("E"));
+            assert!(code.slice_from(1).chars().all(|c| c.is_digit()));
+            assert!(!desc.trim().is_empty());
+            assert!(!DIAGNOSTICS.slice_from(i + 1).iter().any(|&(c, _)| c == code));
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        assert!(explain("E0001").is_some());
+        assert!(explain("E9999").is_none());
+    }
+}
@@ -23,6 +23,7 @@ use metadata::cstore::CStore;
 use metadata::creader::Loader;
 use metadata;
 use middle::{trans, freevars, kind, ty, typeck, lint, astencode, reachable};
+use middle::{borrowck, cfg, save_analysis};
 use middle;
 use util::common::time;
 use util::ppaux;
@@ -37,6 +38,7 @@ use std::vec;
 use extra::getopts::groups::{optopt, optmulti, optflag, optflagopt};
 use extra::getopts;
 use syntax::ast;
+use syntax::ast_map;
 use syntax::abi;
 use syntax::attr;
 use syntax::attr::{AttrMetaMethods};
@@ -46,6 +48,8 @@ use syntax::ext::base::CrateLoader;
 use syntax::parse;
 use syntax::parse::token;
 use syntax::print::{pp, pprust};
+use syntax::visit;
+use syntax::visit::Visitor;
 use syntax;
 
 pub enum PpMode {
@@ -53,7 +57,8 @@ pub enum PpMode {
     PpmExpanded,
     PpmTyped,
     PpmIdentified,
-    PpmExpandedIdentified
+    PpmExpandedIdentified,
+    PpmFlowGraph(~str),
 }
 
 /**
@@ -143,7 +148,7 @@ pub enum Input {
 
 pub fn phase_1_parse_input(sess: Session, cfg: ast::CrateConfig, input: &Input)
     -> ast::Crate {
-    time(sess.time_passes(), "parsing", (), |_| {
+    let crate = time(sess.time_passes(), "parsing", (), |_| {
         match *input {
             FileInput(ref file) => {
                 parse::parse_crate_from_file(&(*file), cfg.clone(), sess.parse_sess)
@@ -153,7 +158,15 @@ pub fn phase_1_parse_input(sess: Session, cfg: ast::CrateConfig, input: &Input)
                     anon_src(), src, cfg.clone(), sess.parse_sess)
             }
         }
-    })
+    });
+
+    // Lint flags which don't name a built-in lint can only name a lint from a
+    // plugin. If no plugins are loaded they're all unknown, so that's reported
+    // now rather than waiting for expansion.
+    if !lint::loads_syntax_crates(&crate) {
+        lint::check_plugin_lint_opts(sess);
+    }
+    crate
 }
 
 // For continuing compilation after a parsed crate has been
@@ -176,6 +189,8 @@ pub fn phase_2_configure_and_expand(sess: Session,
     time(time_passes, "gated feature checking", (), |_|
          front::feature_gate::check_crate(sess, &crate));
 
+    let loads_syntax_crates = lint::loads_syntax_crates(&crate);
+
     // strip before expansion to allow macros to depend on
     // configuration variables e.g/ in
     //
@@ -200,6 +215,11 @@ pub fn phase_2_configure_and_expand(sess: Session,
     // dump the syntax-time crates
     sess.cstore.reset();
 
+    // all the lints which plugins register are known now
+    if loads_syntax_crates {
+        lint::check_plugin_lint_opts(sess);
+    }
+
     // strip again, in case expansion added anything with a #[cfg].
     crate = time(time_passes, "configuration 2", crate, |crate|
                  front::config::strip_unconfigured_items(crate));
@@ -220,6 +240,8 @@ pub struct CrateAnalysis {
     public_items: middle::privacy::PublicItems,
     ty_cx: ty::ctxt,
     maps: astencode::Maps,
+    moves_map: middle::moves::MovesMap,
+    moved_variables_set: middle::moves::MovedVariablesSet,
     reachable: @RefCell<HashSet<ast::NodeId>>
 }
 
@@ -261,6 +283,11 @@ pub fn phase_3_run_analysis_passes(sess: Session,
             syntax::ext::registrar::find_macro_registrar(
                 sess.span_diagnostic, crate)));
 
+    sess.lint_registrar_fn.with_mut(|r| *r =
+        time(time_passes, "looking for lint registrar", (), |_|
+            syntax::ext::registrar::find_lint_registrar(
+                sess.span_diagnostic, crate)));
+
     let freevars = time(time_passes, "freevar finding", (), |_|
                         freevars::annotate_freevars(def_map, crate));
 
@@ -344,6 +371,8 @@ pub fn phase_3_run_analysis_passes(sess: Session,
             vtable_map: vtable_map,
             capture_map: capture_map
         },
+        moves_map: moves_map,
+        moved_variables_set: moved_variables_set,
         reachable: reachable_map
     }
 }
@@ -368,33 +397,55 @@ pub fn phase_4_translate_to_llvm(sess: Session,
                                   &outputs.obj_filename))
 }
 
-/// Run LLVM itself, producing a bitcode file, assembly file or object file
-/// as a side effect.
+/// Run LLVM itself, producing whichever of bitcode, LLVM assembly, assembly
+/// and object files were requested as a side effect.
 pub fn phase_5_run_llvm_passes(sess: Session,
                                trans: &CrateTranslation,
                                outputs: &OutputFilenames) {
+    let output_types = sess.opts.output_types.as_slice();
+    let needs_object = output_types.iter().any(|&ty| {
+        ty == link::OutputTypeObject || ty == link::OutputTypeExe
+    });
 
-    if sess.no_integrated_as() {
-        let output_type = link::OutputTypeAssembly;
+    // The object file is always written to `obj_filename`, whether it was
+    // requested in its own right or is only needed for linking
+    let mut emit = ~[];
+    for &ty in output_types.iter() {
+        match ty {
+            link::OutputTypeObject | link::OutputTypeExe => {}
+            _ => emit.push((ty, outputs.path(ty)))
+        }
+    }
+
+    if needs_object && sess.no_integrated_as() {
         let asm_filename = outputs.obj_filename.with_extension("s");
+        let keep_asm = output_types.contains(&link::OutputTypeAssembly);
+        if !keep_asm {
+            emit.push((link::OutputTypeAssembly, asm_filename.clone()));
+        }
 
         time(sess.time_passes(), "LLVM passes", (), |_|
             link::write::run_passes(sess,
                                     trans,
-                                    output_type,
-                                    &asm_filename));
+                                    emit.as_slice(),
+                                    &outputs.obj_filename));
 
         link::write::run_assembler(sess, &asm_filename, &outputs.obj_filename);
 
-        // Remove assembly source, unless --save-temps was specified
-        if !sess.opts.save_temps {
+        // Remove assembly source, unless --save-temps was specified or it was
+        // requested as an output
+        if !sess.opts.save_temps && !keep_asm {
             fs::unlink(&asm_filename);
         }
     } else {
+        if needs_object {
+            emit.push((link::OutputTypeObject, outputs.obj_filename.clone()));
+        }
+
         time(sess.time_passes(), "LLVM passes", (), |_|
             link::write::run_passes(sess,
                                     trans,
-                                    sess.opts.output_type,
+                                    emit.as_slice(),
                                     &outputs.obj_filename));
     }
 }
@@ -437,7 +488,7 @@ pub fn stop_after_phase_2(sess: Session) -> bool {
 }
 
 pub fn stop_after_phase_5(sess: Session) -> bool {
-    if sess.opts.output_type != link::OutputTypeExe {
+    if !sess.opts.output_types.contains(&link::OutputTypeExe) {
         debug!("not building executable, returning early from compile_input");
         return true;
     }
@@ -495,6 +546,26 @@ fn write_out_deps(sess: Session, input: &Input, outputs: &OutputFilenames, crate
     }
 }
 
+/// Write the results of name resolution and type checking to
+/// `<crate name>.analysis.json` next to the object file, if requested with
+/// --save-analysis
+pub fn phase_save_analysis(sess: Session, crate: &ast::Crate,
+                           analysis: &CrateAnalysis, outputs: &OutputFilenames) {
+    if !sess.opts.save_analysis { return; }
+
+    let lm = link::build_link_meta(sess, crate.attrs, &outputs.obj_filename,
+                                   &mut ::util::sha2::Sha256::new());
+    let filename = outputs.obj_filename.with_filename(
+        format!("{}.analysis.json", lm.crateid.name));
+
+    time(sess.time_passes(), "saving analysis", (), |_| {
+        let mut file = io::File::create(&filename);
+        save_analysis::dump_crate(analysis.ty_cx, analysis.maps.method_map,
+                                  crate, lm.crateid.name,
+                                  &mut file as &mut Writer);
+    });
+}
+
 pub fn compile_input(sess: Session, cfg: ast::CrateConfig, input: &Input,
                      outdir: &Option<Path>, output: &Option<Path>) {
     // We need nested scopes here, because the intermediate results can keep
@@ -515,6 +586,7 @@ pub fn compile_input(sess: Session, cfg: ast::CrateConfig, input: &Input,
         if stop_after_phase_2(sess) { return; }
 
         let analysis = phase_3_run_analysis_passes(sess, &expanded_crate, ast_map);
+        phase_save_analysis(sess, &expanded_crate, &analysis, outputs);
         if stop_after_phase_3(sess) { return; }
         let trans = phase_4_translate_to_llvm(sess, expanded_crate,
                                               &analysis, outputs);
@@ -592,7 +664,7 @@ pub fn pretty_print_input(sess: Session,
     let crate = phase_1_parse_input(sess, cfg.clone(), input);
 
     let (crate, ast_map, is_expanded) = match ppm {
-        PpmExpanded | PpmExpandedIdentified | PpmTyped => {
+        PpmExpanded | PpmExpandedIdentified | PpmTyped | PpmFlowGraph(..) => {
             let loader = &mut Loader::new(sess);
             let (crate, ast_map) = phase_2_configure_and_expand(sess, cfg, loader, crate);
             (crate, Some(ast_map), true)
@@ -600,6 +672,16 @@ pub fn pretty_print_input(sess: Session,
         _ => (crate, None, false)
     };
 
+    match ppm {
+        PpmFlowGraph(ref name) => {
+            let ast_map = ast_map.expect("--pretty=flowgraph missing ast_map");
+            let analysis = phase_3_run_analysis_passes(sess, &crate, ast_map);
+            print_flowgraph(sess, &analysis, &crate, *name);
+            return;
+        }
+        _ => {}
+    }
+
     let annotation = match ppm {
         PpmIdentified | PpmExpandedIdentified => {
             @IdentifiedAnnotation {
@@ -630,6 +712,74 @@ pub fn pretty_print_input(sess: Session,
                         is_expanded);
 }
 
+// Finds the fn item or method whose path is `name`
+struct FnFinder<'a> {
+    tcx: ty::ctxt,
+    name: &'a str,
+    found: Option<(ast::P<ast::FnDecl>, ast::P<ast::Block>)>,
+}
+
+impl<'a> Visitor<()> for FnFinder<'a> {
+    fn visit_fn(&mut self, fk: &visit::FnKind, decl: &ast::FnDecl,
+                body: &ast::Block, sp: codemap::Span, id: ast::NodeId, _: ()) {
+        let itr = token::get_ident_interner();
+        match self.tcx.items.find(id) {
+            Some(ast_map::NodeItem(item, path)) => {
+                match item.node {
+                    ast::ItemFn(decl, _, _, _, body) => {
+                        let path = ast_map::path_ident_to_str(path, item.ident, itr);
+                        if self.name == path {
+                            self.found = Some((decl, body));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some(ast_map::NodeMethod(m, _, path)) => {
+                let path = ast_map::path_ident_to_str(path, m.ident, itr);
+                if self.name == path {
+                    self.found = Some((m.decl, m.body));
+                }
+            }
+            _ => {}
+        }
+
+        visit::walk_fn(self, fk, decl, body, sp, id, ());
+    }
+}
+
+// Writes the control-flow graph of the fn named `name` to stdout
+fn print_flowgraph(sess: Session,
+                   analysis: &CrateAnalysis,
+                   crate: &ast::Crate,
+                   name: &str) {
+    let tcx = analysis.ty_cx;
+    let mut finder = FnFinder { tcx: tcx, name: name, found: None };
+    visit::walk_crate(&mut finder, crate, ());
+    let (decl, body) = match finder.found {
+        Some(fn_parts) => fn_parts,
+        None => sess.fatal(format!("no fn or method named `{}` was found", name)),
+    };
+
+    let method_map = analysis.maps.method_map;
+    let cfg = cfg::CFG::new(tcx, method_map, body);
+    let loans = sess.debugging_opt(session::flowgraph_print_loans);
+    let moves = sess.debugging_opt(session::flowgraph_print_moves);
+
+    let mut stdout = io::stdout();
+    let out = &mut stdout as &mut io::Writer;
+    if loans || moves {
+        let (bccx, data) = borrowck::build_borrowck_dataflow_data_for_fn(
+            tcx, method_map, analysis.moves_map, analysis.moved_variables_set,
+            analysis.maps.capture_map, decl, body);
+        cfg::graphviz::write_cfg(out, tcx, name, &cfg, |id| {
+            data.describe_entry(&bccx, id, loans, moves)
+        });
+    } else {
+        cfg::graphviz::write_cfg(out, tcx, name, &cfg, |_| ~[]);
+    }
+}
+
 pub fn get_os(triple: &str) -> Option<abi::Os> {
     for &(name, os) in os_names.iter() {
         if triple.contains(name) { return Some(os) }
@@ -737,6 +887,7 @@ pub fn build_session_options(binary: ~str,
     let lint_levels = [lint::allow, lint::warn,
                        lint::deny, lint::forbid];
     let mut lint_opts = ~[];
+    let mut plugin_lint_opts = ~[];
     let lint_dict = lint::get_lint_dict();
     for level in lint_levels.iter() {
         let level_name = lint::level_to_str(*level);
@@ -749,8 +900,10 @@ pub fn build_session_options(binary: ~str,
             let lint_name = lint_name.replace("-", "_");
             match lint_dict.find_equiv(&lint_name) {
               None => {
-                early_error(demitter, format!("unknown {} flag: {}",
-                                           level_name, lint_name));
+                // This may be the name of a lint which is registered by a
+                // plugin, so it's checked by `lint::check_plugin_lint_opts`
+                // once it's known which plugins the crate loads.
+                plugin_lint_opts.push((lint_name, *level));
               }
               Some(lint) => {
                 lint_opts.push((lint.lint, *level));
@@ -778,19 +931,38 @@ pub fn build_session_options(binary: ~str,
         unsafe { llvm::LLVMSetDebug(1); }
     }
 
-    let output_type =
-        if parse_only || no_trans {
-            link::OutputTypeNone
-        } else if matches.opt_present("S") &&
-                  matches.opt_present("emit-llvm") {
-            link::OutputTypeLlvmAssembly
+    let mut output_types = ~[];
+    if !parse_only && !no_trans {
+        if matches.opt_present("S") &&
+           matches.opt_present("emit-llvm") {
+            output_types.push(link::OutputTypeLlvmAssembly)
         } else if matches.opt_present("S") {
-            link::OutputTypeAssembly
+            output_types.push(link::OutputTypeAssembly)
         } else if matches.opt_present("c") {
-            link::OutputTypeObject
+            output_types.push(link::OutputTypeObject)
         } else if matches.opt_present("emit-llvm") {
-            link::OutputTypeBitcode
-        } else { link::OutputTypeExe };
+            output_types.push(link::OutputTypeBitcode)
+        }
+        for list in matches.opt_strs("emit").iter() {
+            for part in list.split(',') {
+                let output_type = match part.trim() {
+                    "asm" => link::OutputTypeAssembly,
+                    "bc" => link::OutputTypeBitcode,
+                    "ir" => link::OutputTypeLlvmAssembly,
+                    "obj" => link::OutputTypeObject,
+                    "link" => link::OutputTypeExe,
+                    _ => early_error(demitter,
+                                     format!("unknown emission type: `{}`", part))
+                };
+                if !output_types.contains(&output_type) {
+                    output_types.push(output_type);
+                }
+            }
+        }
+        if output_types.is_empty() {
+            output_types.push(link::OutputTypeExe);
+        }
+    }
     let sysroot_opt = matches.opt_str("sysroot").map(|m| @Path::new(m));
     let target = matches.opt_str("target").unwrap_or(host_triple());
     let target_cpu = matches.opt_str("target-cpu").unwrap_or(~"generic");
@@ -861,6 +1033,7 @@ pub fn build_session_options(binary: ~str,
     let print_metas = (matches.opt_present("crate-id"),
                        matches.opt_present("crate-name"),
                        matches.opt_present("crate-file-name"));
+    let save_analysis = matches.opt_present("save-analysis");
 
     let sopts = @session::Options {
         outputs: outputs,
@@ -871,8 +1044,9 @@ pub fn build_session_options(binary: ~str,
         debuginfo: debuginfo,
         extra_debuginfo: extra_debuginfo,
         lint_opts: lint_opts,
+        plugin_lint_opts: plugin_lint_opts,
         save_temps: save_temps,
-        output_type: output_type,
+        output_types: output_types,
         addl_lib_search_paths: @RefCell::new(addl_lib_search_paths),
         ar: ar,
         linker: linker,
@@ -891,6 +1065,7 @@ pub fn build_session_options(binary: ~str,
         android_cross_path: android_cross_path,
         write_dependency_info: write_dependency_info,
         print_metas: print_metas,
+        save_analysis: save_analysis,
     };
     return sopts;
 }
@@ -928,6 +1103,8 @@ pub fn build_session_(sopts: @session::Options,
         entry_fn: RefCell::new(None),
         entry_type: Cell::new(None),
         macro_registrar_fn: RefCell::new(None),
+        lint_registrar_fn: RefCell::new(None),
+        lint_plugins: RefCell::new(lint::LintRegistry::new()),
         span_diagnostic: span_diagnostic_handler,
         filesearch: filesearch,
         building_library: Cell::new(false),
@@ -945,10 +1122,13 @@ pub fn parse_pretty(sess: Session, name: &str) -> PpMode {
       &"typed" => PpmTyped,
       &"expanded,identified" => PpmExpandedIdentified,
       &"identified" => PpmIdentified,
+      _ if name.starts_with("flowgraph=") => {
+        PpmFlowGraph(name.slice_from("flowgraph=".len()).to_owned())
+      }
       _ => {
         sess.fatal("argument to `pretty` must be one of `normal`, \
                     `expanded`, `typed`, `identified`, \
-                    or `expanded,identified`");
+                    `expanded,identified`, or `flowgraph=<fn>`");
       }
     }
 }
@@ -959,9 +1139,18 @@ pub fn optgroups() -> ~[getopts::groups::OptGroup] {
   optflag("c", "",    "Compile and assemble, but do not link"),
   optmulti("", "cfg", "Configure the compilation
                           environment", "SPEC"),
+  optmulti("", "emit",
+                        "Comma separated list of types of output to
+                          produce: asm, bc, ir, obj or link", "TYPES"),
   optflag("",  "emit-llvm",
                         "Produce an LLVM assembly file if used with -S option;
                          produce an LLVM bitcode file otherwise"),
+  optopt("", "error-format",
+                        "How errors and other diagnostics are reported;
+                          valid formats are: human (the default) or
+                          json (one JSON object per line)", "FORMAT"),
+  optopt("", "explain", "Provide a detailed explanation of an error code",
+                        "CODE"),
   optflag("h", "help","Display this message"),
   optmulti("L", "",   "Add a directory to the library search path",
                               "PATH"),
@@ -1004,11 +1193,16 @@ pub fn optgroups() -> ~[getopts::groups::OptGroup] {
                           valid types are: normal (un-annotated source),
                           expanded (crates expanded),
                           typed (crates expanded, with type annotations),
-                          or identified (fully parenthesized,
-                          AST nodes and blocks with IDs)", "TYPE"),
+                          identified (fully parenthesized,
+                          AST nodes and blocks with IDs),
+                          or flowgraph=<fn> (graphviz formatted control
+                          flow graph of the named fn)", "TYPE"),
   optflag("S", "",    "Compile only; do not assemble or link"),
   optflagopt("", "dep-info",
                         "Output dependency info to <filename> after compiling", "FILENAME"),
+  optflag("", "save-analysis",
+                        "Write the results of name resolution and type
+                          checking to <crate>.analysis.json"),
   optflag("", "save-temps",
                         "Write intermediate files (.bc, .opt.bc, .o)
                           in addition to normal output"),
@@ -1043,7 +1237,29 @@ pub fn optgroups() -> ~[getopts::groups::OptGroup] {
 
 pub struct OutputFilenames {
     out_filename: Path,
-    obj_filename: Path
+    obj_filename: Path,
+    // The file named by -o, if only a single kind of unlinked output was
+    // requested. That output is written there instead of next to the object.
+    single_output_file: Option<Path>,
+}
+
+impl OutputFilenames {
+    /// The path which output of the given type is written to. The object file
+    /// is always `obj_filename`, and linked artifacts are named by
+    /// `link::filename_for_input`.
+    pub fn path(&self, output_type: link::OutputType) -> Path {
+        match self.single_output_file {
+            Some(ref path) => return path.clone(),
+            None => {}
+        }
+        match output_type {
+            link::OutputTypeBitcode => self.obj_filename.with_extension("bc"),
+            link::OutputTypeAssembly => self.obj_filename.with_extension("s"),
+            link::OutputTypeLlvmAssembly => self.obj_filename.with_extension("ll"),
+            link::OutputTypeObject => self.obj_filename.clone(),
+            link::OutputTypeExe => self.out_filename.clone(),
+        }
+    }
 }
 
 pub fn build_output_filenames(input: &Input,
@@ -1054,17 +1270,9 @@ pub fn build_output_filenames(input: &Input,
                            -> ~OutputFilenames {
     let obj_path;
     let out_path;
+    let mut single_output_file = None;
     let sopts = sess.opts;
-    let stop_after_codegen = sopts.output_type != link::OutputTypeExe;
-
-    let obj_suffix = match sopts.output_type {
-        link::OutputTypeNone => ~"none",
-        link::OutputTypeBitcode => ~"bc",
-        link::OutputTypeAssembly => ~"s",
-        link::OutputTypeLlvmAssembly => ~"ll",
-        // Object and exe output both use the '.o' extension here
-        link::OutputTypeObject | link::OutputTypeExe => ~"o"
-    };
+    let obj_suffix = "o";
 
     match *ofile {
       None => {
@@ -1109,11 +1317,15 @@ pub fn build_output_filenames(input: &Input,
 
       Some(ref out_file) => {
         out_path = out_file.clone();
-        obj_path = if stop_after_codegen {
+        obj_path = if sopts.output_types == ~[link::OutputTypeObject] {
             out_file.clone()
         } else {
             out_file.with_extension(obj_suffix)
         };
+        if sopts.output_types.len() == 1 &&
+           sopts.output_types[0] != link::OutputTypeExe {
+            single_output_file = Some(out_file.clone());
+        }
 
         if sess.building_library.get() {
             sess.warn("ignoring specified output filename for library.");
@@ -1127,12 +1339,13 @@ pub fn build_output_filenames(input: &Input,
 
     ~OutputFilenames {
         out_filename: out_path,
-        obj_filename: obj_path
+        obj_filename: obj_path,
+        single_output_file: single_output_file,
     }
 }
 
 pub fn early_error(emitter: &diagnostic::Emitter, msg: &str) -> ! {
-    emitter.emit(None, msg, diagnostic::Fatal);
+    emitter.emit(None, msg, None, diagnostic::Fatal);
     fail!();
 }
 
@@ -69,6 +69,8 @@ pub static gen_crate_map:           uint = 1 << 26;
 pub static prefer_dynamic:          uint = 1 << 27;
 pub static no_integrated_as:        uint = 1 << 28;
 pub static lto:                     uint = 1 << 29;
+pub static flowgraph_print_loans:   uint = 1 << 30;
+pub static flowgraph_print_moves:   uint = 1 << 31;
 
 pub fn debugging_opts_map() -> ~[(&'static str, &'static str, uint)] {
     ~[("verbose", "in general, enable more debug printouts", verbose),
@@ -122,6 +124,12 @@ pub fn debugging_opts_map() -> ~[(&'static str, &'static str, uint)] {
      ("no-integrated-as",
       "Use external assembler rather than LLVM's integrated one", no_integrated_as),
      ("lto", "Perform LLVM link-time optimizations", lto),
+     ("flowgraph-print-loans",
+      "Include loan analysis data in --pretty flowgraph output",
+      flowgraph_print_loans),
+     ("flowgraph-print-moves",
+      "Include move and assignment analysis data in --pretty flowgraph output",
+      flowgraph_print_moves),
     ]
 }
 
@@ -146,8 +154,13 @@ pub struct Options {
     debuginfo: bool,
     extra_debuginfo: bool,
     lint_opts: ~[(lint::Lint, lint::level)],
+    // Lint flags which don't name a built-in lint. These are checked once the
+    // lints registered by plugins are known, right after parsing or expansion.
+    plugin_lint_opts: ~[(~str, lint::level)],
     save_temps: bool,
-    output_type: back::link::OutputType,
+    // The kinds of output to produce, in the order in which they're written.
+    // This is empty if compilation stops before translation.
+    output_types: ~[back::link::OutputType],
     // This is mutable for rustpkg, which updates search paths based on the
     // parsed code.
     addl_lib_search_paths: @RefCell<HashSet<Path>>,
@@ -174,6 +187,8 @@ pub struct Options {
     write_dependency_info: (bool, Option<Path>),
     /// Crate id-related things to maybe print. It's (crate_id, crate_name, crate_file_name).
     print_metas: (bool, bool, bool),
+    /// Whether to dump the results of analysis with `--save-analysis`
+    save_analysis: bool,
 }
 
 // The type of entry function, so
@@ -206,6 +221,9 @@ pub struct Session_ {
     entry_type: Cell<Option<EntryFnType>>,
     span_diagnostic: @diagnostic::SpanHandler,
     macro_registrar_fn: RefCell<Option<ast::DefId>>,
+    lint_registrar_fn: RefCell<Option<ast::DefId>>,
+    // Lints and lint passes registered by the `#[phase(syntax)]` crates
+    lint_plugins: RefCell<lint::LintRegistry>,
     filesearch: @filesearch::FileSearch,
     building_library: Cell<bool>,
     working_dir: Path,
@@ -227,6 +245,9 @@ impl Session_ {
     pub fn span_err(&self, sp: Span, msg: &str) {
         self.span_diagnostic.span_err(sp, msg)
     }
+    pub fn span_err_with_code(&self, sp: Span, msg: &str, code: &str) {
+        self.span_diagnostic.span_err_with_code(sp, msg, code)
+    }
     pub fn err(&self, msg: &str) {
         self.span_diagnostic.handler().err(msg)
     }
@@ -380,8 +401,9 @@ pub fn basic_options() -> @Options {
         debuginfo: false,
         extra_debuginfo: false,
         lint_opts: ~[],
+        plugin_lint_opts: ~[],
         save_temps: false,
-        output_type: link::OutputTypeExe,
+        output_types: ~[link::OutputTypeExe],
         addl_lib_search_paths: @RefCell::new(HashSet::new()),
         ar: None,
         linker: None,
@@ -400,6 +422,7 @@ pub fn basic_options() -> @Options {
         android_cross_path: None,
         write_dependency_info: (false, None),
         print_metas: (false, false, false),
+        save_analysis: false,
     }
 }
 
@@ -45,6 +45,7 @@ static KNOWN_FEATURES: &'static [(&'static str, Status)] = &[
     ("link_args", Active),
     ("phase", Active),
     ("macro_registrar", Active),
+    ("lint_registrar", Active),
     ("log_syntax", Active),
 
     // These are used to test this portion of the compiler, they don't actually
@@ -168,6 +169,11 @@ impl Visitor<()> for Context {
                                       "cross-crate macro exports are \
                                        experimental and possibly buggy");
                 }
+                if attr::contains_name(i.attrs, "lint_registrar") {
+                    self.gate_feature("lint_registrar", i.span,
+                                      "lint plugins are experimental \
+                                       and possibly buggy");
+                }
             }
 
             _ => {}
@@ -83,6 +83,7 @@ pub mod middle {
     pub mod graph;
     pub mod cfg;
     pub mod dead;
+    pub mod save_analysis;
 }
 
 pub mod front {
@@ -110,6 +111,8 @@ pub mod metadata;
 
 pub mod driver;
 
+pub mod diagnostics;
+
 pub mod util {
     pub mod common;
     pub mod ppaux;
@@ -202,6 +205,26 @@ pub fn run_compiler(args: &[~str], demitter: @diagnostic::Emitter) {
           }
         };
 
+    // Everything from here on out is reported with the requested emitter
+    let demitter = match matches.opt_str("error-format") {
+        None => demitter,
+        Some(format) => {
+            if format.as_slice() == "human" {
+                demitter
+            } else if format.as_slice() == "json" {
+                @diagnostic::JsonEmitter::new() as @diagnostic::Emitter
+            } else {
+                d::early_error(demitter,
+                               format!("unknown error format: `{}`", format))
+            }
+        }
+    };
+    run_compiler_with(binary, matches, demitter);
+    demitter.flush();
+}
+
+fn run_compiler_with(binary: ~str, matches: &getopts::Matches,
+                     demitter: @diagnostic::Emitter) {
     if matches.opt_present("h") || matches.opt_present("help") {
         usage(binary);
         return;
@@ -229,6 +252,20 @@ pub fn run_compiler(args: &[~str], demitter: @diagnostic::Emitter) {
         version(binary);
         return;
     }
+    match matches.opt_str("explain") {
+        Some(code) => {
+            match diagnostics::explain(code) {
+                Some(description) => println!("{}", description),
+                None => {
+                    d::early_error(demitter,
+                                   format!("no extended information for {}", code))
+                }
+            }
+            return;
+        }
+        None => {}
+    }
+
     let input = match matches.free.len() {
       0u => d::early_error(demitter, "no input filename given"),
       1u => {
@@ -362,6 +399,7 @@ pub fn monitor(f: proc(@diagnostic::Emitter)) {
                 diagnostic::DefaultEmitter.emit(
                     None,
                     diagnostic::ice_msg("unexpected failure"),
+                    None,
                     diagnostic::Error);
 
                 let xs = [
@@ -371,6 +409,7 @@ pub fn monitor(f: proc(@diagnostic::Emitter)) {
                 for note in xs.iter() {
                     diagnostic::DefaultEmitter.emit(None,
                                                     *note,
+                                                    None,
                                                     diagnostic::Note)
                 }
 
@@ -207,6 +207,7 @@ pub static tag_native_libraries_kind: uint = 0x106;
 pub static tag_macro_registrar_fn: uint = 0x110;
 pub static tag_exported_macros: uint = 0x111;
 pub static tag_macro_def: uint = 0x112;
+pub static tag_lint_registrar_fn: uint = 0x113;
 
 #[deriving(Clone)]
 pub struct LinkMeta {
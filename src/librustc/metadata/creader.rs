@@ -17,6 +17,7 @@ use metadata::cstore;
 use metadata::decoder;
 use metadata::loader;
 use metadata::loader::Os;
+use middle::lint;
 
 use std::cell::RefCell;
 use std::hashmap::HashMap;
@@ -414,6 +415,18 @@ impl CrateLoader for Loader {
         let cnum = resolve_crate(&mut self.env, info.ident, info.name,
                                  info.version, @"", crate.span);
         let library = self.env.sess.cstore.get_used_crate_source(cnum).unwrap();
+
+        // The crate's lints have to be registered now, while its metadata is
+        // still loaded; the syntax-time crates are dropped after expansion.
+        let cstore = self.env.sess.cstore;
+        match (csearch::get_lint_registrar_fn(cstore, cnum), &library.dylib) {
+            (Some(did), &Some(ref path)) => {
+                let registrar = csearch::get_symbol(cstore, did);
+                lint::load_plugin(self.env.sess, path, registrar, crate.span);
+            }
+            _ => {}
+        }
+
         MacroCrate {
             lib: library.dylib,
             cnum: cnum
@@ -308,6 +308,13 @@ pub fn get_macro_registrar_fn(cstore: @cstore::CStore,
     decoder::get_macro_registrar_fn(cdata)
 }
 
+pub fn get_lint_registrar_fn(cstore: @cstore::CStore,
+                             crate_num: ast::CrateNum)
+                             -> Option<ast::DefId> {
+    let cdata = cstore.get_crate_data(crate_num);
+    decoder::get_lint_registrar_fn(cdata)
+}
+
 pub fn get_exported_macros(cstore: @cstore::CStore,
                            crate_num: ast::CrateNum)
                            -> ~[@ast::Item] {
@@ -1282,6 +1282,11 @@ pub fn get_macro_registrar_fn(cdata: Cmd) -> Option<ast::DefId> {
         .map(|doc| item_def_id(doc, cdata))
 }
 
+pub fn get_lint_registrar_fn(cdata: Cmd) -> Option<ast::DefId> {
+    reader::maybe_get_doc(reader::Doc(cdata.data()), tag_lint_registrar_fn)
+        .map(|doc| item_def_id(doc, cdata))
+}
+
 pub fn get_exported_macros(cdata: Cmd) -> ~[@ast::Item] {
     let macros = reader::get_doc(reader::Doc(cdata.data()),
                                  tag_exported_macros);
@@ -80,6 +80,7 @@ struct Stats {
     lang_item_bytes: Cell<u64>,
     native_lib_bytes: Cell<u64>,
     macro_registrar_fn_bytes: Cell<u64>,
+    lint_registrar_fn_bytes: Cell<u64>,
     macro_defs_bytes: Cell<u64>,
     impl_bytes: Cell<u64>,
     misc_bytes: Cell<u64>,
@@ -1705,6 +1706,18 @@ fn encode_macro_registrar_fn(ecx: &EncodeContext, ebml_w: &mut writer::Encoder)
     }
 }
 
+fn encode_lint_registrar_fn(ecx: &EncodeContext, ebml_w: &mut writer::Encoder) {
+    let ptr = ecx.tcx.sess.lint_registrar_fn.borrow();
+    match *ptr.get() {
+        Some(did) => {
+            ebml_w.start_tag(tag_lint_registrar_fn);
+            encode_def_id(ebml_w, did);
+            ebml_w.end_tag();
+        }
+        None => {}
+    }
+}
+
 struct MacroDefVisitor<'a, 'b> {
     ecx: &'a EncodeContext<'a>,
     ebml_w: &'a mut writer::Encoder<'b>
@@ -1862,6 +1875,7 @@ fn encode_metadata_inner(wr: &mut MemWriter, parms: EncodeParams, crate: &Crate)
         lang_item_bytes: Cell::new(0),
         native_lib_bytes: Cell::new(0),
         macro_registrar_fn_bytes: Cell::new(0),
+        lint_registrar_fn_bytes: Cell::new(0),
         macro_defs_bytes: Cell::new(0),
         impl_bytes: Cell::new(0),
         misc_bytes: Cell::new(0),
@@ -1926,6 +1940,11 @@ fn encode_metadata_inner(wr: &mut MemWriter, parms: EncodeParams, crate: &Crate)
     encode_macro_registrar_fn(&ecx, &mut ebml_w);
     ecx.stats.macro_registrar_fn_bytes.set(ebml_w.writer.tell() - i);
 
+    // Encode the lint registrar function
+    i = ebml_w.writer.tell();
+    encode_lint_registrar_fn(&ecx, &mut ebml_w);
+    ecx.stats.lint_registrar_fn_bytes.set(ebml_w.writer.tell() - i);
+
     // Encode macro definitions
     i = ebml_w.writer.tell();
     encode_macro_defs(&ecx, crate, &mut ebml_w);
@@ -1969,6 +1988,7 @@ fn encode_metadata_inner(wr: &mut MemWriter, parms: EncodeParams, crate: &Crate)
         println!("      lang item bytes: {}", ecx.stats.lang_item_bytes.get());
         println!("         native bytes: {}", ecx.stats.native_lib_bytes.get());
         println!("macro registrar bytes: {}", ecx.stats.macro_registrar_fn_bytes.get());
+        println!(" lint registrar bytes: {}", ecx.stats.lint_registrar_fn_bytes.get());
         println!("      macro def bytes: {}", ecx.stats.macro_defs_bytes.get());
         println!("           impl bytes: {}", ecx.stats.impl_bytes.get());
         println!("           misc bytes: {}", ecx.stats.misc_bytes.get());
@@ -232,11 +232,12 @@ impl<'a> CheckLoanCtxt<'a> {
 
             match (new_loan.mutbl, old_loan.mutbl) {
                 (MutableMutability, MutableMutability) => {
-                    self.bccx.span_err(
+                    self.bccx.tcx.sess.span_err_with_code(
                         new_loan.span,
                         format!("cannot borrow `{}` as mutable \
                               more than once at a time",
-                             self.bccx.loan_path_to_str(new_loan.loan_path)));
+                             self.bccx.loan_path_to_str(new_loan.loan_path)),
+                        "E0011");
                     self.bccx.span_note(
                         old_loan.span,
                         format!("previous borrow of `{}` as mutable occurs here",
@@ -128,34 +128,137 @@ fn borrowck_fn(this: &mut BorrowckCtxt,
             debug!("borrowck_fn(id={:?})", id);
 
             // Check the body of fn items.
-            let (id_range, all_loans, move_data) =
-                gather_loans::gather_loans(this, decl, body);
-
+            let AnalysisData { all_loans, loans, move_data } =
+                build_borrowck_dataflow_data(this, decl, body);
             let all_loans = all_loans.borrow();
-            let mut loan_dfcx = DataFlowContext::new(this.tcx,
-                                                     this.method_map,
-                                                     LoanDataFlowOperator,
-                                                     id_range,
-                                                     all_loans.get().len());
-            for (loan_idx, loan) in all_loans.get().iter().enumerate() {
-                loan_dfcx.add_gen(loan.gen_scope, loan_idx);
-                loan_dfcx.add_kill(loan.kill_scope, loan_idx);
-            }
+            check_loans::check_loans(this, &loans, move_data,
+                                     *all_loans.get(), body);
+        }
+    }
 
-            loan_dfcx.propagate(body);
+    visit::walk_fn(this, fk, decl, body, sp, id, ());
+}
+
+/// The dataflow results computed by the borrow checker for a single fn
+pub struct AnalysisData {
+    all_loans: @RefCell<~[Loan]>,
+    loans: LoanDataFlow,
+    move_data: move_data::FlowedMoveData,
+}
+
+impl AnalysisData {
+    /// Describes the state on entry to the node `id`, one line per kind of
+    /// data. Loans in scope are described if `loans` is true, and paths which
+    /// may have been moved or assigned are described if `moves` is true.
+    pub fn describe_entry(&self,
+                          bccx: &BorrowckCtxt,
+                          id: ast::NodeId,
+                          loans: bool,
+                          moves: bool) -> ~[~str] {
+        let mut lines = ~[];
+
+        if loans {
+            let all_loans = self.all_loans.borrow();
+            let mut descs = ~[];
+            self.loans.each_bit_on_entry_frozen(id, |index| {
+                let loan = &all_loans.get()[index];
+                descs.push(format!("{} ({})",
+                                   bccx.loan_path_to_str(loan.loan_path),
+                                   loan.mutbl.to_str()));
+                true
+            });
+            if !descs.is_empty() {
+                lines.push(format!("loans: {}", descs.connect(", ")));
+            }
+        }
 
-            let flowed_moves = move_data::FlowedMoveData::new(move_data,
-                                                              this.tcx,
-                                                              this.method_map,
-                                                              id_range,
-                                                              body);
+        if moves {
+            let mut descs = ~[];
+            self.move_data.each_path_moved_on_entry(id, |lp| {
+                descs.push(bccx.loan_path_to_str(lp));
+                true
+            });
+            if !descs.is_empty() {
+                lines.push(format!("moved or uninitialized: {}",
+                                   descs.connect(", ")));
+            }
 
-            check_loans::check_loans(this, &loan_dfcx, flowed_moves,
-                                     *all_loans.get(), body);
+            let mut descs = ~[];
+            self.move_data.each_path_assigned_on_entry(id, |lp| {
+                descs.push(bccx.loan_path_to_str(lp));
+                true
+            });
+            if !descs.is_empty() {
+                lines.push(format!("assigned: {}", descs.connect(", ")));
+            }
         }
+
+        lines
     }
+}
 
-    visit::walk_fn(this, fk, decl, body, sp, id, ());
+fn build_borrowck_dataflow_data(this: &BorrowckCtxt,
+                                decl: &ast::FnDecl,
+                                body: &ast::Block) -> AnalysisData {
+    let (id_range, all_loans, move_data) =
+        gather_loans::gather_loans(this, decl, body);
+
+    let mut loan_dfcx = {
+        let loans = all_loans.borrow();
+        let mut loan_dfcx = DataFlowContext::new(this.tcx,
+                                                 this.method_map,
+                                                 LoanDataFlowOperator,
+                                                 id_range,
+                                                 loans.get().len());
+        for (loan_idx, loan) in loans.get().iter().enumerate() {
+            loan_dfcx.add_gen(loan.gen_scope, loan_idx);
+            loan_dfcx.add_kill(loan.kill_scope, loan_idx);
+        }
+        loan_dfcx
+    };
+
+    loan_dfcx.propagate(body);
+
+    let flowed_moves = move_data::FlowedMoveData::new(move_data,
+                                                      this.tcx,
+                                                      this.method_map,
+                                                      id_range,
+                                                      body);
+
+    AnalysisData {
+        all_loans: all_loans,
+        loans: loan_dfcx,
+        move_data: flowed_moves,
+    }
+}
+
+/// Computes the borrow checker's dataflow results for the fn with the given
+/// declaration and body, without reporting any errors. This is used to
+/// annotate the fn's control-flow graph with `--pretty flowgraph`.
+pub fn build_borrowck_dataflow_data_for_fn(tcx: ty::ctxt,
+                                           method_map: typeck::method_map,
+                                           moves_map: moves::MovesMap,
+                                           moved_variables_set: moves::MovedVariablesSet,
+                                           capture_map: moves::CaptureMap,
+                                           decl: &ast::FnDecl,
+                                           body: &ast::Block)
+                                           -> (BorrowckCtxt, AnalysisData) {
+    let bccx = BorrowckCtxt {
+        tcx: tcx,
+        method_map: method_map,
+        moves_map: moves_map,
+        moved_variables_set: moved_variables_set,
+        capture_map: capture_map,
+        root_map: root_map(),
+        stats: @BorrowStats {
+            loaned_paths_same: Cell::new(0),
+            loaned_paths_imm: Cell::new(0),
+            stable_paths: Cell::new(0),
+            guaranteed_paths: Cell::new(0),
+        }
+    };
+    let data = build_borrowck_dataflow_data(&bccx, decl, body);
+    (bccx, data)
 }
 
 // ----------------------------------------------------------------------
@@ -529,20 +632,22 @@ impl BorrowckCtxt {
 
         match move.kind {
             move_data::Declared => {
-                self.tcx.sess.span_err(
+                self.tcx.sess.span_err_with_code(
                     use_span,
                     format!("{} of possibly uninitialized value: `{}`",
                          verb,
-                         self.loan_path_to_str(lp)));
+                         self.loan_path_to_str(lp)),
+                    "E0010");
             }
             _ => {
                 let partially = if lp == moved_lp {""} else {"partially "};
-                self.tcx.sess.span_err(
+                self.tcx.sess.span_err_with_code(
                     use_span,
                     format!("{} of {}moved value: `{}`",
                          verb,
                          partially,
-                         self.loan_path_to_str(lp)));
+                         self.loan_path_to_str(lp)),
+                    "E0009");
             }
         }
 
@@ -614,10 +719,11 @@ impl BorrowckCtxt {
                                                 lp: &LoanPath,
                                                 assign:
                                                 &move_data::Assignment) {
-        self.tcx.sess.span_err(
+        self.tcx.sess.span_err_with_code(
             span,
             format!("re-assignment of immutable variable `{}`",
-                 self.loan_path_to_str(lp)));
+                 self.loan_path_to_str(lp)),
+            "E0012");
         self.tcx.sess.span_note(
             assign.span,
             format!("prior assignment occurs here"));
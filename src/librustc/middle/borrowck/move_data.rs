@@ -611,6 +611,38 @@ impl FlowedMoveData {
         })
     }
 
+    pub fn each_path_moved_on_entry(&self,
+                                    id: ast::NodeId,
+                                    f: |@LoanPath| -> bool)
+                                    -> bool {
+        /*!
+         * Iterates through each path which may have been moved on
+         * some path leading to `id`
+         */
+
+        self.dfcx_moves.each_bit_on_entry_frozen(id, |index| {
+            let moves = self.move_data.moves.borrow();
+            let moved_path = moves.get()[index].path;
+            f(self.move_data.path_loan_path(moved_path))
+        })
+    }
+
+    pub fn each_path_assigned_on_entry(&self,
+                                       id: ast::NodeId,
+                                       f: |@LoanPath| -> bool)
+                                       -> bool {
+        /*!
+         * Iterates through each variable which may have been assigned
+         * on some path leading to `id`
+         */
+
+        self.dfcx_assign.each_bit_on_entry_frozen(id, |index| {
+            let var_assignments = self.move_data.var_assignments.borrow();
+            let assigned_path = var_assignments.get()[index].path;
+            f(self.move_data.path_loan_path(assigned_path))
+        })
+    }
+
     pub fn each_move_of(&self,
                         id: ast::NodeId,
                         loan_path: @LoanPath,
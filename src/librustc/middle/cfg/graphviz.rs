@@ -0,0 +1,87 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+
+Rendering of control-flow graphs in the Graphviz DOT language, used by
+`--pretty flowgraph=<fn>`. The output can be turned into an image with e.g.
+`dot -Tpng`.
+
+*/
+
+use middle::cfg::CFG;
+use middle::ty;
+
+use std::io;
+use syntax::ast;
+use syntax::ast_map;
+use syntax::parse::token;
+
+/// Writes `cfg` to `out` as a digraph named `name`. Each node is labelled
+/// with the AST node which it represents, followed by the lines which
+/// `annotate` returns for that node's id.
+pub fn write_cfg(out: &mut io::Writer,
+                 tcx: ty::ctxt,
+                 name: &str,
+                 cfg: &CFG,
+                 annotate: |ast::NodeId| -> ~[~str]) {
+    write!(out, "digraph \"{}\" \\{\n", escape(name));
+
+    cfg.graph.each_node(|idx, node| {
+        let id = node.data.id;
+        let mut lines = if idx == cfg.entry {
+            ~[~"entry"]
+        } else if idx == cfg.exit {
+            ~[~"exit"]
+        } else if id == 0 {
+            ~[~"(dummy node)"]
+        } else {
+            ~[ast_map::node_id_to_str(tcx.items, id,
+                                      token::get_ident_interner())]
+        };
+        if id != 0 {
+            lines.push_all_move(annotate(id));
+        }
+
+        let label = lines.map(|line| escape(*line) + "\\l").concat();
+        write!(out, "    N{}[label=\"{}\"];\n", idx.get(), label);
+        true
+    });
+
+    cfg.graph.each_edge(|_, edge| {
+        let scopes = &edge.data.exiting_scopes;
+        if scopes.is_empty() {
+            write!(out, "    N{} -> N{};\n",
+                   edge.source().get(), edge.target().get());
+        } else {
+            let scopes = scopes.iter().map(|id| id.to_str())
+                               .collect::<~[~str]>().connect(", ");
+            write!(out, "    N{} -> N{}[label=\"exiting scopes {}\"];\n",
+                   edge.source().get(), edge.target().get(), scopes);
+        }
+        true
+    });
+
+    write!(out, "\\}\n");
+}
+
+// Escapes a string for use in a quoted DOT identifier. Newlines are turned
+// into left-justified line breaks.
+fn escape(s: &str) -> ~str {
+    let mut out = ~"";
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => { out.push_char('\\'); out.push_char(c); }
+            '\n' => out.push_str("\\l"),
+            c => out.push_char(c),
+        }
+    }
+    out
+}
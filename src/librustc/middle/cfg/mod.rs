@@ -23,6 +23,7 @@ use syntax::ast;
 use syntax::opt_vec::OptVec;
 
 mod construct;
+pub mod graphviz;
 
 pub struct CFG {
     exit_map: HashMap<ast::NodeId, CFGIndex>,
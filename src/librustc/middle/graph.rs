@@ -68,7 +68,7 @@ pub static Outgoing: Direction = Direction { repr: 0 };
 pub static Incoming: Direction = Direction { repr: 1 };
 
 impl NodeIndex {
-    fn get(&self) -> uint { let NodeIndex(v) = *self; v }
+    pub fn get(&self) -> uint { let NodeIndex(v) = *self; v }
 }
 
 impl EdgeIndex {
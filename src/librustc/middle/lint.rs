@@ -32,6 +32,13 @@
 //! on the session at the appropriate time, or write a few linting functions and
 //! modify the Context visitor appropriately. If you're adding lints from the
 //! Context itself, span_lint should be used instead of add_lint.
+//!
+//! Lints may also be provided by plugins. A crate which is loaded with
+//! `#[phase(syntax)]` may contain a `#[lint_registrar]` function, which is
+//! called with a `LintRegistry` when the crate is loaded. The lints which it
+//! registers are controlled by attributes and command line flags in the same
+//! way as the built-in ones, and the `LintPass`es which it registers are run
+//! alongside the built-in checks.
 
 use driver::session;
 use middle::dead::DEAD_CODE_LINT_STR;
@@ -49,6 +56,9 @@ use middle::typeck::astconv::{ast_ty_to_ty, AstConv};
 
 use std::cmp;
 use std::hashmap::HashMap;
+use std::os;
+use std::util;
+use std::unstable::dynamic_lib::DynamicLibrary;
 use std::i16;
 use std::i32;
 use std::i64;
@@ -57,7 +67,6 @@ use std::u16;
 use std::u32;
 use std::u64;
 use std::u8;
-use extra::smallintmap::SmallIntMap;
 use syntax::ast_map;
 use syntax::attr;
 use syntax::attr::{AttrMetaMethods, AttributeMethods};
@@ -67,7 +76,7 @@ use syntax::{ast, ast_util, visit};
 use syntax::ast_util::IdVisitingOperation;
 use syntax::visit::Visitor;
 
-#[deriving(Clone, Eq, Ord, TotalEq, TotalOrd)]
+#[deriving(Clone, Eq, Ord, TotalEq, TotalOrd, IterBytes)]
 pub enum Lint {
     CTypes,
     UnusedImports,
@@ -105,6 +114,10 @@ pub enum Lint {
     Unstable,
 
     Warnings,
+
+    // A lint registered by a plugin, identified by the order in which it was
+    // registered with the session's `LintRegistry`.
+    PluginLint(uint),
 }
 
 pub fn level_to_str(lv: level) -> &'static str {
@@ -130,6 +143,135 @@ pub struct LintSpec {
 
 pub type LintDict = HashMap<&'static str, LintSpec>;
 
+/// A set of checks provided by a plugin. Each method is called on the
+/// corresponding node while the crate is being linted, with the lint levels
+/// of any enclosing attributes in effect, and reports its lints through
+/// `Context::span_lint`.
+pub trait LintPass {
+    fn check_crate(&mut self, _cx: &Context, _crate: &ast::Crate) {}
+    fn check_item(&mut self, _cx: &Context, _it: &ast::Item) {}
+    fn check_stmt(&mut self, _cx: &Context, _s: &ast::Stmt) {}
+    fn check_expr(&mut self, _cx: &Context, _e: &ast::Expr) {}
+    fn check_pat(&mut self, _cx: &Context, _p: &ast::Pat) {}
+}
+
+/// The type of a plugin's `#[lint_registrar]` function
+pub type LintRegistrationFun = extern "Rust" fn(&mut LintRegistry);
+
+/// The lints and lint passes which have been registered by plugins
+pub struct LintRegistry {
+    priv lints: ~[(&'static str, LintSpec)],
+    priv passes: ~[~LintPass],
+    // The plugins' code and static data must stay loaded for as long as their
+    // passes and lint names may be used.
+    priv libs: ~[DynamicLibrary],
+}
+
+impl LintRegistry {
+    pub fn new() -> LintRegistry {
+        LintRegistry { lints: ~[], passes: ~[], libs: ~[] }
+    }
+
+    /// Registers a new lint, returning the `Lint` which should be passed to
+    /// `Context::span_lint` to report it.
+    pub fn register_lint(&mut self, name: &'static str, default: level,
+                         desc: &'static str) -> Lint {
+        let lint = PluginLint(self.lints.len());
+        self.lints.push((name, LintSpec {
+            default: default,
+            lint: lint,
+            desc: desc,
+        }));
+        lint
+    }
+
+    pub fn register_pass(&mut self, pass: ~LintPass) {
+        self.passes.push(pass);
+    }
+}
+
+/// Loads the plugin library at `path` and calls its lint registrar, which is
+/// exported with the symbol name `registrar`. `span` is the span of the
+/// `extern mod` which loaded the plugin.
+pub fn load_plugin(sess: session::Session, path: &Path, registrar: &str,
+                   span: Span) {
+    // Make sure the path contains a / or the linker will search for it.
+    let path = os::make_absolute(path);
+    let lib = match DynamicLibrary::open(Some(&path)) {
+        Ok(lib) => lib,
+        Err(err) => sess.span_fatal(span, err)
+    };
+
+    sess.lint_plugins.with_mut(|registry| {
+        let first = registry.lints.len();
+        unsafe {
+            let registrar: LintRegistrationFun = match lib.symbol(registrar) {
+                Ok(registrar) => registrar,
+                Err(err) => sess.span_fatal(span, err)
+            };
+            registrar(registry);
+        }
+
+        // Plugin lints share a namespace with the built-in lints, and with
+        // each other, because they're all named by the same attributes.
+        let builtin = get_lint_dict();
+        for i in range(first, registry.lints.len()) {
+            let (name, _) = registry.lints[i];
+            if builtin.contains_key(&name) ||
+               registry.lints.slice_to(i).iter().any(|&(n, _)| n == name) {
+                sess.span_err(span, format!("lint `{}` is already registered",
+                                            name));
+            }
+        }
+    });
+
+    sess.lint_plugins.with_mut(|registry| registry.libs.push(lib));
+}
+
+/// Reports the lint flags given on the command line which name neither a
+/// built-in lint nor one registered by a plugin, aborting if there are any.
+/// This must be called once every plugin which could register lints has been
+/// loaded.
+pub fn check_plugin_lint_opts(sess: session::Session) {
+    sess.lint_plugins.with(|registry| {
+        for &(ref name, level) in sess.opts.plugin_lint_opts.iter() {
+            if !registry.lints.iter().any(|&(n, _)| n == name.as_slice()) {
+                sess.err(format!("unknown {} flag: {}",
+                                 level_to_str(level), *name));
+            }
+        }
+    });
+    sess.abort_if_errors();
+}
+
+struct SyntaxCrateVisitor {
+    found: bool,
+}
+
+impl Visitor<()> for SyntaxCrateVisitor {
+    fn visit_view_item(&mut self, vi: &ast::ViewItem, _: ()) {
+        match vi.node {
+            ast::ViewItemExternMod(..) => {
+                self.found = self.found || vi.attrs.iter().any(|attr| {
+                    "phase" == attr.name() &&
+                        attr.meta_item_list().map_or(false, |phases| {
+                            attr::contains_name(phases, "syntax")
+                        })
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns whether `crate` loads any crates with `#[phase(syntax)]`, which
+/// are the only crates that can register lints.
+pub fn loads_syntax_crates(crate: &ast::Crate) -> bool {
+    let mut v = SyntaxCrateVisitor { found: false };
+    visit::walk_crate(&mut v, crate, ());
+    v.found
+}
+
 #[deriving(Eq)]
 enum LintSource {
     Node(Span),
@@ -368,43 +510,51 @@ pub fn get_lint_dict() -> LintDict {
     return map;
 }
 
-struct Context<'a> {
+pub struct Context<'a> {
     // All known lint modes (string versions)
-    dict: @LintDict,
+    priv dict: @LintDict,
     // Current levels of each lint warning
-    cur: SmallIntMap<(level, LintSource)>,
+    priv cur: HashMap<Lint, (level, LintSource)>,
     // context we're checking in (used to access fields like sess)
-    tcx: ty::ctxt,
+    priv tcx: ty::ctxt,
     // maps from an expression id that corresponds to a method call to the
     // details of the method to be invoked
-    method_map: typeck::method_map,
+    priv method_map: typeck::method_map,
     // Items exported by the crate; used by the missing_doc lint.
-    exported_items: &'a privacy::ExportedItems,
+    priv exported_items: &'a privacy::ExportedItems,
     // The id of the current `ast::StructDef` being walked.
-    cur_struct_def_id: ast::NodeId,
+    priv cur_struct_def_id: ast::NodeId,
     // Whether some ancestor of the current node was marked
     // #[doc(hidden)].
-    is_doc_hidden: bool,
+    priv is_doc_hidden: bool,
 
     // When recursing into an attributed node of the ast which modifies lint
     // levels, this stack keeps track of the previous lint levels of whatever
     // was modified.
-    lint_stack: ~[(Lint, level, LintSource)],
+    priv lint_stack: ~[(Lint, level, LintSource)],
 
     // id of the last visited negated expression
-    negated_expr_id: ast::NodeId
+    priv negated_expr_id: ast::NodeId,
+
+    // The passes registered by plugins
+    priv plugin_passes: ~[~LintPass],
 }
 
 impl<'a> Context<'a> {
+    /// The type context of the crate being checked
+    pub fn tcx(&self) -> ty::ctxt {
+        self.tcx
+    }
+
     fn get_level(&self, lint: Lint) -> level {
-        match self.cur.find(&(lint as uint)) {
+        match self.cur.find(&lint) {
           Some(&(lvl, _)) => lvl,
           None => allow
         }
     }
 
     fn get_source(&self, lint: Lint) -> LintSource {
-        match self.cur.find(&(lint as uint)) {
+        match self.cur.find(&lint) {
           Some(&(_, src)) => src,
           None => Default
         }
@@ -412,9 +562,9 @@ impl<'a> Context<'a> {
 
     fn set_level(&mut self, lint: Lint, level: level, src: LintSource) {
         if level == allow {
-            self.cur.remove(&(lint as uint));
+            self.cur.remove(&lint);
         } else {
-            self.cur.insert(lint as uint, (level, src));
+            self.cur.insert(lint, (level, src));
         }
     }
 
@@ -427,8 +577,10 @@ impl<'a> Context<'a> {
         fail!("unregistered lint {:?}", lint);
     }
 
-    fn span_lint(&self, lint: Lint, span: Span, msg: &str) {
-        let (level, src) = match self.cur.find(&(lint as uint)) {
+    /// Reports `lint` at `span`, according to the lint's level at the node
+    /// which is currently being checked.
+    pub fn span_lint(&self, lint: Lint, span: Span, msg: &str) {
+        let (level, src) = match self.cur.find(&lint) {
             None => { return }
             Some(&(warn, src)) => (self.get_level(Warnings), src),
             Some(&pair) => pair,
@@ -521,6 +673,16 @@ impl<'a> Context<'a> {
         })
     }
 
+    // Runs `f` on each of the plugin passes. The passes are moved out of the
+    // context while they run so that they can be handed the context itself.
+    fn with_plugin_passes(&mut self, f: |&mut ~LintPass, &Context<'a>|) {
+        let mut passes = util::replace(&mut self.plugin_passes, ~[]);
+        for pass in passes.mut_iter() {
+            f(pass, self);
+        }
+        self.plugin_passes = passes;
+    }
+
     fn visit_ids(&self, f: |&mut ast_util::IdVisitor<Context>|) {
         let mut v = ast_util::IdVisitor {
             operation: self,
@@ -933,7 +1095,7 @@ static other_attrs: &'static [&'static str] = &[
 
     // fn-level
     "test", "bench", "should_fail", "ignore", "inline", "lang", "main", "start",
-    "no_split_stack", "cold", "macro_registrar",
+    "no_split_stack", "cold", "macro_registrar", "lint_registrar",
 
     // internal attribute: bypass privacy inside items
     "!resolve_unexported",
@@ -1394,6 +1556,7 @@ impl<'a> Visitor<()> for Context<'a> {
             check_heap_item(cx, it);
             check_missing_doc_item(cx, it);
             check_attrs_usage(cx, it.attrs);
+            cx.with_plugin_passes(|pass, cx| pass.check_item(cx, it));
 
             cx.visit_ids(|v| v.visit_item(it, ()));
 
@@ -1418,6 +1581,7 @@ impl<'a> Visitor<()> for Context<'a> {
     fn visit_pat(&mut self, p: &ast::Pat, _: ()) {
         check_pat_non_uppercase_statics(self, p);
         check_unused_mut_pat(self, p);
+        self.with_plugin_passes(|pass, cx| pass.check_pat(cx, p));
 
         visit::walk_pat(self, p, ());
     }
@@ -1445,12 +1609,14 @@ impl<'a> Visitor<()> for Context<'a> {
 
         check_type_limits(self, e);
         check_unused_casts(self, e);
+        self.with_plugin_passes(|pass, cx| pass.check_expr(cx, e));
 
         visit::walk_expr(self, e, ());
     }
 
     fn visit_stmt(&mut self, s: &ast::Stmt, _: ()) {
         check_path_statement(self, s);
+        self.with_plugin_passes(|pass, cx| pass.check_stmt(cx, s));
 
         visit::walk_stmt(self, s, ());
     }
@@ -1539,16 +1705,25 @@ pub fn check_crate(tcx: ty::ctxt,
                    method_map: typeck::method_map,
                    exported_items: &privacy::ExportedItems,
                    crate: &ast::Crate) {
+    let mut dict = get_lint_dict();
+    let plugin_passes = tcx.sess.lint_plugins.with_mut(|registry| {
+        for &(name, spec) in registry.lints.iter() {
+            dict.insert(name, spec);
+        }
+        util::replace(&mut registry.passes, ~[])
+    });
+
     let mut cx = Context {
-        dict: @get_lint_dict(),
-        cur: SmallIntMap::new(),
+        dict: @dict,
+        cur: HashMap::new(),
         tcx: tcx,
         method_map: method_map,
         exported_items: exported_items,
         cur_struct_def_id: -1,
         is_doc_hidden: false,
         lint_stack: ~[],
-        negated_expr_id: -1
+        negated_expr_id: -1,
+        plugin_passes: plugin_passes,
     };
 
     // Install default lint levels, followed by the command line levels, and
@@ -1559,6 +1734,17 @@ pub fn check_crate(tcx: ty::ctxt,
     for &(lint, level) in tcx.sess.opts.lint_opts.iter() {
         cx.set_level(lint, level, CommandLine);
     }
+    for &(ref name, level) in tcx.sess.opts.plugin_lint_opts.iter() {
+        // Unknown names have already been reported by
+        // `check_plugin_lint_opts`
+        match cx.dict.find_equiv(name) {
+            None => {}
+            Some(spec) => {
+                let lint = spec.lint;
+                cx.set_level(lint, level, CommandLine);
+            }
+        }
+    }
     cx.with_lint_attrs(crate.attrs, |cx| {
         cx.visit_id(ast::CRATE_NODE_ID);
         cx.visit_ids(|v| {
@@ -1570,6 +1756,7 @@ pub fn check_crate(tcx: ty::ctxt,
         // since the root module isn't visited as an item (because it isn't an item), warn for it
         // here.
         check_missing_doc_attrs(cx, None, crate.attrs, crate.span, "crate");
+        cx.with_plugin_passes(|pass, cx| pass.check_crate(cx, crate));
 
         visit::walk_crate(cx, crate, ());
     });
@@ -2685,15 +2685,19 @@ impl Resolver {
                             hi: span.lo + Pos::from_uint(segment_name.len()),
                             expn_info: span.expn_info,
                         };
-                        self.resolve_error(span,
+                        self.resolve_error_with_code(span,
                                               format!("unresolved import. maybe \
                                                     a missing `extern mod \
                                                     {}`?",
-                                                    segment_name));
+                                                    segment_name),
+                                              "E0001");
                         return Failed;
                     }
-                    self.resolve_error(span, format!("unresolved import: could not find `{}` in \
-                                                     `{}`.", segment_name, module_name));
+                    self.resolve_error_with_code(span,
+                                                 format!("unresolved import: could not find \
+                                                          `{}` in `{}`.",
+                                                         segment_name, module_name),
+                                                 "E0001");
                     return Failed;
                 }
                 Indeterminate => {
@@ -2804,12 +2808,14 @@ impl Resolver {
                 let mpath = self.idents_to_str(module_path);
                 match mpath.rfind(':') {
                     Some(idx) => {
-                        self.resolve_error(span, format!("unresolved import: could not find `{}` \
-                                                         in `{}`",
-                                                         // idx +- 1 to account for the colons
-                                                         // on either side
-                                                         mpath.slice_from(idx + 1),
-                                                         mpath.slice_to(idx - 1)));
+                        self.resolve_error_with_code(span,
+                                                     format!("unresolved import: could not \
+                                                              find `{}` in `{}`",
+                                                             // idx +- 1 to account for the
+                                                             // colons on either side
+                                                             mpath.slice_from(idx + 1),
+                                                             mpath.slice_to(idx - 1)),
+                                                     "E0001");
                     },
                     None => (),
                 };
@@ -3249,12 +3255,13 @@ impl Resolver {
                          .span_to_snippet(imports.get()[index].span)
                          .unwrap();
             if sn.contains("::") {
-                self.resolve_error(imports.get()[index].span,
-                                   "unresolved import");
+                self.resolve_error_with_code(imports.get()[index].span,
+                                             "unresolved import", "E0001");
             } else {
                 let err = format!("unresolved import (maybe you meant `{}::*`?)",
                                sn.slice(0, sn.len()));
-                self.resolve_error(imports.get()[index].span, err);
+                self.resolve_error_with_code(imports.get()[index].span, err,
+                                             "E0001");
             }
         }
 
@@ -3531,10 +3538,11 @@ impl Resolver {
                         // named function item. This is not allowed, so we
                         // report an error.
 
-                        self.resolve_error(
+                        self.resolve_error_with_code(
                             span,
                             "can't capture dynamic environment in a fn item; \
-                            use the || { ... } closure form instead");
+                            use the || { ... } closure form instead",
+                            "E0004");
                     } else {
                         // This was an attempt to use a type parameter outside
                         // its scope.
@@ -3554,10 +3562,11 @@ impl Resolver {
                         // named function item. This is not allowed, so we
                         // report an error.
 
-                        self.resolve_error(
+                        self.resolve_error_with_code(
                             span,
                             "can't capture dynamic environment in a fn item; \
-                            use the || { ... } closure form instead");
+                            use the || { ... } closure form instead",
+                            "E0004");
                     } else {
                         // This was an attempt to use a type parameter outside
                         // its scope.
@@ -3577,9 +3586,10 @@ impl Resolver {
                                                parameter in this context");
                     } else {
                         // Still doesn't deal with upvars
-                        self.resolve_error(span,
-                                              "attempt to use a non-constant \
-                                               value in a constant");
+                        self.resolve_error_with_code(span,
+                                                     "attempt to use a non-constant \
+                                                      value in a constant",
+                                                     "E0003");
                     }
 
                 }
@@ -4509,11 +4519,12 @@ impl Resolver {
                                       // Then this is a duplicate variable
                                       // in the same disjunct, which is an
                                       // error
-                                     self.resolve_error(pattern.span,
+                                     self.resolve_error_with_code(pattern.span,
                                        format!("Identifier `{}` is bound more \
                                              than once in the same pattern",
                                             path_to_str(path, self.session
-                                                        .intr())));
+                                                        .intr())),
+                                       "E0005");
                                   }
                                   // Not bound in the same pattern: do nothing
                                 }
@@ -5100,6 +5111,12 @@ impl Resolver {
         }
     }
 
+    fn resolve_error_with_code(&mut self, span: Span, s: &str, code: &str) {
+        if self.emit_errors {
+            self.session.span_err_with_code(span, s, code);
+        }
+    }
+
     fn find_best_match_for_name(&mut self, name: &str, max_distance: uint)
                                 -> Option<@str> {
         let this = &mut *self;
@@ -5208,15 +5225,17 @@ impl Resolver {
                                // of stupid suggestions
                                match self.find_best_match_for_name(wrong_name, 5) {
                                    Some(m) => {
-                                       self.resolve_error(expr.span,
+                                       self.resolve_error_with_code(expr.span,
                                            format!("unresolved name `{}`. \
                                                     Did you mean `{}`?",
-                                                    wrong_name, m));
+                                                    wrong_name, m),
+                                           "E0002");
                                    }
                                    None => {
-                                       self.resolve_error(expr.span,
+                                       self.resolve_error_with_code(expr.span,
                                             format!("unresolved name `{}`.",
-                                                    wrong_name));
+                                                    wrong_name),
+                                            "E0002");
                                    }
                                }
                         }
@@ -5282,10 +5301,11 @@ impl Resolver {
                 match self.search_ribs(label_ribs.get(), label, expr.span,
                                        DontAllowCapturingSelf) {
                     None =>
-                        self.resolve_error(expr.span,
+                        self.resolve_error_with_code(expr.span,
                                               format!("use of undeclared label \
                                                    `{}`",
-                                                   interner_get(label))),
+                                                   interner_get(label)),
+                                              "E0006"),
                     Some(DlDef(def @ DefLabel(_))) => {
                         // XXX: is AllPublic correct?
                         self.record_def(expr.id, (def, AllPublic))
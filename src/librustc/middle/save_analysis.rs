@@ -0,0 +1,324 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Dumps the results of name resolution and type checking for use by code
+//! browsers and other indexing tools.
+//!
+//! `rustc --save-analysis` writes `<crate name>.analysis.json` alongside the
+//! crate's object file. Each line of the file is a JSON object whose "kind"
+//! is one of:
+//!
+//! * `crate`: always the first line, giving the "name" of the crate.
+//! * `def`: a definition, with its "def_kind" (`fn`, `struct`, `local`, ...),
+//!   "name", "id" and "span".
+//! * `ref`: a use of a name, with its "ref_kind" (`path`, `method` or
+//!   `field`), its "span" and the "def" which it resolves to.
+//! * `type`: the "type" of the expression or local variable with the node
+//!   "id" at "span".
+//!
+//! Definitions are identified by an object holding the name of the "crate"
+//! which they belong to, their "node" number within that crate and, for
+//! anything other than a local variable, their full "path". Spans have the
+//! same form as they do in `--error-format=json` diagnostics. Code which is
+//! generated by macro expansion is skipped, since it has no span of its own.
+
+use middle::ty;
+use middle::typeck;
+use util::ppaux;
+
+use extra::json;
+use extra::treemap::TreeMap;
+use std::io;
+use syntax::ast;
+use syntax::ast_map;
+use syntax::ast_util;
+use syntax::codemap::Span;
+use syntax::diagnostic;
+use syntax::parse::token;
+use syntax::visit;
+use syntax::visit::Visitor;
+
+/// Writes the analysis of `crate`, whose name is `crate_name`, to `out`
+pub fn dump_crate(tcx: ty::ctxt,
+                  method_map: typeck::method_map,
+                  crate: &ast::Crate,
+                  crate_name: &str,
+                  out: &mut io::Writer) {
+    let mut visitor = DumpVisitor {
+        tcx: tcx,
+        method_map: method_map,
+        crate_name: crate_name,
+        out: out,
+    };
+    visitor.record("crate", ~[(~"name", json::String(crate_name.to_owned()))]);
+    visit::walk_crate(&mut visitor, crate, ());
+}
+
+struct DumpVisitor<'a> {
+    tcx: ty::ctxt,
+    method_map: typeck::method_map,
+    crate_name: &'a str,
+    out: &'a mut io::Writer,
+}
+
+impl<'a> DumpVisitor<'a> {
+    fn record(&mut self, kind: &str, fields: ~[(~str, json::Json)]) {
+        let mut obj = TreeMap::new();
+        obj.insert(~"kind", json::String(kind.to_owned()));
+        for (key, value) in fields.move_iter() {
+            obj.insert(key, value);
+        }
+        let line = json::Object(~obj).to_str();
+        self.out.write(line.as_bytes());
+        self.out.write(bytes!("\n"));
+    }
+
+    fn span(&self, sp: Span) -> json::Json {
+        diagnostic::json_span(self.tcx.sess.codemap, sp)
+    }
+
+    fn def_id(&self, did: ast::DefId, path: Option<~str>) -> json::Json {
+        let crate_name = if did.crate == ast::LOCAL_CRATE {
+            self.crate_name.to_owned()
+        } else {
+            self.tcx.sess.cstore.get_crate_data(did.crate).name.to_owned()
+        };
+
+        let mut obj = TreeMap::new();
+        obj.insert(~"crate", json::String(crate_name));
+        obj.insert(~"node", json::Number(did.node as f64));
+        obj.insert(~"path", path.map_or(json::Null, |p| json::String(p)));
+        json::Object(~obj)
+    }
+
+    // The full path of the definition `did`, if it has one
+    fn def_path(&self, did: ast::DefId) -> Option<~str> {
+        if did.crate != ast::LOCAL_CRATE {
+            return Some(ty::item_path_str(self.tcx, did));
+        }
+        match self.tcx.items.find(did.node) {
+            Some(ast_map::NodeItem(..)) |
+            Some(ast_map::NodeForeignItem(..)) |
+            Some(ast_map::NodeMethod(..)) |
+            Some(ast_map::NodeTraitMethod(..)) |
+            Some(ast_map::NodeVariant(..)) |
+            Some(ast_map::NodeStructCtor(..)) => {
+                Some(ty::item_path_str(self.tcx, did))
+            }
+            _ => None,
+        }
+    }
+
+    fn node_type(&self, id: ast::NodeId) -> Option<~str> {
+        let node_types = self.tcx.node_types.borrow();
+        node_types.get().find(&(id as uint)).map(|&t| {
+            ppaux::ty_to_str(self.tcx, t)
+        })
+    }
+
+    fn def(&mut self, kind: &str, name: ast::Ident, id: ast::NodeId,
+           sp: Span) {
+        if sp.expn_info.is_some() { return }
+        let did = ast_util::local_def(id);
+        let path = if kind == "local" { None } else { self.def_path(did) };
+        let fields = ~[
+            (~"def_kind", json::String(kind.to_owned())),
+            (~"name", json::String(token::ident_to_str(&name).to_owned())),
+            (~"id", self.def_id(did, path)),
+            (~"span", self.span(sp)),
+        ];
+        self.record("def", fields);
+    }
+
+    fn reference(&mut self, kind: &str, sp: Span, did: ast::DefId,
+                 path: Option<~str>) {
+        if sp.expn_info.is_some() { return }
+        let fields = ~[
+            (~"ref_kind", json::String(kind.to_owned())),
+            (~"span", self.span(sp)),
+            (~"def", self.def_id(did, path)),
+        ];
+        self.record("ref", fields);
+    }
+
+    fn node_type_record(&mut self, id: ast::NodeId, sp: Span) {
+        if sp.expn_info.is_some() { return }
+        let ty = match self.node_type(id) {
+            Some(ty) => ty,
+            None => return,
+        };
+        let fields = ~[
+            (~"id", json::Number(id as f64)),
+            (~"span", self.span(sp)),
+            (~"type", json::String(ty)),
+        ];
+        self.record("type", fields);
+    }
+
+    fn lookup_def(&self, id: ast::NodeId) -> Option<ast::Def> {
+        let def_map = self.tcx.def_map.borrow();
+        def_map.get().find(&id).map(|&def| def)
+    }
+
+    // The field named `name` of the struct which `base` evaluates to, after
+    // any autoderefs, along with its path.
+    fn lookup_field(&self, base: &ast::Expr, name: ast::Ident)
+                    -> Option<(ast::DefId, ~str)> {
+        let mut t = ty::expr_ty(self.tcx, base);
+        loop {
+            match ty::get(t).sty {
+                ty::ty_struct(did, _) => {
+                    let fields = ty::lookup_struct_fields(self.tcx, did);
+                    return fields.iter().find(|f| f.name == name.name).map(|f| {
+                        (f.id, format!("{}::{}", ty::item_path_str(self.tcx, did),
+                                       token::ident_to_str(&name)))
+                    });
+                }
+                _ => {
+                    match ty::deref(t, false) {
+                        Some(mt) => t = mt.ty,
+                        None => return None,
+                    }
+                }
+            }
+        }
+    }
+
+    fn lookup_method(&self, expr_id: ast::NodeId) -> Option<ast::DefId> {
+        let method_map = self.method_map.borrow();
+        method_map.get().find(&expr_id).map(|entry| {
+            match entry.origin {
+                typeck::method_static(def_id) => def_id,
+                typeck::method_param(typeck::method_param {
+                    trait_id: trait_id,
+                    method_num: index,
+                    ..
+                })
+                | typeck::method_object(typeck::method_object {
+                    trait_id: trait_id,
+                    method_num: index,
+                    ..
+                }) => ty::trait_method(self.tcx, trait_id, index).def_id
+            }
+        })
+    }
+}
+
+impl<'a> Visitor<()> for DumpVisitor<'a> {
+    fn visit_item(&mut self, item: &ast::Item, _: ()) {
+        let kind = match item.node {
+            ast::ItemStatic(..) => Some("static"),
+            ast::ItemFn(..) => Some("fn"),
+            ast::ItemMod(..) => Some("mod"),
+            ast::ItemTy(..) => Some("type"),
+            ast::ItemEnum(..) => Some("enum"),
+            ast::ItemStruct(..) => Some("struct"),
+            ast::ItemTrait(..) => Some("trait"),
+            ast::ItemForeignMod(..) | ast::ItemImpl(..) | ast::ItemMac(..) => None,
+        };
+        for &kind in kind.iter() {
+            self.def(kind, item.ident, item.id, item.span);
+        }
+        visit::walk_item(self, item, ());
+    }
+
+    fn visit_foreign_item(&mut self, item: &ast::ForeignItem, _: ()) {
+        let kind = match item.node {
+            ast::ForeignItemFn(..) => "fn",
+            ast::ForeignItemStatic(..) => "static",
+        };
+        self.def(kind, item.ident, item.id, item.span);
+        visit::walk_foreign_item(self, item, ());
+    }
+
+    fn visit_variant(&mut self, v: &ast::Variant, g: &ast::Generics, _: ()) {
+        self.def("variant", v.node.name, v.node.id, v.span);
+        visit::walk_variant(self, v, g, ());
+    }
+
+    fn visit_struct_field(&mut self, field: &ast::StructField, _: ()) {
+        match field.node.kind {
+            ast::NamedField(name, _) => {
+                self.def("field", name, field.node.id, field.span);
+            }
+            ast::UnnamedField => {}
+        }
+        visit::walk_struct_field(self, field, ());
+    }
+
+    fn visit_fn(&mut self, fk: &visit::FnKind, decl: &ast::FnDecl,
+                body: &ast::Block, sp: Span, id: ast::NodeId, _: ()) {
+        match *fk {
+            visit::FkMethod(name, _, _) => self.def("method", name, id, sp),
+            visit::FkItemFn(..) | visit::FkFnBlock => {}
+        }
+        visit::walk_fn(self, fk, decl, body, sp, id, ());
+    }
+
+    fn visit_ty_method(&mut self, m: &ast::TypeMethod, _: ()) {
+        self.def("method", m.ident, m.id, m.span);
+        visit::walk_ty_method(self, m, ());
+    }
+
+    fn visit_pat(&mut self, p: &ast::Pat, _: ()) {
+        match p.node {
+            ast::PatIdent(_, ref path, _) => {
+                // A binding resolves to itself (or not at all), whereas an
+                // identifier which names e.g. a variant or a static doesn't.
+                let is_binding = match self.lookup_def(p.id) {
+                    None => true,
+                    Some(ast::DefPrimTy(..)) => false,
+                    Some(def) => ast_util::def_id_of_def(def) == ast_util::local_def(p.id),
+                };
+                if is_binding {
+                    let name = path.segments.last().identifier;
+                    self.def("local", name, p.id, p.span);
+                    self.node_type_record(p.id, p.span);
+                }
+            }
+            _ => {}
+        }
+        visit::walk_pat(self, p, ());
+    }
+
+    fn visit_path(&mut self, path: &ast::Path, id: ast::NodeId, _: ()) {
+        match self.lookup_def(id) {
+            None | Some(ast::DefPrimTy(..)) => {}
+            Some(def) => {
+                let did = ast_util::def_id_of_def(def);
+                // Bindings are recorded as definitions by `visit_pat`
+                if did != ast_util::local_def(id) {
+                    let path_str = self.def_path(did);
+                    self.reference("path", path.span, did, path_str);
+                }
+            }
+        }
+        visit::walk_path(self, path, ());
+    }
+
+    fn visit_expr(&mut self, e: &ast::Expr, _: ()) {
+        match e.node {
+            ast::ExprMethodCall(..) => {
+                for &did in self.lookup_method(e.id).iter() {
+                    let path = self.def_path(did);
+                    self.reference("method", e.span, did, path);
+                }
+            }
+            ast::ExprField(base, name, _) => {
+                for &(did, ref path) in self.lookup_field(base, name).iter() {
+                    self.reference("field", e.span, did, Some(path.clone()));
+                }
+            }
+            _ => {}
+        }
+        self.node_type_record(e.id, e.span);
+        visit::walk_expr(self, e, ());
+    }
+}
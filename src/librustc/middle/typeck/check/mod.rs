@@ -1724,7 +1724,7 @@ pub fn check_expr_with_unifier(fcx: @FnCtxt,
                      supplied_arg_count,
                      if supplied_arg_count == 1 {" was"} else {"s were"});
 
-                tcx.sess.span_err(sp, msg);
+                tcx.sess.span_err_with_code(sp, msg, "E0008");
 
                 err_args(supplied_arg_count)
             }
@@ -1744,7 +1744,7 @@ pub fn check_expr_with_unifier(fcx: @FnCtxt,
                  if supplied_arg_count == 1 {" was"} else {"s were"},
                  suffix);
 
-            tcx.sess.span_err(sp, msg);
+            tcx.sess.span_err_with_code(sp, msg, "E0008");
 
             err_args(supplied_arg_count)
         };
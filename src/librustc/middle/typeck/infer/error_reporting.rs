@@ -169,12 +169,13 @@ impl ErrorReporting for InferCtxt {
             infer::IfExpression(_) => "if and else have incompatible types",
         };
 
-        self.tcx.sess.span_err(
+        self.tcx.sess.span_err_with_code(
             trace.origin.span(),
             format!("{}: {} ({})",
                  message_root_str,
                  expected_found_str,
-                 ty::type_err_to_str(tcx, terr)));
+                 ty::type_err_to_str(tcx, terr)),
+            "E0007");
 
         ty::note_and_explain_type_err(self.tcx, terr);
     }
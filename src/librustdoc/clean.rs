@@ -20,6 +20,8 @@ use syntax::ast_util;
 use syntax::attr;
 use syntax::attr::AttributeMethods;
 use syntax::codemap::Pos;
+use syntax::parse::token;
+use syntax::print::pprust;
 
 use rustc::metadata::cstore;
 use rustc::metadata::csearch;
@@ -182,6 +184,7 @@ pub enum ItemEnum {
     VariantItem(Variant),
     ForeignFunctionItem(Function),
     ForeignStaticItem(Static),
+    MacroItem(Macro),
 }
 
 #[deriving(Clone, Encodable, Decodable)]
@@ -207,7 +210,8 @@ impl Clean<Item> for doctree::Module {
                        self.fns.clean(), self.foreigns.clean().concat_vec(),
                        self.mods.clean(), self.typedefs.clean(),
                        self.statics.clean(), self.traits.clean(),
-                       self.impls.clean(), self.view_items.clean()].concat_vec()
+                       self.impls.clean(), self.view_items.clean(),
+                       self.macros.clean()].concat_vec()
             })
         }
     }
@@ -1096,6 +1100,39 @@ impl Clean<Item> for ast::ForeignItem {
     }
 }
 
+#[deriving(Clone, Encodable, Decodable)]
+pub struct Macro {
+    /// The matcher of each of the macro's rules, e.g. `($a:expr, $b:expr)`
+    matchers: ~[~str],
+}
+
+impl Clean<Item> for doctree::Macro {
+    fn clean(&self) -> Item {
+        // The rules of a `macro_rules!` are `matcher => transcriber` pairs
+        let intr = token::get_ident_interner();
+        let matchers = self.rules.windows(2).filter_map(|pair| {
+            match pair[1] {
+                ast::TTTok(_, token::FAT_ARROW) => {
+                    Some(pprust::tt_to_str(&pair[0], intr))
+                }
+                _ => None,
+            }
+        }).collect();
+
+        Item {
+            name: Some(self.name.clean()),
+            attrs: self.attrs.clean(),
+            source: self.where.clean(),
+            id: self.id,
+            // Exported macros are usable from anywhere
+            visibility: Some(ast::Public),
+            inner: MacroItem(Macro {
+                matchers: matchers,
+            }),
+        }
+    }
+}
+
 // Utilities
 
 trait ToSource {
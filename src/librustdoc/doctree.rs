@@ -32,6 +32,7 @@ pub struct Module {
     impls: ~[Impl],
     foreigns: ~[ast::ForeignMod],
     view_items: ~[ast::ViewItem],
+    macros: ~[Macro],
 }
 
 impl Module {
@@ -52,6 +53,7 @@ impl Module {
             impls      : ~[],
             view_items : ~[],
             foreigns   : ~[],
+            macros     : ~[],
         }
     }
 }
@@ -146,6 +148,14 @@ pub struct Trait {
     vis: ast::Visibility,
 }
 
+pub struct Macro {
+    name: Ident,
+    rules: ~[ast::TokenTree],
+    attrs: ~[ast::Attribute],
+    id: ast::NodeId,
+    where: Span,
+}
+
 pub struct Impl {
     generics: ast::Generics,
     trait_: Option<ast::TraitRef>,
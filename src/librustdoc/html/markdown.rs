@@ -13,8 +13,8 @@
 //! This module implements markdown formatting through the sundown C-library
 //! (bundled into the rust runtime). This module self-contains the C bindings
 //! and necessary legwork to render markdown, and exposes all of the
-//! functionality through a unit-struct, `Markdown`, which has an implementation
-//! of `fmt::Default`. Example usage:
+//! functionality through the unit-structs `Markdown` and `MarkdownWithToc`,
+//! which have implementations of `fmt::Default`. Example usage:
 //!
 //! ```rust
 //! let s = "My *markdown* _text_";
@@ -34,6 +34,9 @@ use std::vec;
 /// formatted, this struct will emit the HTML corresponding to the rendered
 /// version of the contained markdown string.
 pub struct Markdown<'a>(&'a str);
+/// A unit struct like `Markdown`, which also emits a table of contents linking
+/// to each of the headers in the rendered markdown.
+pub struct MarkdownWithToc<'a>(&'a str);
 
 static OUTPUT_UNIT: libc::size_t = 64;
 static MKDEXT_NO_INTRA_EMPHASIS: libc::c_uint = 1 << 0;
@@ -42,6 +45,8 @@ static MKDEXT_FENCED_CODE: libc::c_uint = 1 << 2;
 static MKDEXT_AUTOLINK: libc::c_uint = 1 << 3;
 static MKDEXT_STRIKETHROUGH: libc::c_uint = 1 << 4;
 
+static HTML_TOC: libc::c_uint = 1 << 6;
+
 type sd_markdown = libc::c_void;  // this is opaque to us
 
 struct sd_callbacks {
@@ -79,6 +84,8 @@ extern {
     fn sdhtml_renderer(callbacks: *sd_callbacks,
                        options_ptr: *html_renderopt,
                        render_flags: libc::c_uint);
+    fn sdhtml_toc_renderer(callbacks: *sd_callbacks,
+                           options_ptr: *html_renderopt);
     fn sd_markdown_new(extensions: libc::c_uint,
                        max_nesting: libc::size_t,
                        callbacks: *sd_callbacks,
@@ -107,7 +114,7 @@ fn stripped_filtered_line<'a>(s: &'a str) -> Option<&'a str> {
     }
 }
 
-pub fn render(w: &mut io::Writer, s: &str) {
+pub fn render(w: &mut io::Writer, s: &str, print_toc: bool) {
     extern fn block(ob: *buf, text: *buf, lang: *buf, opaque: *libc::c_void) {
         unsafe {
             let my_opaque: &my_opaque = cast::transmute(opaque);
@@ -127,6 +134,12 @@ pub fn render(w: &mut io::Writer, s: &str) {
         }
     }
 
+    if print_toc {
+        write!(w, "<nav id='TOC'>");
+        render_toc(w, s);
+        write!(w, "</nav>");
+    }
+
     // This code is all lifted from examples/sundown.c in the sundown repo
     unsafe {
         let ob = bufnew(OUTPUT_UNIT);
@@ -144,7 +157,10 @@ pub fn render(w: &mut io::Writer, s: &str) {
         };
         let mut callbacks: sd_callbacks = intrinsics::init();
 
-        sdhtml_renderer(&callbacks, &options, 0);
+        // With HTML_TOC each header is given an id for the table of contents
+        // to link to.
+        sdhtml_renderer(&callbacks, &options,
+                        if print_toc { HTML_TOC } else { 0 });
         let opaque = my_opaque {
             opt: options,
             dfltblk: callbacks.blockcode,
@@ -165,6 +181,31 @@ pub fn render(w: &mut io::Writer, s: &str) {
     }
 }
 
+// Renders a nested list of links to each of the headers in `s`
+fn render_toc(w: &mut io::Writer, s: &str) {
+    unsafe {
+        let ob = bufnew(OUTPUT_UNIT);
+        let extensions = MKDEXT_NO_INTRA_EMPHASIS | MKDEXT_TABLES |
+                         MKDEXT_FENCED_CODE | MKDEXT_AUTOLINK |
+                         MKDEXT_STRIKETHROUGH;
+        let options: html_renderopt = intrinsics::init();
+        let callbacks: sd_callbacks = intrinsics::init();
+
+        sdhtml_toc_renderer(&callbacks, &options);
+        let markdown = sd_markdown_new(extensions, 16, &callbacks,
+                                       &options as *html_renderopt as *libc::c_void);
+
+        sd_markdown_render(ob, s.as_ptr(), s.len() as libc::size_t, markdown);
+        sd_markdown_free(markdown);
+
+        vec::raw::buf_as_slice((*ob).data, (*ob).size as uint, |buf| {
+            w.write(buf);
+        });
+
+        bufrelease(ob);
+    }
+}
+
 pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector) {
     extern fn block(_ob: *buf, text: *buf, lang: *buf, opaque: *libc::c_void) {
         unsafe {
@@ -212,6 +253,13 @@ impl<'a> fmt::Default for Markdown<'a> {
         let Markdown(md) = *md;
         // This is actually common enough to special-case
         if md.len() == 0 { return; }
-        render(fmt.buf, md.as_slice());
+        render(fmt.buf, md.as_slice(), false);
+    }
+}
+
+impl<'a> fmt::Default for MarkdownWithToc<'a> {
+    fn fmt(md: &MarkdownWithToc<'a>, fmt: &mut fmt::Formatter) {
+        let MarkdownWithToc(md) = *md;
+        render(fmt.buf, md.as_slice(), true);
     }
 }
@@ -147,6 +147,12 @@ pub struct Cache {
     /// of trait ids to the list of known implementors of the trait
     implementors: HashMap<ast::NodeId, ~[Implementor]>,
 
+    /// Implementors of traits which are defined in other crates, keyed by the
+    /// fully qualified path of the trait. These can't be listed on the trait's
+    /// page directly, so they're published to the implementors registry in
+    /// the output directory instead (see `write_implementors`).
+    extern_implementors: HashMap<~[~str], ~[Implementor]>,
+
     /// Cache of where external crate documentation can be found.
     extern_locations: HashMap<ast::CrateNum, ExternalLocation>,
 
@@ -178,6 +184,21 @@ struct Source<'a>(&'a str);
 struct Item<'a> { cx: &'a Context, item: &'a clean::Item, }
 struct Sidebar<'a> { cx: &'a Context, item: &'a clean::Item, }
 
+/// Badge showing the stability level of an item, if it has one
+struct StabilityBadge<'a>(&'a clean::Item);
+
+/// The number of items in one module at each stability level. The counts are
+/// indexed by `attr::StabilityLevel`, and items without a stability attribute
+/// are counted last.
+struct ModuleStability {
+    path: ~[~str],
+    counts: [uint, ..7],
+}
+
+/// Table of the stability of each module in a crate, which is rendered as the
+/// crate's stability summary page
+struct StabilitySummary(~[ModuleStability]);
+
 /// Struct representing one entry in the JS search index. These are all emitted
 /// by hand to a large JS file at the end of cache-creation.
 struct IndexItem {
@@ -236,6 +257,7 @@ pub fn run(mut crate: clean::Crate, dst: Path) {
         paths: HashMap::new(),
         traits: HashMap::new(),
         implementors: HashMap::new(),
+        extern_implementors: HashMap::new(),
         stack: ~[],
         parent_stack: ~[],
         search_index: ~[],
@@ -247,11 +269,7 @@ pub fn run(mut crate: clean::Crate, dst: Path) {
 
     // Add all the static files
     let mut dst = cx.dst.join(crate.name.as_slice());
-    mkdir(&dst);
-    write(dst.join("jquery.js"), include_str!("static/jquery-2.0.3.min.js"));
-    write(dst.join("main.js"), include_str!("static/main.js"));
-    write(dst.join("main.css"), include_str!("static/main.css"));
-    write(dst.join("normalize.css"), include_str!("static/normalize.css"));
+    write_static_files(&dst);
 
     // Publish the search index
     {
@@ -300,10 +318,40 @@ pub fn run(mut crate: clean::Crate, dst: Path) {
         cache.extern_locations.insert(n, extern_location(e, &cx.dst));
     }
 
+    // Summarize how much of the crate has been stabilized
+    match crate.module {
+        Some(ref m) => {
+            let mut summary = ~[];
+            summarize_stability(m, ~[crate.name.clone()], &mut summary);
+            let dst = cx.dst.join(crate.name.as_slice()).join("stability.html");
+            let mut w = BufferedWriter::new(File::create(&dst).unwrap());
+            let title = format!("{} - Stability", crate.name);
+            let page = layout::Page {
+                title: title.as_slice(),
+                ty: "stability",
+                root_path: "../",
+            };
+            layout::render(&mut w as &mut Writer, &cx.layout, &page, &(""),
+                           &StabilitySummary(summary));
+            w.flush();
+        }
+        None => {}
+    }
+
     // And finally render the whole crate's documentation
     cx.crate(crate, cache);
 }
 
+/// Writes the scripts and stylesheets which every page links to into the
+/// directory `dst`, creating it if necessary
+pub fn write_static_files(dst: &Path) {
+    mkdir(dst);
+    write(dst.join("jquery.js"), include_str!("static/jquery-2.0.3.min.js"));
+    write(dst.join("main.js"), include_str!("static/main.js"));
+    write(dst.join("main.css"), include_str!("static/main.css"));
+    write(dst.join("normalize.css"), include_str!("static/normalize.css"));
+}
+
 /// Writes the entire contents of a string to a destination, not attempting to
 /// catch any errors.
 fn write(dst: Path, contents: &str) {
@@ -324,6 +372,71 @@ fn mkdir(path: &Path) {
     })
 }
 
+/// Adds the implementors of foreign traits found in the crate `crate` to the
+/// registry in `dst/implementors`.
+///
+/// The registry holds a script for each trait, at the same relative path as
+/// the trait's own page, with one line per crate which implements the trait.
+/// Lines written by previous runs of rustdoc for other crates are preserved,
+/// so documenting several crates into the same directory in any order lists
+/// all of their implementors on the trait's page.
+fn write_implementors(dst: &Path, crate: &str) {
+    // Links in the registry are relative to the root of the output directory,
+    // and are adjusted by main.js for the page which loads them
+    local_data::set(current_location_key, ~[]);
+
+    local_data::get(cache_key, |cache| {
+        let cache = cache.unwrap().get();
+        for (fqp, implementors) in cache.extern_implementors.iter() {
+            let mut mydst = dst.join("implementors");
+            mkdir(&mydst);
+            for part in fqp.slice_to(fqp.len() - 1).iter() {
+                mydst.push(part.as_slice());
+                mkdir(&mydst);
+            }
+            mydst.push(format!("trait.{}.js", *fqp.last()));
+
+            let prefix = format!("implementors['{}']", crate);
+            let mut lines = read_implementors(&mydst, prefix);
+            let impls = implementors.iter().map(|i| {
+                let s = match *i {
+                    PathType(ref ty) => format!("{}", *ty),
+                    OtherType(ref generics, ref trait_, ref for_) => {
+                        format!("impl{} {} for {}", *generics, *trait_, *for_)
+                    }
+                };
+                s.to_json().to_str()
+            }).to_owned_vec();
+            lines.push(format!("{} = [{}];", prefix, impls.connect(",")));
+            lines.sort();
+
+            let mut w = BufferedWriter::new(File::create(&mydst).unwrap());
+            let w = &mut w as &mut Writer;
+            write!(w, "(function() \\{var implementors = \\{\\};\n");
+            for line in lines.iter() {
+                write!(w, "{}\n", *line);
+            }
+            write!(w, "if (window.register_implementors) \\{\n\
+                           window.register_implementors(implementors);\n\
+                       \\} else \\{\n\
+                           window.pending_implementors = implementors;\n\
+                       \\}\n\
+                       \\})()\n");
+            w.flush();
+        }
+    })
+}
+
+/// Reads the lines of an existing implementors script which were written for
+/// other crates, i.e. those which don't start with `prefix`.
+fn read_implementors(path: &Path, prefix: &str) -> ~[~str] {
+    if !path.exists() { return ~[] }
+    let contents = str::from_utf8_owned(File::open(path).read_to_end());
+    contents.lines().filter(|line| {
+        line.starts_with("implementors[") && !line.starts_with(prefix)
+    }).map(|line| line.to_owned()).collect()
+}
+
 /// Takes a path to a source file and cleans the path to it. This canonicalizes
 /// things like ".." to components which preserve the "top down" hierarchy of a
 /// static HTML tree.
@@ -493,11 +606,23 @@ impl DocFolder for Cache {
         // Collect all the implementors of traits.
         match item.inner {
             clean::ImplItem(ref i) => {
-                match i.trait_ {
+                let v = match i.trait_ {
                     Some(clean::ResolvedPath{ id, .. }) => {
-                        let v = self.implementors.find_or_insert_with(id, |_|{
+                        Some(self.implementors.find_or_insert_with(id, |_|{
                             ~[]
-                        });
+                        }))
+                    }
+                    Some(clean::ExternalPath{ fqn: ref fqn, .. }) => {
+                        let fqn = fqn.clone();
+                        Some(self.extern_implementors.find_or_insert_with(fqn,
+                                                                          |_|{
+                            ~[]
+                        }))
+                    }
+                    Some(..) | None => None,
+                };
+                match v {
+                    Some(v) => {
                         match i.for_ {
                             clean::ResolvedPath{..} => {
                                 v.unshift(PathType(i.for_.clone()));
@@ -509,7 +634,7 @@ impl DocFolder for Cache {
                             }
                         }
                     }
-                    Some(..) | None => {}
+                    None => {}
                 }
             }
             _ => {}
@@ -692,6 +817,10 @@ impl Context {
         // using a rwarc makes this parallelizable in the future
         local_data::set(cache_key, Arc::new(cache));
 
+        // Now that types can be rendered, publish this crate's implementors of
+        // foreign traits for the pages of those traits to pick up
+        write_implementors(&self.dst, item.name.get_ref().as_slice());
+
         let mut work = ~[(self, item)];
         while work.len() > 0 {
             let (mut cx, item) = work.pop();
@@ -787,6 +916,7 @@ fn shortty(item: &clean::Item) -> &'static str {
         clean::VariantItem(..)         => "variant",
         clean::ForeignFunctionItem(..) => "ffi",
         clean::ForeignStaticItem(..)   => "ffs",
+        clean::MacroItem(..)           => "macro",
     }
 }
 
@@ -800,16 +930,12 @@ impl<'a> Item<'a> {
 
 impl<'a> fmt::Default for Item<'a> {
     fn fmt(it: &Item<'a>, fmt: &mut fmt::Formatter) {
-        match attr::find_stability(it.item.attrs.iter()) {
-            Some(stability) => {
-                write!(fmt.buf,
-                       "<a class='stability {lvl}' title='{reason}'>{lvl}</a>",
-                       lvl = stability.level.to_str(),
-                       reason = match stability.text {
-                           Some(s) => s, None => @"",
-                       });
-            }
-            None => {}
+        write!(fmt.buf, "{}", StabilityBadge(it.item));
+
+        // The crate's root page links to the summary of its stability
+        if it.ismodule() && it.cx.current.len() == 1 {
+            write!(fmt.buf, "<a class='stability-summary'
+                                href='stability.html'>[stability]</a>");
         }
 
         if it.cx.include_sources {
@@ -839,6 +965,7 @@ impl<'a> fmt::Default for Item<'a> {
             clean::TraitItem(..) => write!(fmt.buf, "Trait "),
             clean::StructItem(..) => write!(fmt.buf, "Struct "),
             clean::EnumItem(..) => write!(fmt.buf, "Enum "),
+            clean::MacroItem(..) => write!(fmt.buf, "Macro "),
             _ => {}
         }
         let cur = it.cx.current.as_slice();
@@ -863,6 +990,7 @@ impl<'a> fmt::Default for Item<'a> {
             clean::StructItem(ref s) => item_struct(fmt.buf, it.item, s),
             clean::EnumItem(ref e) => item_enum(fmt.buf, it.item, e),
             clean::TypedefItem(ref t) => item_typedef(fmt.buf, it.item, t),
+            clean::MacroItem(ref m) => item_macro(fmt.buf, it.item, m),
             _ => {}
         }
     }
@@ -899,6 +1027,126 @@ fn shorter<'a>(s: Option<&'a str>) -> &'a str {
     }
 }
 
+impl<'a> fmt::Default for StabilityBadge<'a> {
+    fn fmt(s: &StabilityBadge<'a>, f: &mut fmt::Formatter) {
+        let StabilityBadge(item) = *s;
+        match attr::find_stability(item.attrs.iter()) {
+            Some(stability) => {
+                let reason = match stability.text {
+                    Some(s) => s, None => @"",
+                };
+                write!(f.buf,
+                       "<a class='stability {lvl}' title='{reason}'>{lvl}</a>",
+                       lvl = stability.level.to_str(),
+                       reason = Escape(reason.as_slice()));
+            }
+            None => {}
+        }
+    }
+}
+
+static STABILITY_LEVELS: [attr::StabilityLevel, ..6] = [
+    attr::Deprecated, attr::Experimental, attr::Unstable,
+    attr::Stable, attr::Frozen, attr::Locked,
+];
+
+/// Appends the stability counts of the module `item`, whose path is `path`,
+/// to `summary`, followed by those of each of its submodules in turn. The
+/// members of structs, enums, traits and impls are counted along with the
+/// module which contains them.
+fn summarize_stability(item: &clean::Item, path: ~[~str],
+                       summary: &mut ~[ModuleStability]) {
+    fn count(item: &clean::Item, counts: &mut [uint, ..7]) {
+        if item.name.is_some() {
+            let idx = match attr::find_stability(item.attrs.iter()) {
+                Some(stability) => stability.level as uint,
+                None => STABILITY_LEVELS.len(),
+            };
+            counts[idx] += 1;
+        }
+        match item.inner {
+            clean::StructItem(ref s) => {
+                for field in s.fields.iter() { count(field, counts) }
+            }
+            clean::EnumItem(ref e) => {
+                for variant in e.variants.iter() { count(variant, counts) }
+            }
+            clean::TraitItem(ref t) => {
+                for meth in t.methods.iter() { count(meth.item(), counts) }
+            }
+            clean::ImplItem(ref i) => {
+                for meth in i.methods.iter() { count(meth, counts) }
+            }
+            _ => {}
+        }
+    }
+
+    let items = match item.inner {
+        clean::ModuleItem(ref m) => m.items.as_slice(),
+        _ => return,
+    };
+    let mut counts = [0u, ..7];
+    for it in items.iter() {
+        match it.inner {
+            clean::ViewItemItem(..) => {}
+            _ => count(it, &mut counts),
+        }
+    }
+    summary.push(ModuleStability { path: path.clone(), counts: counts });
+
+    for it in items.iter() {
+        match it.inner {
+            clean::ModuleItem(..) => {
+                let mut path = path.clone();
+                path.push(it.name.get_ref().to_owned());
+                summarize_stability(it, path, summary);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Default for StabilitySummary {
+    fn fmt(s: &StabilitySummary, f: &mut fmt::Formatter) {
+        let StabilitySummary(ref modules) = *s;
+        let crate = modules[0].path[0].as_slice();
+        write!(f.buf, "<h1 class='fqn'>Stability of \
+                           <a class='mod' href='index.html'>{}</a></h1>",
+               crate);
+
+        let mut totals = [0u, ..7];
+        write!(f.buf, "<table class='stability-summary'><tr><th>Module</th>");
+        for level in STABILITY_LEVELS.iter() {
+            write!(f.buf, "<th class='stability {lvl}'>{lvl}</th>",
+                   lvl = level.to_str());
+        }
+        write!(f.buf, "<th>Unmarked</th><th>Total</th></tr>");
+
+        for module in modules.iter() {
+            // Links are relative to the crate's root directory
+            let href = module.path.slice_from(1).iter().map(|p| {
+                format!("{}/", *p)
+            }).to_owned_vec().concat();
+            write!(f.buf, "<tr><td><a class='mod' href='{}index.html'>{}</a></td>",
+                   href, module.path.connect("::"));
+            let mut total = 0;
+            for (i, &n) in module.counts.iter().enumerate() {
+                write!(f.buf, "<td>{}</td>", n);
+                totals[i] += n;
+                total += n;
+            }
+            write!(f.buf, "<td>{}</td></tr>", total);
+        }
+
+        write!(f.buf, "<tr class='total'><td>Total</td>");
+        for &n in totals.iter() {
+            write!(f.buf, "<td>{}</td>", n);
+        }
+        write!(f.buf, "<td>{}</td></tr></table>",
+               totals.iter().fold(0, |a, &b| a + b));
+    }
+}
+
 fn document(w: &mut Writer, item: &clean::Item) {
     match item.doc_value() {
         Some(s) => {
@@ -946,6 +1194,8 @@ fn item_module(w: &mut Writer, cx: &Context,
             (_, &clean::FunctionItem(..)) => Greater,
             (&clean::TypedefItem(..), _) => Less,
             (_, &clean::TypedefItem(..)) => Greater,
+            (&clean::MacroItem(..), _) => Less,
+            (_, &clean::MacroItem(..)) => Greater,
             _ => idx1.cmp(&idx2),
         }
     }
@@ -980,6 +1230,7 @@ fn item_module(w: &mut Writer, cx: &Context,
                 clean::VariantItem(..)         => "Variants",
                 clean::ForeignFunctionItem(..) => "Foreign Functions",
                 clean::ForeignStaticItem(..)   => "Foreign Statics",
+                clean::MacroItem(..)           => "Macros",
             });
         }
 
@@ -999,10 +1250,11 @@ fn item_module(w: &mut Writer, cx: &Context,
 
                 write!(w, "
                     <tr>
-                        <td><code>{}static {}: {}</code>{}</td>
+                        <td>{}<code>{}static {}: {}</code>{}</td>
                         <td class='docblock'>{}&nbsp;</td>
                     </tr>
                 ",
+                StabilityBadge(myitem),
                 VisSpace(myitem.visibility),
                 *myitem.name.get_ref(),
                 s.type_,
@@ -1038,11 +1290,12 @@ fn item_module(w: &mut Writer, cx: &Context,
                 if myitem.name.is_none() { continue }
                 write!(w, "
                     <tr>
-                        <td><a class='{class}' href='{href}'
-                               title='{title}'>{}</a></td>
+                        <td>{}<a class='{class}' href='{href}'
+                                 title='{title}'>{}</a></td>
                         <td class='docblock short'>{}</td>
                     </tr>
                 ",
+                StabilityBadge(myitem),
                 *myitem.name.get_ref(),
                 Markdown(shorter(myitem.doc_value())),
                 class = shortty(myitem),
@@ -1108,9 +1361,10 @@ fn item_trait(w: &mut Writer, it: &clean::Item, t: &clean::Trait) {
     document(w, it);
 
     fn meth(w: &mut Writer, m: &clean::TraitMethod) {
-        write!(w, "<h3 id='{}.{}' class='method'><code>",
+        write!(w, "<h3 id='{}.{}' class='method'>{}<code>",
                shortty(m.item()),
-               *m.item().name.get_ref());
+               *m.item().name.get_ref(),
+               StabilityBadge(m.item()));
         render_method(w, m.item(), false);
         write!(w, "</code></h3>");
         document(w, m.item());
@@ -1138,14 +1392,17 @@ fn item_trait(w: &mut Writer, it: &clean::Item, t: &clean::Trait) {
         write!(w, "</div>");
     }
 
+    // Implementors from this crate are listed directly, and those from other
+    // crates documented into the same directory are added by the script in the
+    // implementors registry (see `write_implementors`)
+    write!(w, "
+        <h2 id='implementors'>Implementors</h2>
+        <ul class='item-list' id='implementors-list'>
+    ");
     local_data::get(cache_key, |cache| {
         let cache = cache.unwrap().get();
         match cache.implementors.find(&it.id) {
             Some(implementors) => {
-                write!(w, "
-                    <h2 id='implementors'>Implementors</h2>
-                    <ul class='item-list'>
-                ");
                 for i in implementors.iter() {
                     match *i {
                         PathType(ref ty) => {
@@ -1157,7 +1414,22 @@ fn item_trait(w: &mut Writer, it: &clean::Item, t: &clean::Trait) {
                         }
                     }
                 }
-                write!(w, "</ul>");
+            }
+            None => {}
+        }
+        write!(w, "</ul>");
+
+        match cache.paths.find(&it.id) {
+            Some(&(ref fqp, _)) => {
+                let root = local_data::get(current_location_key, |loc| {
+                    "../".repeat(loc.unwrap().len())
+                });
+                write!(w, "<script type='text/javascript' async \
+                                   src='{root}implementors/{path}/\
+                                        trait.{name}.js'></script>",
+                       root = root,
+                       path = fqp.slice_to(fqp.len() - 1).connect("/"),
+                       name = *fqp.last());
             }
             None => {}
         }
@@ -1205,8 +1477,9 @@ fn item_struct(w: &mut Writer, it: &clean::Item, s: &clean::Struct) {
         doctree::Plain if s.fields.len() > 0 => {
             write!(w, "<h2 class='fields'>Fields</h2>\n<table>");
             for field in s.fields.iter() {
-                write!(w, "<tr><td id='structfield.{name}'>\
+                write!(w, "<tr><td id='structfield.{name}'>{stab}\
                                 <code>{name}</code></td><td>",
+                       stab = StabilityBadge(field),
                        name = field.name.get_ref().as_slice());
                 document(w, field);
                 write!(w, "</td></tr>");
@@ -1264,7 +1537,9 @@ fn item_enum(w: &mut Writer, it: &clean::Item, e: &clean::Enum) {
     if e.variants.len() > 0 {
         write!(w, "<h2 class='variants'>Variants</h2>\n<table>");
         for variant in e.variants.iter() {
-            write!(w, "<tr><td id='variant.{name}'><code>{name}</code></td><td>",
+            write!(w, "<tr><td id='variant.{name}'>{stab}<code>{name}</code>\
+                       </td><td>",
+                   stab = StabilityBadge(variant),
                    name = variant.name.get_ref().as_slice());
             document(w, variant);
             match variant.inner {
@@ -1403,8 +1678,8 @@ fn render_impl(w: &mut Writer, i: &clean::Impl, dox: &Option<~str>) {
     }
 
     fn docmeth(w: &mut Writer, item: &clean::Item) -> bool {
-        write!(w, "<h4 id='method.{}' class='method'><code>",
-               *item.name.get_ref());
+        write!(w, "<h4 id='method.{}' class='method'>{}<code>",
+               *item.name.get_ref(), StabilityBadge(item));
         render_method(w, item, false);
         write!(w, "</code></h4>\n");
         match item.doc_value() {
@@ -1487,6 +1762,17 @@ fn item_typedef(w: &mut Writer, it: &clean::Item, t: &clean::Typedef) {
     document(w, it);
 }
 
+fn item_macro(w: &mut Writer, it: &clean::Item, m: &clean::Macro) {
+    write!(w, "<pre class='macro'>macro_rules! {} \\{\n",
+           it.name.get_ref().as_slice());
+    for matcher in m.matchers.iter() {
+        write!(w, "    {} => \\{ ... \\};\n", Escape(*matcher));
+    }
+    write!(w, "\\}</pre>");
+
+    document(w, it);
+}
+
 impl<'a> fmt::Default for Sidebar<'a> {
     fn fmt(s: &Sidebar<'a>, fmt: &mut fmt::Formatter) {
         let cx = s.cx;
@@ -1531,6 +1817,7 @@ impl<'a> fmt::Default for Sidebar<'a> {
         block(fmt.buf, "enum", "Enums", it, cx);
         block(fmt.buf, "trait", "Traits", it, cx);
         block(fmt.buf, "fn", "Functions", it, cx);
+        block(fmt.buf, "macro", "Macros", it, cx);
     }
 }
 
@@ -543,4 +543,26 @@
     }
 
     initSearch(searchIndex);
+
+    // Implementors of a trait from other crates are loaded from the registry
+    // in the implementors/ directory, whose links are relative to the root
+    window.register_implementors = function(imp) {
+        var list = $('#implementors-list');
+        $.each(imp, function(crate, impls) {
+            var i, code;
+            for (i = 0; i < impls.length; i += 1) {
+                code = $('<code>').append(impls[i]);
+                code.find('a').each(function() {
+                    var href = $(this).attr('href');
+                    if (href && !/^[a-z]+:/.test(href)) {
+                        $(this).attr('href', rootPath + href);
+                    }
+                });
+                list.append($('<li>').append(code));
+            }
+        });
+    };
+    if (window.pending_implementors) {
+        window.register_implementors(window.pending_implementors);
+    }
 }());
@@ -33,6 +33,7 @@ pub mod clean;
 pub mod core;
 pub mod doctree;
 pub mod fold;
+pub mod markdown;
 pub mod html {
     pub mod escape;
     pub mod format;
@@ -84,7 +85,7 @@ pub fn opts() -> ~[groups::OptGroup] {
         optflag("h", "help", "show this help message"),
         optflag("", "version", "print rustdoc's version"),
         optopt("r", "input-format", "the input type of the specified file",
-               "[rust|json]"),
+               "[rust|json|markdown]"),
         optopt("w", "output-format", "the output type to write",
                "[html|json]"),
         optopt("o", "output", "where to place the output", "PATH"),
@@ -133,6 +134,20 @@ pub fn main_args(args: &[~str]) -> int {
     }
     let input = matches.free[0].as_slice();
 
+    let is_markdown = match matches.opt_str("r") {
+        Some(~"markdown") => true,
+        Some(..) => false,
+        None => input.ends_with(".md") || input.ends_with(".markdown"),
+    };
+    if is_markdown {
+        return if matches.opt_present("test") {
+            markdown::test(input, &matches)
+        } else {
+            let output = matches.opt_str("o").map(|s| Path::new(s));
+            markdown::render(input, output.unwrap_or(Path::new("doc")))
+        };
+    }
+
     if matches.opt_present("test") {
         return test::run(input, &matches);
     }
@@ -0,0 +1,139 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Standalone Markdown documents
+//!
+//! Guides and tutorials which live next to the code are rendered with the same
+//! templates as the API documentation. A document may begin with a line of the
+//! form `% Title`, as understood by pandoc, which becomes the title of the
+//! page; otherwise the page is named after the file. The document
+//! `guide.md` is rendered to `<output>/guide/index.html`, alongside its own
+//! copy of the stylesheets and scripts.
+
+use std::cell::RefCell;
+use std::io;
+use std::io::{File, BufferedWriter};
+use std::io::fs;
+use std::str;
+
+use extra::getopts;
+use extra::test;
+
+use html::layout;
+use html::markdown::MarkdownWithToc;
+use html::markdown;
+use html::render;
+
+/// Renders the Markdown file `input` into the directory `output`
+pub fn render(input: &str, output: Path) -> int {
+    let (name, contents) = match load(input) {
+        Ok(pair) => pair,
+        Err(s) => {
+            println!("input error: {}", s);
+            return 1;
+        }
+    };
+    let (title, text) = extract_title(contents);
+    let title = title.unwrap_or(name.as_slice());
+
+    let dst = output.join(name.as_slice());
+    fs::mkdir_recursive(&dst, io::UserRWX);
+    render::write_static_files(&dst);
+    // Pages share the layout of the API documentation, which expects a search
+    // index next to the scripts
+    File::create(&dst.join("search-index.js"))
+        .write(bytes!("var searchIndex = [];var allPaths = {};"));
+
+    let layout = layout::Layout {
+        logo: ~"",
+        favicon: ~"",
+        crate: name.clone(),
+    };
+    let page = layout::Page {
+        title: title,
+        ty: "markdown",
+        root_path: "../",
+    };
+    let mut w = BufferedWriter::new(File::create(&dst.join("index.html")));
+    layout::render(&mut w as &mut io::Writer, &layout, &page, &(""),
+                   &MarkdownWithToc(text));
+    w.flush();
+
+    0
+}
+
+/// Runs the code blocks in the Markdown file `input` as tests
+pub fn test(input: &str, matches: &getopts::Matches) -> int {
+    let (name, contents) = match load(input) {
+        Ok(pair) => pair,
+        Err(s) => {
+            println!("input error: {}", s);
+            return 1;
+        }
+    };
+    let (_, text) = extract_title(contents);
+
+    let libs = matches.opt_strs("L").map(|s| Path::new(s.as_slice()));
+    let libs = @RefCell::new(libs.move_iter().collect());
+    let mut collector = ::test::Collector::new(None, libs, ~[name]);
+    markdown::find_testable_code(text, &mut collector);
+
+    test::test_main(::test::test_args(matches), collector.tests());
+    0
+}
+
+// Reads the document at `input`, returning the name of its page along with
+// its contents
+fn load(input: &str) -> Result<(~str, ~str), ~str> {
+    let path = Path::new(input);
+    let name = match path.filestem_str() {
+        Some(name) => name.to_owned(),
+        None => return Err(format!("{} is not a file", input)),
+    };
+    let contents = match io::result(|| File::open(&path).read_to_end()) {
+        Ok(bytes) => bytes,
+        Err(e) => return Err(format!("couldn't read {}: {}", input, e.desc)),
+    };
+    match str::from_utf8_owned_opt(contents) {
+        Some(s) => Ok((name, s)),
+        None => Err(format!("{} is not UTF-8", input)),
+    }
+}
+
+// Splits off a pandoc-style `% Title` line from the start of a document,
+// along with any other `%` metadata lines which follow it
+fn extract_title<'a>(s: &'a str) -> (Option<&'a str>, &'a str) {
+    if !s.starts_with("%") {
+        return (None, s);
+    }
+    let mut title = None;
+    let mut rest = s;
+    while rest.starts_with("%") {
+        let end = rest.find('\n').unwrap_or(rest.len());
+        if title.is_none() {
+            title = Some(rest.slice(1, end).trim());
+        }
+        rest = rest.slice_from(if end < rest.len() { end + 1 } else { end });
+    }
+    (title, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extract_title;
+
+    #[test]
+    fn title() {
+        assert_eq!(extract_title("% The Guide\n% Authors\n\n# Intro"),
+                   (Some("The Guide"), "\n# Intro"));
+        assert_eq!(extract_title("# Intro\n"), (None, "# Intro\n"));
+        assert_eq!(extract_title("% Only a title"), (Some("Only a title"), ""));
+    }
+}
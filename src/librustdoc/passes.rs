@@ -112,6 +112,9 @@ impl<'a> fold::DocFolder for Stripper<'a> {
 
             // impls/tymethods have no control over privacy
             clean::ImplItem(..) | clean::TyMethodItem(..) => {}
+
+            // only exported macros survive expansion
+            clean::MacroItem(..) => {}
         }
 
         let fastreturn = match i.inner {
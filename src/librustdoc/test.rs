@@ -75,27 +75,25 @@ pub fn run(input: &str, matches: &getopts::Matches) -> int {
     let (crate, _) = passes::unindent_comments(crate);
     let (crate, _) = passes::collapse_docs(crate);
 
-    let mut collector = Collector {
-        tests: ~[],
-        names: ~[],
-        cnt: 0,
-        libs: libs,
-        cratename: crate.name.to_owned(),
-    };
+    let mut collector = Collector::new(Some(crate.name.to_owned()), libs, ~[]);
     collector.fold_crate(crate);
 
+    test::test_main(test_args(matches), collector.tests);
+
+    0
+}
+
+/// The arguments to pass to the test runner, as given by --test-args
+pub fn test_args(matches: &getopts::Matches) -> ~[~str] {
     let args = matches.opt_strs("test-args");
     let mut args = args.iter().flat_map(|s| s.words()).map(|s| s.to_owned());
     let mut args = args.to_owned_vec();
     args.unshift(~"rustdoctest");
-
-    test::test_main(args, collector.tests);
-
-    0
+    args
 }
 
-fn runtest(test: &str, cratename: &str, libs: HashSet<Path>) {
-    let test = maketest(test, cratename);
+fn runtest(test: &str, cratename: Option<~str>, libs: HashSet<Path>) {
+    let test = maketest(test, cratename.as_ref().map(|s| s.as_slice()));
     let parsesess = parse::new_parse_sess(None);
     let input = driver::StrInput(test);
 
@@ -136,7 +134,7 @@ fn runtest(test: &str, cratename: &str, libs: HashSet<Path>) {
     }
 }
 
-fn maketest(s: &str, cratename: &str) -> @str {
+fn maketest(s: &str, cratename: Option<&str>) -> @str {
     let mut prog = ~r"
 #[deny(warnings)];
 #[allow(unused_variable, dead_assignment, unused_mut, attribute_usage, dead_code)];
@@ -144,8 +142,11 @@ fn maketest(s: &str, cratename: &str) -> @str {
     if s.contains("extra") {
         prog.push_str("extern mod extra;\n");
     }
-    if s.contains(cratename) {
-        prog.push_str(format!("extern mod {};\n", cratename));
+    match cratename {
+        Some(cratename) if s.contains(cratename) => {
+            prog.push_str(format!("extern mod {};\n", cratename));
+        }
+        Some(..) | None => {}
     }
     if s.contains("fn main") {
         prog.push_str(s);
@@ -163,17 +164,35 @@ pub struct Collector {
     priv names: ~[~str],
     priv libs: @RefCell<HashSet<Path>>,
     priv cnt: uint,
-    priv cratename: ~str,
+    priv cratename: Option<~str>,
 }
 
 impl Collector {
+    /// Creates a collector whose tests are named after `names`. Examples which
+    /// mention `cratename` are linked against that crate.
+    pub fn new(cratename: Option<~str>, libs: @RefCell<HashSet<Path>>,
+               names: ~[~str]) -> Collector {
+        Collector {
+            tests: ~[],
+            names: names,
+            cnt: 0,
+            libs: libs,
+            cratename: cratename,
+        }
+    }
+
+    /// The tests which have been collected
+    pub fn tests(self) -> ~[test::TestDescAndFn] {
+        self.tests
+    }
+
     pub fn add_test(&mut self, test: &str, ignore: bool, should_fail: bool) {
         let test = test.to_owned();
         let name = format!("{}_{}", self.names.connect("::"), self.cnt);
         self.cnt += 1;
         let libs = self.libs.borrow();
         let libs = (*libs.get()).clone();
-        let cratename = self.cratename.to_owned();
+        let cratename = self.cratename.clone();
         debug!("Creating test {}: {}", name, test);
         self.tests.push(test::TestDescAndFn {
             desc: test::TestDesc {
@@ -280,6 +280,20 @@ impl<'a> RustdocVisitor<'a> {
             ast::ItemForeignMod(ref fm) => {
                 om.foreigns.push(fm.clone());
             }
+            // Only exported macros are left in the crate after expansion
+            ast::ItemMac(ref mac) => {
+                match mac.node {
+                    ast::MacInvocTT(_, ref tts, _) => {
+                        om.macros.push(Macro {
+                            name: item.ident,
+                            rules: tts.clone(),
+                            attrs: item.attrs.clone(),
+                            id: item.id,
+                            where: item.span,
+                        });
+                    }
+                }
+            }
             _ => (),
         }
     }
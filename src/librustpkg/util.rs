@@ -217,16 +217,16 @@ pub fn compile_input(context: &BuildContext,
     debug!("compile_input's sysroot = {}", csysroot.display());
     debug!("sysroot_to_use = {}", sysroot_to_use.display());
 
-    let output_type = match context.compile_upto() {
-        Assemble => link::OutputTypeAssembly,
-        Link     => link::OutputTypeObject,
-        Pretty | Trans | Analysis => link::OutputTypeNone,
-        LLVMAssemble => link::OutputTypeLlvmAssembly,
-        LLVMCompileBitcode => link::OutputTypeBitcode,
-        Nothing => link::OutputTypeExe
+    let output_types = match context.compile_upto() {
+        Assemble => ~[link::OutputTypeAssembly],
+        Link     => ~[link::OutputTypeObject],
+        Pretty | Trans | Analysis => ~[],
+        LLVMAssemble => ~[link::OutputTypeLlvmAssembly],
+        LLVMCompileBitcode => ~[link::OutputTypeBitcode],
+        Nothing => ~[link::OutputTypeExe]
     };
 
-    debug!("Output type = {:?}", output_type);
+    debug!("Output types = {:?}", output_types);
 
     let options = @session::Options {
         optimize: opt,
@@ -234,7 +234,7 @@ pub fn compile_input(context: &BuildContext,
         maybe_sysroot: Some(sysroot_to_use),
         addl_lib_search_paths:
             @RefCell::new(context.additional_library_paths()),
-        output_type: output_type,
+        output_types: output_types,
         .. (*driver::build_session_options(binary,
                                            &matches,
                                            @diagnostic::DefaultEmitter as
@@ -391,10 +391,10 @@ pub fn compile_crate_from_input(input: &Path,
         }
     };
 
-    debug!("Outputs are out_filename: {} and obj_filename: {} and output type = {:?}",
+    debug!("Outputs are out_filename: {} and obj_filename: {} and output types = {:?}",
            outputs.out_filename.display(),
            outputs.obj_filename.display(),
-           sess.opts.output_type);
+           sess.opts.output_types);
     debug!("additional libraries:");
     {
         let addl_lib_search_paths = sess.opts.addl_lib_search_paths.borrow();
@@ -54,7 +54,7 @@ impl GetAddrInfoRequest {
         let hint = hints.map(|hint| {
             let mut flags = 0;
             each_ai_flag(|cval, aival| {
-                if hint.flags & (aival as uint) != 0 {
+                if hint.flags & (1 << (aival as uint)) != 0 {
                     flags |= cval as i32;
                 }
             });
@@ -117,18 +117,16 @@ impl Drop for Addrinfo {
     }
 }
 
-fn each_ai_flag(_f: |c_int, ai::Flag|) {
-    /* XXX: do we really want to support these?
-    unsafe {
-        f(uvll::rust_AI_ADDRCONFIG(), ai::AddrConfig);
-        f(uvll::rust_AI_ALL(), ai::All);
-        f(uvll::rust_AI_CANONNAME(), ai::CanonName);
-        f(uvll::rust_AI_NUMERICHOST(), ai::NumericHost);
-        f(uvll::rust_AI_NUMERICSERV(), ai::NumericServ);
-        f(uvll::rust_AI_PASSIVE(), ai::Passive);
-        f(uvll::rust_AI_V4MAPPED(), ai::V4Mapped);
-    }
-    */
+fn each_ai_flag(f: |c_int, ai::Flag|) {
+    use uvll::ai_flags;
+
+    f(ai_flags::AI_ADDRCONFIG, ai::AddrConfig);
+    f(ai_flags::AI_ALL, ai::All);
+    f(ai_flags::AI_CANONNAME, ai::CanonName);
+    f(ai_flags::AI_NUMERICHOST, ai::NumericHost);
+    f(ai_flags::AI_NUMERICSERV, ai::NumericServ);
+    f(ai_flags::AI_PASSIVE, ai::Passive);
+    f(ai_flags::AI_V4MAPPED, ai::V4Mapped);
 }
 
 // Traverse the addrinfo linked list, producing a vector of Rust socket addresses
@@ -143,7 +141,7 @@ pub fn accum_addrinfo(addr: &Addrinfo) -> ~[ai::Info] {
             let mut flags = 0;
             each_ai_flag(|cval, aival| {
                 if (*addr).ai_flags & cval != 0 {
-                    flags |= aival as uint;
+                    flags |= 1 << (aival as uint);
                 }
             });
 
@@ -184,6 +182,7 @@ pub fn accum_addrinfo(addr: &Addrinfo) -> ~[ai::Info] {
 // cannot give tcp/ip permission without help of apk
 #[cfg(test, not(target_os="android"))]
 mod test {
+    use ai = std::io::net::addrinfo;
     use std::io::net::ip::{SocketAddr, Ipv4Addr};
     use super::super::local_loop;
     use super::GetAddrInfoRequest;
@@ -207,6 +206,19 @@ mod test {
         }
     }
 
+    #[test]
+    fn numeric_host_rejects_names() {
+        let loop_ = &mut local_loop().loop_;
+        let hint = ai::Hint {
+            family: 0,
+            socktype: None,
+            protocol: None,
+            flags: 1 << (ai::NumericHost as uint),
+        };
+        assert!(GetAddrInfoRequest::run(loop_, Some("localhost"), None,
+                                        Some(hint)).is_err());
+    }
+
     #[test]
     fn issue_10663() {
         let loop_ = &mut local_loop().loop_;
@@ -88,6 +88,7 @@ pub mod pipe;
 pub mod tty;
 pub mod signal;
 pub mod stream;
+pub mod timeout;
 
 /// A type that wraps a uv handle
 pub trait UvHandle<T> {
@@ -348,6 +349,7 @@ pub fn uv_error_to_io_error(uverr: UvError) -> IoError {
             uvll::EPIPE => io::BrokenPipe,
             uvll::ECONNABORTED => io::ConnectionAborted,
             uvll::EADDRNOTAVAIL => io::ConnectionRefused,
+            uvll::ETIMEDOUT => io::TimedOut,
             err => {
                 uvdebug!("uverr.code {}", err as int);
                 // XXX: Need to map remaining uv error types
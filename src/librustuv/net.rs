@@ -9,6 +9,7 @@
 // except according to those terms.
 
 use std::cast;
+use std::comm::Select;
 use std::io::IoError;
 use std::io::net::ip::{Ipv4Addr, Ipv6Addr, SocketAddr, IpAddr};
 use std::libc::{size_t, ssize_t, c_int, c_void, c_uint, c_char};
@@ -26,6 +27,8 @@ use stream::StreamWatcher;
 use super::{Loop, Request, UvError, Buf, status_to_io_result,
             uv_error_to_io_error, UvHandle, slice_to_uv_buf,
             wait_until_woken_after, wakeup};
+use timeout::Timeouts;
+use timer::TimerWatcher;
 use uvio::UvIoFactory;
 use uvll;
 use uvll::sockaddr;
@@ -155,6 +158,8 @@ pub struct TcpListener {
 
 pub struct TcpAcceptor {
     listener: ~TcpListener,
+    priv timeout: Option<u64>,
+    priv timer: Option<~TimerWatcher>,
 }
 
 // TCP watchers (clients/streams)
@@ -275,6 +280,33 @@ impl rtio::RtioTcpStream for TcpWatcher {
             uvll::uv_tcp_keepalive(self.handle, 0 as c_int, 0 as c_uint)
         })
     }
+
+    fn set_read_timeout(&mut self, timeout_ms: Option<u64>)
+        -> Result<(), IoError>
+    {
+        let _m = self.fire_homing_missile();
+        self.stream.timeouts.read = timeout_ms;
+        Ok(())
+    }
+
+    fn set_write_timeout(&mut self, timeout_ms: Option<u64>)
+        -> Result<(), IoError>
+    {
+        let _m = self.fire_homing_missile();
+        self.stream.timeouts.write = timeout_ms;
+        Ok(())
+    }
+
+    fn close_write(&mut self) -> Result<(), IoError> {
+        let _m = self.fire_homing_missile();
+        self.stream.shutdown().map_err(uv_error_to_io_error)
+    }
+
+    fn close_read(&mut self) -> Result<(), IoError> {
+        let _m = self.fire_homing_missile();
+        self.stream.close_read();
+        Ok(())
+    }
 }
 
 impl UvHandle<uvll::uv_tcp_t> for TcpWatcher {
@@ -284,6 +316,7 @@ impl UvHandle<uvll::uv_tcp_t> for TcpWatcher {
 impl Drop for TcpWatcher {
     fn drop(&mut self) {
         let _m = self.fire_homing_missile();
+        self.stream.timeouts.close();
         self.close();
     }
 }
@@ -333,7 +366,11 @@ impl rtio::RtioSocket for TcpListener {
 impl rtio::RtioTcpListener for TcpListener {
     fn listen(mut ~self) -> Result<~rtio::RtioTcpAcceptor, IoError> {
         // create the acceptor object from ourselves
-        let mut acceptor = ~TcpAcceptor { listener: self };
+        let mut acceptor = ~TcpAcceptor {
+            listener: self,
+            timeout: None,
+            timer: None,
+        };
 
         let _m = acceptor.fire_homing_missile();
         // XXX: the 128 backlog should be configurable
@@ -383,7 +420,31 @@ impl rtio::RtioSocket for TcpAcceptor {
 
 impl rtio::RtioTcpAcceptor for TcpAcceptor {
     fn accept(&mut self) -> Result<~rtio::RtioTcpStream, IoError> {
-        self.listener.incoming.recv()
+        let ms = match self.timeout {
+            Some(ms) => ms,
+            None => return self.listener.incoming.recv(),
+        };
+
+        // Connections are delivered over a channel, so a timeout is just a
+        // matter of racing the incoming port against a timer.
+        if self.timer.is_none() {
+            let _m = self.fire_homing_missile();
+            let loop_ = Loop::wrap(unsafe {
+                uvll::get_loop_for_uv_handle(self.listener.handle)
+            });
+            let home = self.home().clone();
+            self.timer = Some(TimerWatcher::new_home(&loop_, home));
+        }
+        let mut timeout = self.timer.get_mut_ref().oneshot(ms);
+
+        let s = Select::new();
+        let mut incoming = s.add(&mut self.listener.incoming);
+        let _timeout = s.add(&mut timeout);
+        if s.wait() == incoming.id {
+            incoming.recv()
+        } else {
+            Err(uv_error_to_io_error(UvError(uvll::ETIMEDOUT)))
+        }
     }
 
     fn accept_simultaneously(&mut self) -> Result<(), IoError> {
@@ -399,6 +460,11 @@ impl rtio::RtioTcpAcceptor for TcpAcceptor {
             uvll::uv_tcp_simultaneous_accepts(self.listener.handle, 0)
         })
     }
+
+    fn set_timeout(&mut self, timeout_ms: Option<u64>) -> Result<(), IoError> {
+        self.timeout = timeout_ms;
+        Ok(())
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -408,6 +474,7 @@ impl rtio::RtioTcpAcceptor for TcpAcceptor {
 pub struct UdpWatcher {
     handle: *uvll::uv_udp_t,
     home: HomeHandle,
+    timeouts: Timeouts,
 }
 
 impl UdpWatcher {
@@ -416,6 +483,7 @@ impl UdpWatcher {
         let udp = UdpWatcher {
             handle: unsafe { uvll::malloc_handle(uvll::UV_UDP) },
             home: io.make_handle(),
+            timeouts: Timeouts::new(),
         };
         assert_eq!(unsafe {
             uvll::uv_udp_init(io.uv_loop(), udp.handle)
@@ -453,6 +521,7 @@ impl rtio::RtioUdpSocket for UdpWatcher {
             task: Option<BlockedTask>,
             buf: Option<Buf>,
             result: Option<(ssize_t, Option<SocketAddr>)>,
+            timer: *uvll::uv_timer_t,
         }
         let _m = self.fire_homing_missile();
 
@@ -464,7 +533,15 @@ impl rtio::RtioUdpSocket for UdpWatcher {
                     task: None,
                     buf: Some(slice_to_uv_buf(buf)),
                     result: None,
+                    timer: ptr::null(),
                 };
+                match self.timeouts.read {
+                    Some(ms) => {
+                        cx.timer = self.timeouts.arm(self.handle, ms,
+                                                     timeout_cb, self.handle);
+                    }
+                    None => {}
+                }
                 wait_until_woken_after(&mut cx.task, || {
                     unsafe { uvll::set_data_for_uv_handle(self.handle, &cx) }
                 });
@@ -506,6 +583,9 @@ impl rtio::RtioUdpSocket for UdpWatcher {
             unsafe {
                 assert_eq!(uvll::uv_udp_recv_stop(handle), 0)
             }
+            if !cx.timer.is_null() {
+                unsafe { assert_eq!(uvll::uv_timer_stop(cx.timer), 0) }
+            }
 
             let cx: &mut Ctx = unsafe {
                 cast::transmute(uvll::get_data_for_uv_handle(handle))
@@ -518,15 +598,45 @@ impl rtio::RtioUdpSocket for UdpWatcher {
             cx.result = Some((nread, addr));
             wakeup(&mut cx.task);
         }
+
+        // On a timeout the receive is stopped, so recv_cb won't be invoked
+        extern fn timeout_cb(timer: *uvll::uv_timer_t, status: c_int) {
+            assert_eq!(status, 0);
+            unsafe {
+                let handle = uvll::get_data_for_uv_handle(timer)
+                                as *uvll::uv_udp_t;
+                let cx: &mut Ctx =
+                    cast::transmute(uvll::get_data_for_uv_handle(handle));
+                assert_eq!(uvll::uv_udp_recv_stop(handle), 0);
+                cx.result = Some((uvll::ETIMEDOUT as ssize_t, None));
+                wakeup(&mut cx.task);
+            }
+        }
     }
 
     fn sendto(&mut self, buf: &[u8], dst: SocketAddr) -> Result<(), IoError> {
-        struct Ctx { task: Option<BlockedTask>, result: c_int }
+        // As with stream writes, a send with a timeout owns a copy of its data
+        // which is handed off (along with the request) to a new context if
+        // the timeout fires. This context is freed by send_cb.
+        struct Ctx {
+            task: Option<BlockedTask>,
+            result: c_int,
+            timer: *uvll::uv_timer_t,
+            data: Option<~[u8]>,
+            timed_out: bool,
+        }
 
         let _m = self.fire_homing_missile();
 
         let mut req = Request::new(uvll::UV_UDP_SEND);
-        let buf = slice_to_uv_buf(buf);
+        let data = match self.timeouts.write {
+            Some(..) => Some(buf.to_owned()),
+            None => None,
+        };
+        let buf = match data {
+            Some(ref data) => slice_to_uv_buf(data.as_slice()),
+            None => slice_to_uv_buf(buf),
+        };
         let result = socket_addr_as_sockaddr(dst, |dst| unsafe {
             uvll::uv_udp_send(req.handle, self.handle, [buf], dst, send_cb)
         });
@@ -534,7 +644,20 @@ impl rtio::RtioUdpSocket for UdpWatcher {
         return match result {
             0 => {
                 req.defuse(); // uv callback now owns this request
-                let mut cx = Ctx { task: None, result: 0 };
+                let mut cx = Ctx {
+                    task: None,
+                    result: 0,
+                    timer: ptr::null(),
+                    data: data,
+                    timed_out: false,
+                };
+                match self.timeouts.write {
+                    Some(ms) => {
+                        cx.timer = self.timeouts.arm(self.handle, ms,
+                                                     timeout_cb, req.handle);
+                    }
+                    None => {}
+                }
                 wait_until_woken_after(&mut cx.task, || {
                     req.set_data(&cx);
                 });
@@ -548,11 +671,39 @@ impl rtio::RtioUdpSocket for UdpWatcher {
 
         extern fn send_cb(req: *uvll::uv_udp_send_t, status: c_int) {
             let req = Request::wrap(req);
-            assert!(status != uvll::ECANCELED);
             let cx: &mut Ctx = unsafe { req.get_data() };
+            if cx.timed_out {
+                let _cx: ~Ctx = unsafe { cast::transmute(cx) };
+                return
+            }
+            assert!(status != uvll::ECANCELED);
+            if !cx.timer.is_null() {
+                unsafe { assert_eq!(uvll::uv_timer_stop(cx.timer), 0) }
+            }
             cx.result = status;
             wakeup(&mut cx.task);
         }
+
+        extern fn timeout_cb(timer: *uvll::uv_timer_t, status: c_int) {
+            assert_eq!(status, 0);
+            let mut req = Request::wrap(unsafe {
+                uvll::get_data_for_uv_handle(timer)
+            });
+            let cx: &mut Ctx = unsafe { req.get_data() };
+            let orphan = ~Ctx {
+                task: None,
+                result: 0,
+                timer: ptr::null(),
+                data: cx.data.take(),
+                timed_out: true,
+            };
+            unsafe { req.set_data(cast::transmute::<~Ctx, *Ctx>(orphan)) }
+            req.defuse(); // uv still owns this request
+
+            cx.result = uvll::ETIMEDOUT;
+            cx.timed_out = true;
+            wakeup(&mut cx.task);
+        }
     }
 
     fn join_multicast(&mut self, multi: IpAddr) -> Result<(), IoError> {
@@ -623,12 +774,29 @@ impl rtio::RtioUdpSocket for UdpWatcher {
                                        0 as c_int)
         })
     }
+
+    fn set_read_timeout(&mut self, timeout_ms: Option<u64>)
+        -> Result<(), IoError>
+    {
+        let _m = self.fire_homing_missile();
+        self.timeouts.read = timeout_ms;
+        Ok(())
+    }
+
+    fn set_write_timeout(&mut self, timeout_ms: Option<u64>)
+        -> Result<(), IoError>
+    {
+        let _m = self.fire_homing_missile();
+        self.timeouts.write = timeout_ms;
+        Ok(())
+    }
 }
 
 impl Drop for UdpWatcher {
     fn drop(&mut self) {
         // Send ourselves home to close this handle (blocking while doing so).
         let _m = self.fire_homing_missile();
+        self.timeouts.close();
         self.close();
     }
 }
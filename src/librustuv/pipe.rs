@@ -126,6 +126,17 @@ impl RtioPipe for PipeWatcher {
         let _m = self.fire_homing_missile();
         self.stream.write(buf).map_err(uv_error_to_io_error)
     }
+
+    fn close_write(&mut self) -> Result<(), IoError> {
+        let _m = self.fire_homing_missile();
+        self.stream.shutdown().map_err(uv_error_to_io_error)
+    }
+
+    fn close_read(&mut self) -> Result<(), IoError> {
+        let _m = self.fire_homing_missile();
+        self.stream.close_read();
+        Ok(())
+    }
 }
 
 impl HomingIO for PipeWatcher {
@@ -15,6 +15,7 @@ use std::rt::task::BlockedTask;
 
 use super::{UvError, Buf, slice_to_uv_buf, Request, wait_until_woken_after,
             ForbidUnwind, wakeup};
+use timeout::Timeouts;
 use uvll;
 
 // This is a helper structure which is intended to get embedded into other
@@ -29,17 +30,34 @@ pub struct StreamWatcher {
     // structure, but currently we don't have mappings for all the structures
     // defined in libuv, so we're foced to malloc this.
     priv last_write_req: Option<Request>,
+
+    // The read/write timeouts for this stream. The timer backing these is
+    // closed by the enclosing structure, just like the stream itself.
+    timeouts: Timeouts,
+
+    // Set once the read half of this stream has been closed, after which all
+    // reads will return EOF.
+    priv read_closed: bool,
 }
 
 struct ReadContext {
     buf: Option<Buf>,
     result: ssize_t,
     task: Option<BlockedTask>,
+    timer: *uvll::uv_timer_t,
 }
 
 struct WriteContext {
     result: c_int,
     task: Option<BlockedTask>,
+    timer: *uvll::uv_timer_t,
+
+    // A write can't be canceled once it's been handed to libuv, so a write
+    // which has a timeout keeps its own copy of the data being written. If the
+    // timeout fires then this copy (along with the request) is handed off to a
+    // new context which write_cb frees once the write finally completes.
+    data: Option<~[u8]>,
+    timed_out: bool,
 }
 
 impl StreamWatcher {
@@ -59,10 +77,14 @@ impl StreamWatcher {
         StreamWatcher {
             handle: stream,
             last_write_req: None,
+            timeouts: Timeouts::new(),
+            read_closed: false,
         }
     }
 
     pub fn read(&mut self, buf: &mut [u8]) -> Result<uint, UvError> {
+        if self.read_closed { return Err(UvError(uvll::EOF)) }
+
         // This read operation needs to get canceled on an unwind via libuv's
         // uv_read_stop function
         let _f = ForbidUnwind::new("stream read");
@@ -71,6 +93,7 @@ impl StreamWatcher {
             buf: Some(slice_to_uv_buf(buf)),
             result: 0,
             task: None,
+            timer: ptr::null(),
         };
         // When reading a TTY stream on windows, libuv will invoke alloc_cb
         // immediately as part of the call to alloc_cb. What this means is that
@@ -87,6 +110,14 @@ impl StreamWatcher {
             uvll::uv_read_start(self.handle, alloc_cb, read_cb)
         } {
             0 => {
+                match self.timeouts.read {
+                    Some(ms) => {
+                        rcx.timer = self.timeouts.arm(self.handle, ms,
+                                                      read_timeout_cb,
+                                                      self.handle);
+                    }
+                    None => {}
+                }
                 wait_until_woken_after(&mut rcx.task, || {});
                 match rcx.result {
                     n if n < 0 => Err(UvError(n as c_int)),
@@ -110,20 +141,48 @@ impl StreamWatcher {
         };
         req.set_data(ptr::null::<()>());
 
+        // If there's a timeout, then the data needs to outlive this function
+        let data = match self.timeouts.write {
+            Some(..) => Some(buf.to_owned()),
+            None => None,
+        };
+        let uvbuf = match data {
+            Some(ref data) => slice_to_uv_buf(data.as_slice()),
+            None => slice_to_uv_buf(buf),
+        };
+
         // Send off the request, but be careful to not block until we're sure
         // that the write reqeust is queued. If the reqeust couldn't be queued,
         // then we should return immediately with an error.
         match unsafe {
-            uvll::uv_write(req.handle, self.handle, [slice_to_uv_buf(buf)],
-                           write_cb)
+            uvll::uv_write(req.handle, self.handle, [uvbuf], write_cb)
         } {
             0 => {
-                let mut wcx = WriteContext { result: 0, task: None, };
+                let mut wcx = WriteContext {
+                    result: 0,
+                    task: None,
+                    timer: ptr::null(),
+                    data: data,
+                    timed_out: false,
+                };
                 req.defuse(); // uv callback now owns this request
 
+                match self.timeouts.write {
+                    Some(ms) => {
+                        wcx.timer = self.timeouts.arm(self.handle, ms,
+                                                      write_timeout_cb,
+                                                      req.handle);
+                    }
+                    None => {}
+                }
                 wait_until_woken_after(&mut wcx.task, || {
                     req.set_data(&wcx);
                 });
+                // The request is still in use if the write timed out, so it
+                // can't be cached for the next write.
+                if wcx.timed_out {
+                    return Err(UvError(uvll::ETIMEDOUT))
+                }
                 self.last_write_req = Some(Request::wrap(req.handle));
                 match wcx.result {
                     0 => Ok(()),
@@ -133,6 +192,47 @@ impl StreamWatcher {
             n => Err(UvError(n)),
         }
     }
+
+    // Shuts down the write half of this stream. libuv will wait for all
+    // pending writes to complete before actually shutting down the stream (and
+    // invoking the callback).
+    pub fn shutdown(&mut self) -> Result<(), UvError> {
+        struct Ctx { result: c_int, task: Option<BlockedTask> }
+
+        let _f = ForbidUnwind::new("stream shutdown");
+        let mut req = Request::new(uvll::UV_SHUTDOWN);
+        return match unsafe {
+            uvll::uv_shutdown(req.handle, self.handle, shutdown_cb)
+        } {
+            0 => {
+                req.defuse(); // uv callback now owns this request
+                let mut cx = Ctx { result: 0, task: None };
+                wait_until_woken_after(&mut cx.task, || {
+                    req.set_data(&cx);
+                });
+                match cx.result {
+                    0 => Ok(()),
+                    n => Err(UvError(n)),
+                }
+            }
+            n => Err(UvError(n)),
+        };
+
+        extern fn shutdown_cb(req: *uvll::uv_shutdown_t, status: c_int) {
+            let req = Request::wrap(req);
+            assert!(status != uvll::ECANCELED);
+            let cx: &mut Ctx = unsafe { req.get_data() };
+            cx.result = status;
+            wakeup(&mut cx.task);
+        }
+    }
+
+    // libuv has no way of shutting down the read half of a stream, so this is
+    // emulated by having all future reads return EOF. There's never a pending
+    // read when this is called because the stream is owned by the caller.
+    pub fn close_read(&mut self) {
+        self.read_closed = true;
+    }
 }
 
 // This allocation callback expects to be invoked once and only once. It will
@@ -160,22 +260,77 @@ extern fn read_cb(handle: *uvll::uv_stream_t, nread: ssize_t, _buf: *Buf) {
     // XXX: Is there a performance impact to calling
     // stop here?
     unsafe { assert_eq!(uvll::uv_read_stop(handle), 0); }
+    if !rcx.timer.is_null() {
+        unsafe { assert_eq!(uvll::uv_timer_stop(rcx.timer), 0); }
+    }
     rcx.result = nread;
 
     wakeup(&mut rcx.task);
 }
 
+// If a read times out, then the read is stopped (so read_cb will never be
+// called) and the reading task is woken up with an error. The timer's data is
+// the stream being read.
+extern fn read_timeout_cb(timer: *uvll::uv_timer_t, status: c_int) {
+    assert_eq!(status, 0);
+    unsafe {
+        let handle = uvll::get_data_for_uv_handle(timer) as *uvll::uv_stream_t;
+        let rcx: &mut ReadContext =
+            cast::transmute(uvll::get_data_for_uv_handle(handle));
+        assert_eq!(uvll::uv_read_stop(handle), 0);
+        rcx.result = uvll::ETIMEDOUT as ssize_t;
+        wakeup(&mut rcx.task);
+    }
+}
+
 // Unlike reading, the WriteContext is stored in the uv_write_t request. Like
 // reading, however, all this does is wake up the blocked task after squirreling
 // away the error code as a result.
 extern fn write_cb(req: *uvll::uv_write_t, status: c_int) {
     let mut req = Request::wrap(req);
+    let wcx: &mut WriteContext = unsafe { req.get_data() };
+
+    // If this write previously timed out, then nobody is waiting for it any
+    // more. All that's left to do is to free the context and the request
+    // (which isn't going to be re-used). The stream may have been closed in
+    // the meantime, so this write may have been canceled.
+    if wcx.timed_out {
+        let _wcx: ~WriteContext = unsafe { cast::transmute(wcx) };
+        return
+    }
+
     assert!(status != uvll::ECANCELED);
+    if !wcx.timer.is_null() {
+        unsafe { assert_eq!(uvll::uv_timer_stop(wcx.timer), 0); }
+    }
     // Remember to not free the request because it is re-used between writes on
     // the same stream.
-    let wcx: &mut WriteContext = unsafe { req.get_data() };
     wcx.result = status;
     req.defuse();
 
     wakeup(&mut wcx.task);
 }
+
+// When a write times out, the write request is left to complete in the
+// background. Its data is moved into a heap-allocated context (to be freed by
+// write_cb), and the writing task is woken up with an error. The timer's data
+// is the write request.
+extern fn write_timeout_cb(timer: *uvll::uv_timer_t, status: c_int) {
+    assert_eq!(status, 0);
+    let mut req = Request::wrap(unsafe { uvll::get_data_for_uv_handle(timer) });
+    let wcx: &mut WriteContext = unsafe { req.get_data() };
+    let orphan = ~WriteContext {
+        result: 0,
+        task: None,
+        timer: ptr::null(),
+        data: wcx.data.take(),
+        timed_out: true,
+    };
+    unsafe {
+        req.set_data(cast::transmute::<~WriteContext, *WriteContext>(orphan));
+    }
+    req.defuse(); // uv still owns this request
+
+    wcx.timed_out = true;
+    wakeup(&mut wcx.task);
+}
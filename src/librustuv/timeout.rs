@@ -0,0 +1,69 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Timeouts for I/O operations
+//!
+//! An I/O object which has a timeout configured arms a uv timer alongside each
+//! operation that it performs. The timer's callback and the operation's
+//! callback both run on the event loop, so whichever of the two fires first is
+//! free to cancel the other one (and wake up the blocked task) without any
+//! further synchronization.
+
+use std::ptr;
+
+use uvll;
+
+pub struct Timeouts {
+    read: Option<u64>,
+    write: Option<u64>,
+
+    // Lazily allocated the first time an operation with a timeout is
+    // performed. This is null if no timeout has ever been used.
+    priv timer: *uvll::uv_timer_t,
+}
+
+impl Timeouts {
+    pub fn new() -> Timeouts {
+        Timeouts { read: None, write: None, timer: ptr::null() }
+    }
+
+    // Starts the timer such that `cb` is invoked after `ms` milliseconds with
+    // `data` as the timer's data, returning the timer so the operation can
+    // stop it if it completes first. The timer is created on the same event
+    // loop as `handle` the first time it's needed.
+    pub fn arm<T, U>(&mut self, handle: *T, ms: u64, cb: uvll::uv_timer_cb,
+                     data: *U) -> *uvll::uv_timer_t {
+        unsafe {
+            if self.timer.is_null() {
+                let timer = uvll::malloc_handle(uvll::UV_TIMER);
+                let loop_ = uvll::get_loop_for_uv_handle(handle);
+                assert_eq!(uvll::uv_timer_init(loop_, timer), 0);
+                self.timer = timer;
+            }
+            uvll::set_data_for_uv_handle(self.timer, data);
+            assert_eq!(uvll::uv_timer_start(self.timer, cb, ms, 0), 0);
+            self.timer
+        }
+    }
+
+    // Releases the timer. Like the handle that owns these timeouts, this must
+    // be called from the handle's home event loop.
+    pub fn close(&mut self) {
+        extern fn close_cb(handle: *uvll::uv_handle_t) {
+            unsafe { uvll::free_handle(handle) }
+        }
+
+        if self.timer.is_null() { return }
+        unsafe {
+            uvll::uv_close(self.timer as *uvll::uv_handle_t, close_cb);
+        }
+        self.timer = ptr::null();
+    }
+}
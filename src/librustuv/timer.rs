@@ -15,7 +15,7 @@ use std::rt::task::{BlockedTask, Task};
 use std::util;
 
 use homing::{HomeHandle, HomingIO};
-use super::{UvHandle, ForbidUnwind, ForbidSwitch};
+use super::{Loop, UvHandle, ForbidUnwind, ForbidSwitch};
 use uvio::UvIoFactory;
 use uvll;
 
@@ -34,14 +34,19 @@ pub enum NextAction {
 
 impl TimerWatcher {
     pub fn new(io: &mut UvIoFactory) -> ~TimerWatcher {
+        let home = io.make_handle();
+        TimerWatcher::new_home(&io.loop_, home)
+    }
+
+    pub fn new_home(loop_: &Loop, home: HomeHandle) -> ~TimerWatcher {
         let handle = UvHandle::alloc(None::<TimerWatcher>, uvll::UV_TIMER);
         assert_eq!(unsafe {
-            uvll::uv_timer_init(io.uv_loop(), handle)
+            uvll::uv_timer_init(loop_.handle, handle)
         }, 0);
         let me = ~TimerWatcher {
             handle: handle,
             action: None,
-            home: io.make_handle(),
+            home: home,
             id: 0,
         };
         return me.install();
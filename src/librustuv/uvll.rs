@@ -39,7 +39,8 @@ use std::rt::global_heap::malloc_raw;
 use std::libc::uintptr_t;
 
 pub use self::errors::{EACCES, ECONNREFUSED, ECONNRESET, EPIPE, ECONNABORTED,
-                       ECANCELED, EBADF, ENOTCONN, ENOENT, EADDRNOTAVAIL};
+                       ECANCELED, EBADF, ENOTCONN, ENOENT, EADDRNOTAVAIL,
+                       ETIMEDOUT};
 
 pub static OK: c_int = 0;
 pub static EOF: c_int = -4095;
@@ -62,6 +63,7 @@ pub mod errors {
     pub static ECANCELED: c_int = -4081;
     pub static EBADF: c_int = -4083;
     pub static EADDRNOTAVAIL: c_int = -4090;
+    pub static ETIMEDOUT: c_int = -4039;
 }
 #[cfg(not(windows))]
 pub mod errors {
@@ -78,6 +80,7 @@ pub mod errors {
     pub static ECANCELED : c_int = -libc::ECANCELED;
     pub static EBADF : c_int = -libc::EBADF;
     pub static EADDRNOTAVAIL : c_int = -libc::EADDRNOTAVAIL;
+    pub static ETIMEDOUT : c_int = -libc::ETIMEDOUT;
 }
 
 pub static PROCESS_SETUID: c_int = 1 << 0;
@@ -148,6 +151,7 @@ pub type uv_udp_t = c_void;
 pub type uv_connect_t = c_void;
 pub type uv_connection_t = c_void;
 pub type uv_write_t = c_void;
+pub type uv_shutdown_t = c_void;
 pub type uv_async_t = c_void;
 pub type uv_timer_t = c_void;
 pub type uv_stream_t = c_void;
@@ -240,6 +244,8 @@ pub type uv_timer_cb = extern "C" fn(handle: *uv_timer_t,
                                      status: c_int);
 pub type uv_write_cb = extern "C" fn(handle: *uv_write_t,
                                      status: c_int);
+pub type uv_shutdown_cb = extern "C" fn(req: *uv_shutdown_t,
+                                        status: c_int);
 pub type uv_getaddrinfo_cb = extern "C" fn(req: *uv_getaddrinfo_t,
                                            status: c_int,
                                            res: *addrinfo);
@@ -294,6 +300,47 @@ pub struct addrinfo {
     ai_next: *addrinfo
 }
 
+#[cfg(target_os = "android")]
+#[cfg(target_os = "linux")]
+pub mod ai_flags {
+    use std::libc::c_int;
+    pub static AI_PASSIVE: c_int = 0x0001;
+    pub static AI_CANONNAME: c_int = 0x0002;
+    pub static AI_NUMERICHOST: c_int = 0x0004;
+    pub static AI_V4MAPPED: c_int = 0x0008;
+    pub static AI_ALL: c_int = 0x0010;
+    pub static AI_ADDRCONFIG: c_int = 0x0020;
+    pub static AI_NUMERICSERV: c_int = 0x0400;
+}
+
+#[cfg(target_os = "macos")]
+#[cfg(target_os = "freebsd")]
+pub mod ai_flags {
+    use std::libc::c_int;
+    pub static AI_PASSIVE: c_int = 0x0001;
+    pub static AI_CANONNAME: c_int = 0x0002;
+    pub static AI_NUMERICHOST: c_int = 0x0004;
+    pub static AI_ALL: c_int = 0x0100;
+    pub static AI_ADDRCONFIG: c_int = 0x0400;
+    pub static AI_V4MAPPED: c_int = 0x0800;
+    #[cfg(target_os = "macos")]
+    pub static AI_NUMERICSERV: c_int = 0x1000;
+    #[cfg(target_os = "freebsd")]
+    pub static AI_NUMERICSERV: c_int = 0x0008;
+}
+
+#[cfg(windows)]
+pub mod ai_flags {
+    use std::libc::c_int;
+    pub static AI_PASSIVE: c_int = 0x0001;
+    pub static AI_CANONNAME: c_int = 0x0002;
+    pub static AI_NUMERICHOST: c_int = 0x0004;
+    pub static AI_NUMERICSERV: c_int = 0x0008;
+    pub static AI_ALL: c_int = 0x0100;
+    pub static AI_ADDRCONFIG: c_int = 0x0400;
+    pub static AI_V4MAPPED: c_int = 0x0800;
+}
+
 #[cfg(unix)] pub type uv_uid_t = libc::types::os::arch::posix88::uid_t;
 #[cfg(unix)] pub type uv_gid_t = libc::types::os::arch::posix88::gid_t;
 #[cfg(windows)] pub type uv_uid_t = libc::c_uchar;
@@ -593,6 +640,8 @@ extern {
                          on_alloc: uv_alloc_cb,
                          on_read: uv_read_cb) -> c_int;
     pub fn uv_read_stop(stream: *uv_stream_t) -> c_int;
+    pub fn uv_shutdown(req: *uv_shutdown_t, stream: *uv_stream_t,
+                       cb: uv_shutdown_cb) -> c_int;
 
     // idle bindings
     pub fn uv_idle_init(l: *uv_loop_t, i: *uv_idle_t) -> c_int;
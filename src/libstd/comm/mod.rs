@@ -15,21 +15,28 @@
 //! communication between concurrent tasks. The primitives defined in this
 //! module are the building blocks for synchronization in rust.
 //!
-//! This module currently provides three main types:
+//! This module currently provides four main types:
 //!
 //! * `Chan`
 //! * `Port`
 //! * `SharedChan`
+//! * `SyncChan`
 //!
-//! The `Chan` and `SharedChan` types are used to send data to a `Port`. A
-//! `SharedChan` is clone-able such that many tasks can send simultaneously to
-//! one receiving port. These communication primitives are *task blocking*, not
-//! *thread blocking*. This means that if one task is blocked on a channel,
-//! other tasks can continue to make progress.
+//! The `Chan`, `SharedChan` and `SyncChan` types are used to send data to a
+//! `Port`. A `SharedChan` is clone-able such that many tasks can send
+//! simultaneously to one receiving port. These communication primitives are
+//! *task blocking*, not *thread blocking*. This means that if one task is
+//! blocked on a channel, other tasks can continue to make progress.
 //!
-//! Rust channels can be used as if they have an infinite internal buffer. What
-//! this means is that the `send` operation will never block. `Port`s, on the
-//! other hand, will block the task if there is no data to be received.
+//! `Chan` and `SharedChan` can be used as if they have an infinite internal
+//! buffer. What this means is that the `send` operation will never block.
+//! `Port`s, on the other hand, will block the task if there is no data to be
+//! received.
+//!
+//! A `SyncChan` has a buffer of a fixed size instead, and its `send` operation
+//! will block the task while the buffer is full until the port receives some
+//! of the data. This keeps a fast sender from getting arbitrarily far ahead of
+//! its receiver.
 //!
 //! ## Failure Propagation
 //!
@@ -42,6 +49,12 @@
 //! There are methods on all of `Chan`, `SharedChan`, and `Port` to perform
 //! their respective operations without failing, however.
 //!
+//! ## Timeouts
+//!
+//! A port can be waited on for a limited amount of time with `recv_timeout`.
+//! To wait on several ports with a timeout, a oneshot port from an
+//! `io::Timer` can be added to a `Select` alongside them.
+//!
 //! ## Outside the Runtime
 //!
 //! All channels and ports work seamlessly inside and outside of the rust
@@ -79,6 +92,17 @@
 //!     assert!(0 <= j && j < 10);
 //! }
 //!
+//! // Create a channel which buffers at most two values
+//! let (port, chan) = SyncChan::new(2);
+//! do spawn {
+//!     for i in range(0, 10) {
+//!         chan.send(i); // blocks while two values are waiting
+//!     }
+//! }
+//! for i in range(0, 10) {
+//!     assert_eq!(port.recv(), i);
+//! }
+//!
 //! // The call to recv() will fail!() because the channel has already hung
 //! // up (or been deallocated)
 //! let (port, chan) = Chan::<int>::new();
@@ -220,6 +244,30 @@
 // believe that there is anything fundamental which needs to change about these
 // channels, however, in order to support a more efficient select().
 //
+// ## Bounded channels
+//
+// A `SyncChan` is a stream whose packet additionally counts the free slots in
+// its buffer (the `space` field). This is the mirror image of the channel
+// count: the sender takes a slot before each send, and the port hands a slot
+// back after each receive. The protocol for blocking is the same as for
+// receiving, only with the roles reversed:
+//
+//      send(t)                             recv()
+//        take_slot() or deschedule {         t = queue.pop()
+//          if space.decrement() > 0          if space.increment() == -1
+//            cancel_deschedule()               wakeup(sender)
+//        }                                   return t
+//        queue.push(t) ...
+//
+// Which is to say that if the port sees -1 free slots, then the sender is
+// guaranteed to be waiting in `send_to_wake`, and the slot which the port just
+// released is handed directly over to it. A port hanging up stores
+// DISCONNECTED into the count of free slots, waking up the sender if it was
+// waiting for a slot, so the sender gets its value back.
+//
+// As the receiving half is an ordinary port, selection over a bounded channel
+// works exactly as it does for any other stream.
+//
 // # Conclusion
 //
 // And now that you've seen all the races that I found and attempted to fix,
@@ -229,11 +277,12 @@ use cast;
 use clone::Clone;
 use container::Container;
 use int;
+use io::timer::Timer;
 use iter::Iterator;
 use kinds::Send;
 use ops::Drop;
 use option::{Option, Some, None};
-use result::{Ok, Err};
+use result::{Result, Ok, Err};
 use rt::local::Local;
 use rt::task::{Task, BlockedTask};
 use rt::thread::Thread;
@@ -324,6 +373,25 @@ pub struct SharedChan<T> {
     priv queue: mpsc::Producer<T, Packet>,
 }
 
+/// The sending-half of a bounded channel. Only a fixed number of values may be
+/// buffered on a bounded channel at once, and sending on it blocks while its
+/// buffer is full. This half can only be owned by one task.
+#[no_freeze] // can't share chans in an arc
+pub struct SyncChan<T> {
+    priv chan: Chan<T>,
+}
+
+/// This enumeration is the list of the possible results of `SyncChan.try_send`
+#[deriving(Eq, Clone)]
+pub enum TrySendResult<T> {
+    /// The value was placed in the channel's buffer
+    Sent,
+    /// The channel's buffer is full, so the value is handed back
+    Full(T),
+    /// The channel's port has hung up, so the value is handed back
+    Closed(T),
+}
+
 /// This enumeration is the list of the possible reasons that try_recv could not
 /// return data when called.
 #[deriving(Eq, Clone)]
@@ -361,6 +429,12 @@ struct Packet {
     select_next: *mut Packet,
     select_prev: *mut Packet,
     recv_cnt: int,
+
+    // The size of the buffer of a bounded channel, or 0 if it's unbounded,
+    // and how much of it is free.
+    bound: uint,
+    space: AtomicInt,
+    send_to_wake: Option<BlockedTask>, // Sender waiting for a free slot
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -383,9 +457,21 @@ impl Packet {
             select_next: 0 as *mut Packet,
             select_prev: 0 as *mut Packet,
             recv_cnt: 0,
+
+            bound: 0,
+            space: AtomicInt::new(0),
+            send_to_wake: None,
         }
     }
 
+    fn bounded(bound: uint) -> Packet {
+        assert!(bound > 0, "bounded channels need a buffer of at least one");
+        let mut p = Packet::new();
+        p.bound = bound;
+        p.space = AtomicInt::new(bound as int);
+        p
+    }
+
     // Increments the channel size count, preserving the disconnected state if
     // the other end has disconnected.
     fn increment(&mut self) -> int {
@@ -504,6 +590,57 @@ impl Packet {
         }
     }
 
+    // Takes one of the free slots of a bounded channel without blocking. If
+    // there isn't one, returns whether that's because the port has hung up.
+    fn try_take_slot(&mut self) -> Result<(), bool> {
+        match self.space.load(SeqCst) {
+            DISCONNECTED => Err(true),
+            n if n <= 0 => Err(false),
+            // Only the sender ever takes slots, so one is sure to still be
+            // free, unless the port is hanging up concurrently
+            _ => match self.space.fetch_sub(1, SeqCst) {
+                DISCONNECTED => {
+                    self.space.store(DISCONNECTED, SeqCst);
+                    Err(true)
+                }
+                n => { assert!(n > 0); Ok(()) }
+            }
+        }
+    }
+
+    // Prepares the sender to sleep until a slot is freed, much as decrement()
+    // does for the receiver. Returns whether the sender should block; if not,
+    // then either a slot was released in the meantime, which now belongs to
+    // the sender, or the port has hung up.
+    fn decrement_space(&mut self) -> bool {
+        match self.space.fetch_sub(1, SeqCst) {
+            DISCONNECTED => {
+                self.space.store(DISCONNECTED, SeqCst);
+                false
+            }
+            n => { assert!(n >= 0); n == 0 }
+        }
+    }
+
+    // Hands the slot of a value which the port has just received back to the
+    // sender of a bounded channel, waking up the sender if it's waiting for
+    // the slot.
+    fn release_slot(&mut self) {
+        if self.bound == 0 { return }
+        match self.space.fetch_add(1, SeqCst) {
+            -1 => self.wakeup_sender(false),
+            n => assert!(n >= 0),
+        }
+    }
+
+    // Like wakeup(), this must have had at least an acquire fence before it.
+    fn wakeup_sender(&mut self, can_resched: bool) {
+        match self.send_to_wake.take_unwrap().wake() {
+            Some(task) => task.reawaken(can_resched),
+            None => {}
+        }
+    }
+
     // Decrement the reference count on a channel. This is called whenever a
     // Chan is dropped and may end up waking up a receiver. It's the receiver's
     // responsibility on the other end to figure out that we've disconnected.
@@ -531,6 +668,7 @@ impl Drop for Packet {
             // the `to_wake` assert.
             assert_eq!(self.cnt.load(SeqCst), DISCONNECTED);
             assert!(self.to_wake.is_none());
+            assert!(self.send_to_wake.is_none());
             assert_eq!(self.channels.load(SeqCst), 0);
         }
     }
@@ -632,6 +770,88 @@ impl<T: Send> Drop for Chan<T> {
     }
 }
 
+impl<T: Send> SyncChan<T> {
+    /// Creates a new port/channel pair whose channel can only buffer `bound`
+    /// values at once. Once that many values have been sent without being
+    /// received, sending on the channel blocks until the port receives one.
+    ///
+    /// # Failure
+    ///
+    /// This function will fail if `bound` is 0.
+    pub fn new(bound: uint) -> (Port<T>, SyncChan<T>) {
+        // The queue never holds more than `bound` values, so cache as many
+        // nodes as it will ever need (up to the same limit as `Chan`)
+        let cache = if bound < 128 { bound } else { 128 };
+        let (c, p) = spsc::queue(cache, Packet::bounded(bound));
+        let c = SPSC(c);
+        (Port { queue: c }, SyncChan { chan: Chan { queue: p } })
+    }
+
+    /// Sends a value along this channel to be received by the corresponding
+    /// port, blocking first if the channel's buffer is full.
+    ///
+    /// # Failure
+    ///
+    /// This function will fail if the other end of the channel has hung up,
+    /// including if it hangs up while this function is blocked. As with
+    /// `Chan.send`, not failing does not mean that the data will be received.
+    pub fn send(&self, t: T) {
+        if self.send_opt(t).is_err() {
+            fail!("sending on a closed channel");
+        }
+    }
+
+    /// Equivalent to `send`, except that rather than failing if the other end
+    /// of the channel has hung up, the value is returned in an `Err`.
+    pub fn send_opt(&self, t: T) -> Result<(), T> {
+        let packet;
+        unsafe {
+            packet = self.chan.queue.packet();
+            match (*packet).try_take_slot() {
+                Ok(()) => {}
+                Err(true) => return Err(t),
+                Err(false) => {
+                    let task: ~Task = Local::take();
+                    task.deschedule(1, |task| {
+                        assert!((*packet).send_to_wake.is_none());
+                        (*packet).send_to_wake = Some(task);
+                        if (*packet).decrement_space() {
+                            Ok(())
+                        } else {
+                            Err((*packet).send_to_wake.take_unwrap())
+                        }
+                    });
+
+                    // Either the port handed us a slot, or it has hung up
+                    if (*packet).space.load(SeqCst) == DISCONNECTED {
+                        return Err(t);
+                    }
+                }
+            }
+        }
+        // Even though it hadn't hung up a moment ago, the port may do so
+        // before the value is queued, in which case it's dropped along with
+        // the channel as for `Chan`.
+        self.chan.try(t, true);
+        Ok(())
+    }
+
+    /// Attempts to send a value on this channel without blocking. If the
+    /// channel's buffer is full or its port has hung up, the value is returned
+    /// to the caller.
+    ///
+    /// As with `Chan.try_send`, a return value of `Sent` does *not* mean that
+    /// the value will be received, since the port may hang up at any time.
+    pub fn try_send(&self, t: T) -> TrySendResult<T> {
+        let packet = unsafe { self.chan.queue.packet() };
+        match unsafe { (*packet).try_take_slot() } {
+            Ok(()) => { self.chan.try(t, false); Sent }
+            Err(true) => Closed(t),
+            Err(false) => Full(t),
+        }
+    }
+}
+
 impl<T: Send> SharedChan<T> {
     /// Creates a new shared channel and port pair. The purpose of a shared
     /// channel is to be cloneable such that many tasks can send data at the
@@ -823,7 +1043,10 @@ impl<T: Send> Port<T> {
             unsafe { (*this.queue.packet()).steals += 1; }
         }
         match ret {
-            Some(t) => Data(t),
+            Some(t) => {
+                unsafe { (*this.queue.packet()).release_slot(); }
+                Data(t)
+            }
             None => {
                 // It's possible that between the time that we saw the queue was
                 // empty and here the other side disconnected. It's also
@@ -846,7 +1069,10 @@ impl<T: Send> Port<T> {
                     }
                 };
                 match ret {
-                    Some(data) => Data(data),
+                    Some(data) => {
+                        unsafe { (*this.queue.packet()).release_slot(); }
+                        Data(data)
+                    }
                     None => Disconnected,
                 }
             }
@@ -897,6 +1123,44 @@ impl<T: Send> Port<T> {
         }
     }
 
+    /// Blocks waiting for a value on this port for at most `msecs`
+    /// milliseconds.
+    ///
+    /// This has the same semantics as `try_recv`, except that if there's no
+    /// data available then it waits for some to arrive (or for the channel to
+    /// hang up) until the timeout expires, after which `Empty` is returned.
+    ///
+    /// This is implemented by selecting over this port and a oneshot port of
+    /// an `io::Timer`, so it works both inside and outside of a green
+    /// scheduler. The same can be done directly with a `Select` in order to
+    /// wait on several ports with a timeout.
+    ///
+    /// # Failure
+    ///
+    /// This function will fail if a timer can't be created, for example if
+    /// the current task has no local I/O available.
+    pub fn recv_timeout(&self, msecs: u64) -> TryRecvResult<T> {
+        // optimistic preflight check (timers are more expensive still)
+        match self.try_recv() {
+            Empty => {}
+            ret => return ret,
+        }
+
+        let mut timer = Timer::new().expect("recv_timeout: could not create a Timer");
+        let mut timeout = timer.oneshot(msecs);
+        let this = unsafe { cast::transmute_mut(self) };
+
+        let sel = Select::new();
+        let mut port = sel.add(this);
+        let _timeout = sel.add(&mut timeout);
+        if sel.wait() == port.id {
+            // The port is ready, so this is either data or a disconnection
+            port.try_recv()
+        } else {
+            Empty
+        }
+    }
+
     /// Returns an iterator which will block waiting for messages, but never
     /// `fail!`. It will return `None` when the channel has hung up.
     pub fn iter<'a>(&'a self) -> Messages<'a, T> {
@@ -915,7 +1179,17 @@ impl<T: Send> Drop for Port<T> {
         // half has already disconnected, then we'll just deallocate everything
         // when the shared packet is deallocated.
         unsafe {
-            (*self.queue.packet()).cnt.store(DISCONNECTED, SeqCst);
+            let packet = self.queue.packet();
+            (*packet).cnt.store(DISCONNECTED, SeqCst);
+
+            // A sender blocked on a full bounded channel has to be woken up
+            // so that it can find out that it's been hung up on
+            if (*packet).bound > 0 {
+                match (*packet).space.swap(DISCONNECTED, SeqCst) {
+                    -1 => (*packet).wakeup_sender(true),
+                    _ => {}
+                }
+            }
         }
     }
 }
@@ -1393,4 +1667,142 @@ mod test {
         p2.recv();
         assert_eq!(p.try_recv(), Disconnected);
     })
+
+    test!(fn sync_smoke() {
+        let (p, c) = SyncChan::new(1);
+        c.send(1);
+        assert_eq!(p.recv(), 1);
+    })
+
+    test!(fn sync_drop_full() {
+        let (_p, c) = SyncChan::new(2);
+        c.send(~1);
+        c.send(~2);
+    })
+
+    test!(fn sync_port_gone() {
+        let (p, c) = SyncChan::new(1);
+        drop(p);
+        c.send(1);
+    } #[should_fail])
+
+    test!(fn sync_chan_gone() {
+        let (p, c) = SyncChan::<int>::new(1);
+        drop(c);
+        p.recv();
+    } #[should_fail])
+
+    test!(fn sync_try_send() {
+        let (p, c) = SyncChan::new(2);
+        assert_eq!(c.try_send(1), Sent);
+        assert_eq!(c.try_send(2), Sent);
+        assert_eq!(c.try_send(3), Full(3));
+        assert_eq!(p.recv(), 1);
+        assert_eq!(c.try_send(3), Sent);
+        assert_eq!(c.try_send(4), Full(4));
+        assert_eq!(p.try_recv(), Data(2));
+        assert_eq!(p.recv(), 3);
+        drop(p);
+        assert_eq!(c.try_send(5), Closed(5));
+        assert_eq!(c.send_opt(5), Err(5));
+    })
+
+    test!(fn sync_send_blocks() {
+        let (p, c) = SyncChan::new(1);
+        let (p1, c1) = Chan::new();
+        do spawn {
+            c.send(1);
+            c.send(2);
+            c1.send(());
+        }
+
+        // The second send can't complete until the first value is received
+        20.times(task::deschedule);
+        assert_eq!(p1.try_recv(), Empty);
+        assert_eq!(p.recv(), 1);
+        assert_eq!(p.recv(), 2);
+        p1.recv();
+    })
+
+    test!(fn sync_port_gone_while_blocked() {
+        let (p, c) = SyncChan::new(1);
+        let (p1, c1) = Chan::new();
+        let (p2, c2) = Chan::new();
+        do spawn {
+            c.send(1);
+            c1.send(());
+            assert_eq!(c.send_opt(2), Err(2));
+            c2.send(());
+        }
+
+        p1.recv();
+        20.times(task::deschedule);
+        drop(p);
+        p2.recv();
+    })
+
+    test!(fn sync_stress() {
+        let (p, c) = SyncChan::new(3);
+        do spawn {
+            for i in range(0, 10000) { c.send(i); }
+        }
+        for i in range(0, 10000) {
+            assert_eq!(p.recv(), i);
+        }
+    })
+
+    test!(fn sync_stress_threads() {
+        let amt = 10000 * stress_factor();
+        let (p, c) = SyncChan::new(1);
+        do native::task::spawn {
+            for i in range(0, amt) { c.send(i); }
+        }
+        for i in range(0, amt) {
+            assert_eq!(p.recv(), i);
+        }
+    })
+
+    test!(fn recv_timeout_data() {
+        let (p, c) = Chan::new();
+        c.send(1);
+        assert_eq!(p.recv_timeout(100000), Data(1));
+    })
+
+    test!(fn recv_timeout_expires() {
+        let (p, c) = Chan::<int>::new();
+        assert_eq!(p.recv_timeout(10), Empty);
+        assert_eq!(p.recv_timeout(0), Empty);
+
+        // the port is still usable afterwards
+        c.send(2);
+        assert_eq!(p.recv_timeout(10), Data(2));
+    })
+
+    test!(fn recv_timeout_disconnected() {
+        let (p, c) = Chan::<int>::new();
+        drop(c);
+        assert_eq!(p.recv_timeout(100000), Disconnected);
+    })
+
+    test!(fn recv_timeout_wakes_up() {
+        let (p, c) = SharedChan::new();
+        let (p1, c1) = Chan::new();
+        do spawn {
+            p1.recv();
+            c.send(1);
+        }
+        c1.send(());
+        assert_eq!(p.recv_timeout(100000), Data(1));
+        assert_eq!(p.recv_timeout(100000), Disconnected);
+    })
+
+    test!(fn recv_timeout_sync() {
+        let (p, c) = SyncChan::new(1);
+        c.send(1);
+        do spawn {
+            c.send(2);
+        }
+        assert_eq!(p.recv_timeout(100000), Data(1));
+        assert_eq!(p.recv_timeout(100000), Data(2));
+    })
 }
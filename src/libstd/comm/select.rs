@@ -23,6 +23,13 @@
 //! received values of ports in a much more natural syntax then usage of the
 //! `Select` structure directly.
 //!
+//! The port of a bounded channel (`SyncChan`) can be selected over just like
+//! any other port. Receiving a value through its handle frees up a slot in the
+//! channel's buffer, waking up the sender if it's blocked.
+//!
+//! A timeout can be included in a set by adding the port returned by
+//! `io::Timer::oneshot`, which receives a value once the timer expires.
+//!
 //! # Example
 //!
 //! ```rust,ignore
@@ -417,6 +424,51 @@ mod test {
         c3.send(());
     })
 
+    test!(fn sync() {
+        let (mut p1, c1) = SyncChan::<int>::new(1);
+        let (mut p2, _c2) = Chan::<int>::new();
+        let (p3, c3) = Chan::<()>::new();
+
+        do spawn {
+            c1.send(1);
+            c1.send(2); // blocks until 1 has been selected
+            c3.send(());
+        }
+
+        select! (
+            a = p1.recv() => { assert_eq!(a, 1); },
+            _b = p2.recv() => { fail!() }
+        )
+        select! (
+            a = p1.recv() => { assert_eq!(a, 2); },
+            _b = p2.recv() => { fail!() }
+        )
+        p3.recv();
+        select! (
+            a = p1.recv_opt() => { assert_eq!(a, None); },
+            _b = p2.recv() => { fail!() }
+        )
+    })
+
+    test!(fn timeout() {
+        use io::timer::Timer;
+
+        let (mut p1, c1) = Chan::<int>::new();
+        let mut timer = Timer::new().unwrap();
+        let mut timeout = timer.oneshot(10);
+        select! (
+            _a = p1.recv() => { fail!() },
+            () = timeout.recv() => {}
+        )
+
+        c1.send(1);
+        let mut timeout = timer.oneshot(100000);
+        select! (
+            a = p1.recv() => { assert_eq!(a, 1); },
+            () = timeout.recv() => { fail!() }
+        )
+    })
+
     test!(fn stress() {
         static AMT: int = 10000;
         let (mut p1, c1) = Chan::<int>::new();
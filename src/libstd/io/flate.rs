@@ -8,45 +8,746 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-//! Some various other I/O types
-
-// FIXME(#3660): should move to libextra
+//! Streaming compression and decompression using the 'deflate' scheme
+//!
+//! `DeflateWriter` and `InflateReader` wrap any `Writer` or `Reader` and
+//! compress or decompress data incrementally as it passes through them, so a
+//! stream never has to be held in memory all at once. Each type understands
+//! three stream formats, selected by its constructor:
+//!
+//! * `new` - a raw deflate stream (RFC 1951)
+//! * `new_zlib` - a deflate stream wrapped in a zlib header and an adler32
+//!   checksum (RFC 1950)
+//! * `new_gzip` - a deflate stream wrapped in gzip framing with a crc32
+//!   checksum (RFC 1952)
+//!
+//! The actual compression is performed by the copy of miniz which is bundled
+//! with the runtime.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::io::{MemReader, MemWriter};
+//! use std::io::flate::{DeflateWriter, InflateReader};
+//!
+//! let mut writer = DeflateWriter::new_gzip(MemWriter::new());
+//! writer.write(bytes!("hello, world"));
+//! let compressed = writer.finish().unwrap();
+//!
+//! let mut reader = InflateReader::new_gzip(MemReader::new(compressed));
+//! assert_eq!(reader.read_to_end(), bytes!("hello, world").to_owned());
+//! ```
 
 use prelude::*;
-use super::*;
+
+use cmp;
+use io::{Reader, Writer, IoError, io_error, InvalidInput, OtherIoError,
+         DEFAULT_BUF_SIZE};
+use libc::{c_int, c_ulong, c_void, size_t};
+use libc;
+use ptr;
+use rt::global_heap::malloc_raw;
+use vec;
+
+mod rustrt {
+    use libc::{c_int, c_ulong, c_void, size_t};
+
+    extern {
+        pub fn rust_tdefl_compressor_size() -> size_t;
+        pub fn rust_tinfl_decompressor_size() -> size_t;
+        pub fn rust_tinfl_take_unread(r: *mut c_void, buf: *mut u8) -> size_t;
+
+        pub fn tdefl_init(d: *mut c_void,
+                          put_buf_func: *c_void,
+                          put_buf_user: *c_void,
+                          flags: c_int) -> c_int;
+        pub fn tdefl_compress(d: *mut c_void,
+                              in_buf: *c_void,
+                              in_buf_size: *mut size_t,
+                              out_buf: *mut c_void,
+                              out_buf_size: *mut size_t,
+                              flush: c_int) -> c_int;
+
+        pub fn tinfl_decompress(r: *mut c_void,
+                                in_buf_next: *u8,
+                                in_buf_size: *mut size_t,
+                                out_buf_start: *mut u8,
+                                out_buf_next: *mut u8,
+                                out_buf_size: *mut size_t,
+                                flags: u32) -> c_int;
+
+        pub fn mz_crc32(crc: c_ulong, buf: *u8, len: size_t) -> c_ulong;
+    }
+}
+
+static LZ_NORM: c_int = 0x80; // LZ with 128 probes, "normal"
+static TDEFL_WRITE_ZLIB_HEADER: c_int = 0x01000;
+
+static TDEFL_STATUS_OKAY: c_int = 0;
+static TDEFL_STATUS_DONE: c_int = 1;
+
+static TDEFL_NO_FLUSH: c_int = 0;
+static TDEFL_SYNC_FLUSH: c_int = 2;
+static TDEFL_FINISH: c_int = 4;
+
+static TINFL_FLAG_PARSE_ZLIB_HEADER: u32 = 1;
+static TINFL_FLAG_HAS_MORE_INPUT: u32 = 2;
+
+static TINFL_STATUS_DONE: c_int = 0;
+static TINFL_STATUS_NEEDS_MORE_INPUT: c_int = 1;
+static TINFL_STATUS_HAS_MORE_OUTPUT: c_int = 2;
+
+// The decompressor writes into a circular buffer which must be at least as
+// large as the deflate dictionary (and a power of two).
+static TINFL_LZ_DICT_SIZE: uint = 32768;
+
+// magic, compression method (deflate), no flags, no mtime, no extra flags,
+// unknown OS
+static GZIP_HEADER: [u8, ..10] = [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+
+static GZIP_FHCRC: u8 = 0x02;
+static GZIP_FEXTRA: u8 = 0x04;
+static GZIP_FNAME: u8 = 0x08;
+static GZIP_FCOMMENT: u8 = 0x10;
+
+#[deriving(Eq)]
+enum Format {
+    Raw,
+    Zlib,
+    Gzip,
+}
+
+// Owned, heap-allocated miniz state. These are opaque structures (and the
+// compressor is rather large), so they are allocated based on the sizes
+// reported by the runtime.
+struct Compressor {
+    state: *mut c_void,
+}
+
+impl Compressor {
+    fn new(flags: c_int) -> Compressor {
+        unsafe {
+            let size = rustrt::rust_tdefl_compressor_size();
+            let state = malloc_raw(size as uint);
+            let ret = rustrt::tdefl_init(state, ptr::null(), ptr::null(),
+                                         flags);
+            assert_eq!(ret, TDEFL_STATUS_OKAY);
+            Compressor { state: state }
+        }
+    }
+}
+
+impl Drop for Compressor {
+    fn drop(&mut self) {
+        unsafe { libc::free(self.state as *c_void) }
+    }
+}
+
+struct Decompressor {
+    state: *mut c_void,
+}
+
+impl Decompressor {
+    fn new() -> Decompressor {
+        unsafe {
+            let size = rustrt::rust_tinfl_decompressor_size() as uint;
+            let state = malloc_raw(size);
+            // equivalent to tinfl_init()
+            ptr::set_memory(state as *mut u8, 0, size);
+            Decompressor { state: state }
+        }
+    }
+}
+
+impl Drop for Decompressor {
+    fn drop(&mut self) {
+        unsafe { libc::free(self.state as *c_void) }
+    }
+}
 
 /// A Writer decorator that compresses using the 'deflate' scheme
+///
+/// Compressed data is written to the underlying writer as it becomes
+/// available. The stream is only complete once `finish` has been called;
+/// dropping a `DeflateWriter` without finishing it leaves a truncated stream
+/// in the underlying writer.
 pub struct DeflateWriter<W> {
-    priv inner_writer: W
+    priv inner: W,
+    priv compressor: Compressor,
+    priv buf: ~[u8],
+    priv gzip: bool,
+    priv crc: c_ulong,
+    priv size: u32,
 }
 
 impl<W: Writer> DeflateWriter<W> {
-    pub fn new(inner_writer: W) -> DeflateWriter<W> {
+    /// Creates a new `DeflateWriter` which writes a raw deflate stream
+    pub fn new(inner: W) -> DeflateWriter<W> {
+        DeflateWriter::create(inner, Raw)
+    }
+
+    /// Creates a new `DeflateWriter` which writes a zlib stream
+    pub fn new_zlib(inner: W) -> DeflateWriter<W> {
+        DeflateWriter::create(inner, Zlib)
+    }
+
+    /// Creates a new `DeflateWriter` which writes a gzip stream
+    ///
+    /// The gzip header is written to the underlying writer immediately.
+    pub fn new_gzip(inner: W) -> DeflateWriter<W> {
+        DeflateWriter::create(inner, Gzip)
+    }
+
+    fn create(mut inner: W, format: Format) -> DeflateWriter<W> {
+        let flags = match format {
+            Zlib => LZ_NORM | TDEFL_WRITE_ZLIB_HEADER,
+            Raw | Gzip => LZ_NORM,
+        };
+        if format == Gzip {
+            inner.write(GZIP_HEADER);
+        }
         DeflateWriter {
-            inner_writer: inner_writer
+            inner: inner,
+            compressor: Compressor::new(flags),
+            buf: vec::from_elem(DEFAULT_BUF_SIZE, 0u8),
+            gzip: format == Gzip,
+            crc: 0,
+            size: 0,
+        }
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref<'a>(&'a self) -> &'a W { &self.inner }
+
+    /// Completes the compressed stream and returns the underlying writer.
+    ///
+    /// All pending compressed data is written out, followed by the trailer
+    /// (if any) of the stream's format.
+    pub fn finish(mut self) -> W {
+        self.compress([], TDEFL_FINISH);
+        if self.gzip {
+            let crc = self.crc as u32;
+            let size = self.size;
+            self.inner.write_le_u32(crc);
+            self.inner.write_le_u32(size);
+        }
+        self.inner
+    }
+
+    // Feeds all of `input` to the compressor, writing out whatever output it
+    // produces along the way.
+    fn compress(&mut self, mut input: &[u8], flush: c_int) {
+        loop {
+            let mut in_size = input.len() as size_t;
+            let mut out_size = self.buf.len() as size_t;
+            let status = unsafe {
+                rustrt::tdefl_compress(self.compressor.state,
+                                       input.as_ptr() as *c_void,
+                                       &mut in_size,
+                                       self.buf.as_mut_ptr() as *mut c_void,
+                                       &mut out_size,
+                                       flush)
+            };
+            input = input.slice_from(in_size as uint);
+            if out_size > 0 {
+                self.inner.write(self.buf.slice_to(out_size as uint));
+            }
+
+            match status {
+                TDEFL_STATUS_DONE => break,
+                // If the output buffer wasn't filled, then the compressor has
+                // nothing left to hand back until it's given more input.
+                TDEFL_STATUS_OKAY if input.len() == 0 &&
+                                     (out_size as uint) < self.buf.len() &&
+                                     flush != TDEFL_FINISH => break,
+                TDEFL_STATUS_OKAY => {}
+                _ => {
+                    io_error::cond.raise(IoError {
+                        kind: OtherIoError,
+                        desc: "failed to compress data",
+                        detail: Some(format!("status {}", status)),
+                    });
+                    break
+                }
+            }
         }
     }
 }
 
 impl<W: Writer> Writer for DeflateWriter<W> {
-    fn write(&mut self, _buf: &[u8]) { fail!() }
+    fn write(&mut self, buf: &[u8]) {
+        if self.gzip {
+            self.crc = unsafe {
+                rustrt::mz_crc32(self.crc, buf.as_ptr(), buf.len() as size_t)
+            };
+            // gzip records the size modulo 2^32
+            self.size += buf.len() as u32;
+        }
+        self.compress(buf, TDEFL_NO_FLUSH);
+    }
 
-    fn flush(&mut self) { fail!() }
+    /// Flushes all data written so far through to the underlying writer.
+    ///
+    /// This performs a "sync flush", so the output so far can be fully
+    /// decompressed by the other end. Flushing often hurts compression.
+    fn flush(&mut self) {
+        self.compress([], TDEFL_SYNC_FLUSH);
+        self.inner.flush();
+    }
 }
 
 /// A Reader decorator that decompresses using the 'deflate' scheme
+///
+/// The compressed stream is read from the underlying reader on demand. Errors
+/// in the compressed data (including checksum mismatches) are raised on the
+/// `io_error` condition with the `InvalidInput` kind, after which the reader
+/// reports EOF.
 pub struct InflateReader<R> {
-    priv inner_reader: R
+    priv inner: R,
+    priv decompressor: Decompressor,
+    priv format: Format,
+
+    // compressed data read from `inner` which hasn't been consumed yet
+    priv input: ~[u8],
+    priv in_pos: uint,
+    priv in_cap: uint,
+    priv eof: bool,
+
+    // decompressed data, out_pos..out_cap is yet to be returned from `read`
+    priv dict: ~[u8],
+    priv dict_pos: uint,
+    priv out_pos: uint,
+    priv out_cap: uint,
+
+    priv started: bool,
+    priv done: bool,
+    priv crc: c_ulong,
+    priv size: u32,
 }
 
 impl<R: Reader> InflateReader<R> {
-    pub fn new(inner_reader: R) -> InflateReader<R> {
+    /// Creates a new `InflateReader` which reads a raw deflate stream
+    pub fn new(inner: R) -> InflateReader<R> {
+        InflateReader::create(inner, Raw)
+    }
+
+    /// Creates a new `InflateReader` which reads a zlib stream
+    pub fn new_zlib(inner: R) -> InflateReader<R> {
+        InflateReader::create(inner, Zlib)
+    }
+
+    /// Creates a new `InflateReader` which reads a gzip stream
+    ///
+    /// Only the first member of a multi-member gzip file is read.
+    pub fn new_gzip(inner: R) -> InflateReader<R> {
+        InflateReader::create(inner, Gzip)
+    }
+
+    fn create(inner: R, format: Format) -> InflateReader<R> {
         InflateReader {
-            inner_reader: inner_reader
+            inner: inner,
+            decompressor: Decompressor::new(),
+            format: format,
+            input: vec::from_elem(DEFAULT_BUF_SIZE, 0u8),
+            in_pos: 0,
+            in_cap: 0,
+            eof: false,
+            dict: vec::from_elem(TINFL_LZ_DICT_SIZE, 0u8),
+            dict_pos: 0,
+            out_pos: 0,
+            out_cap: 0,
+            started: false,
+            done: false,
+            crc: 0,
+            size: 0,
+        }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref<'a>(&'a self) -> &'a R { &self.inner }
+
+    /// Unwraps this reader, returning the underlying reader.
+    ///
+    /// Note that any compressed data which has been read from the underlying
+    /// reader but not yet decompressed is lost.
+    pub fn unwrap(self) -> R { self.inner }
+
+    fn fill(&mut self) {
+        self.in_pos = 0;
+        match self.inner.read(self.input) {
+            Some(n) => { self.in_cap = n; }
+            None => { self.in_cap = 0; self.eof = true; }
+        }
+    }
+
+    fn corrupt(&mut self, desc: &'static str) -> bool {
+        self.done = true;
+        io_error::cond.raise(IoError {
+            kind: InvalidInput,
+            desc: desc,
+            detail: None,
+        });
+        false
+    }
+
+    // Runs the decompressor once, returning whether any progress can
+    // continue to be made.
+    fn decompress(&mut self) -> bool {
+        if self.in_pos == self.in_cap && !self.eof {
+            self.fill();
+        }
+
+        let mut flags = 0;
+        if self.format == Zlib { flags |= TINFL_FLAG_PARSE_ZLIB_HEADER; }
+        if !self.eof { flags |= TINFL_FLAG_HAS_MORE_INPUT; }
+
+        let mut in_size = (self.in_cap - self.in_pos) as size_t;
+        let mut out_size = (self.dict.len() - self.dict_pos) as size_t;
+        let status = unsafe {
+            let dict = self.dict.as_mut_ptr();
+            rustrt::tinfl_decompress(self.decompressor.state,
+                                     self.input.as_ptr().offset(self.in_pos as int),
+                                     &mut in_size,
+                                     dict,
+                                     dict.offset(self.dict_pos as int),
+                                     &mut out_size,
+                                     flags)
+        };
+        self.in_pos += in_size as uint;
+        self.out_pos = self.dict_pos;
+        self.out_cap = self.dict_pos + out_size as uint;
+        self.dict_pos = self.out_cap & (self.dict.len() - 1);
+
+        if self.format == Gzip {
+            let out = self.dict.slice(self.out_pos, self.out_cap);
+            self.crc = unsafe {
+                rustrt::mz_crc32(self.crc, out.as_ptr(), out.len() as size_t)
+            };
+            self.size += out.len() as u32;
+        }
+
+        match status {
+            TINFL_STATUS_DONE => {
+                self.done = true;
+                if self.format != Zlib {
+                    // Any input which the decompressor read beyond the end
+                    // of the deflate stream (such as the gzip trailer) is
+                    // still sitting in its bit buffer.
+                    let mut buf = [0u8, ..8];
+                    let n = unsafe {
+                        rustrt::rust_tinfl_take_unread(self.decompressor.state,
+                                                       buf.as_mut_ptr())
+                    };
+                    self.unread(buf.slice_to(n as uint));
+                }
+                if self.format == Gzip {
+                    self.read_gzip_trailer()
+                } else {
+                    true
+                }
+            }
+            TINFL_STATUS_NEEDS_MORE_INPUT |
+            TINFL_STATUS_HAS_MORE_OUTPUT => true,
+            _ => self.corrupt("corrupt deflate stream"),
+        }
+    }
+
+    // Puts bytes back at the front of the unconsumed input
+    fn unread(&mut self, bytes: &[u8]) {
+        let n = bytes.len();
+        if self.in_pos >= n {
+            self.in_pos -= n;
+            vec::bytes::copy_memory(self.input.mut_slice(self.in_pos,
+                                                         self.in_pos + n),
+                                    bytes);
+        } else {
+            let mut input = bytes.to_owned();
+            input.push_all(self.input.slice(self.in_pos, self.in_cap));
+            self.in_pos = 0;
+            self.in_cap = input.len();
+            let len = input.len();
+            if len < DEFAULT_BUF_SIZE {
+                input.grow(DEFAULT_BUF_SIZE - len, &0u8);
+            }
+            self.input = input;
+        }
+    }
+
+    fn next_byte(&mut self) -> Option<u8> {
+        while self.in_pos == self.in_cap {
+            if self.eof { return None }
+            self.fill();
+        }
+        let b = self.input[self.in_pos];
+        self.in_pos += 1;
+        Some(b)
+    }
+
+    fn next_le_u32(&mut self) -> Option<u32> {
+        let mut ret = 0u32;
+        for i in range(0u, 4) {
+            match self.next_byte() {
+                Some(b) => { ret |= (b as u32) << (8 * i); }
+                None => return None,
+            }
+        }
+        Some(ret)
+    }
+
+    fn read_gzip_header(&mut self) -> bool {
+        let mut header = [0u8, ..10];
+        for b in header.mut_iter() {
+            match self.next_byte() {
+                Some(byte) => { *b = byte; }
+                None => return self.corrupt("truncated gzip header"),
+            }
+        }
+        if header[0] != GZIP_HEADER[0] || header[1] != GZIP_HEADER[1] ||
+           header[2] != GZIP_HEADER[2] {
+            return self.corrupt("invalid gzip header");
+        }
+        let flags = header[3];
+
+        let mut skip = 0u;
+        if flags & GZIP_FEXTRA != 0 {
+            match (self.next_byte(), self.next_byte()) {
+                (Some(lo), Some(hi)) => { skip += (lo as uint) | (hi as uint << 8); }
+                _ => return self.corrupt("truncated gzip header"),
+            }
+        }
+        if flags & GZIP_FHCRC != 0 {
+            skip += 2;
+        }
+        for _ in range(0, skip) {
+            if self.next_byte().is_none() {
+                return self.corrupt("truncated gzip header");
+            }
+        }
+
+        // the file name and comment are both zero-terminated
+        let strings = [flags & GZIP_FNAME != 0, flags & GZIP_FCOMMENT != 0];
+        for &present in strings.iter() {
+            if !present { continue }
+            loop {
+                match self.next_byte() {
+                    Some(0) => break,
+                    Some(..) => {}
+                    None => return self.corrupt("truncated gzip header"),
+                }
+            }
+        }
+        true
+    }
+
+    fn read_gzip_trailer(&mut self) -> bool {
+        let crc = self.next_le_u32();
+        let size = self.next_le_u32();
+        match (crc, size) {
+            (Some(crc), Some(size)) => {
+                if crc != self.crc as u32 || size != self.size {
+                    self.corrupt("gzip checksum mismatch")
+                } else {
+                    true
+                }
+            }
+            _ => self.corrupt("truncated gzip trailer"),
         }
     }
 }
 
 impl<R: Reader> Reader for InflateReader<R> {
-    fn read(&mut self, _buf: &mut [u8]) -> Option<uint> { fail!() }
+    fn read(&mut self, buf: &mut [u8]) -> Option<uint> {
+        if !self.started {
+            self.started = true;
+            if self.format == Gzip && !self.read_gzip_header() {
+                return None;
+            }
+        }
+
+        loop {
+            if self.out_pos < self.out_cap {
+                let n = cmp::min(buf.len(), self.out_cap - self.out_pos);
+                vec::bytes::copy_memory(buf.mut_slice_to(n),
+                                        self.dict.slice(self.out_pos,
+                                                        self.out_pos + n));
+                self.out_pos += n;
+                return Some(n);
+            }
+            if self.done || !self.decompress() {
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use prelude::*;
+    use super::*;
+    use cmp;
+    use io::*;
+    use rand;
+    use rand::Rng;
+
+    // "hello world\n" as compressed by other implementations
+    static ZLIB_HELLO: &'static [u8] = &[
+        120, 156, 203, 72, 205, 201, 201, 87, 40, 207, 47, 202, 73, 225, 2, 0,
+        30, 114, 4, 103];
+    static GZIP_HELLO: &'static [u8] = &[
+        31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 203, 72, 205, 201, 201, 87, 40, 207,
+        47, 202, 73, 225, 2, 0, 45, 59, 8, 175, 12, 0, 0, 0];
+    // same, but with a file name in the header
+    static GZIP_NAMED_HELLO: &'static [u8] = &[
+        31, 139, 8, 8, 0, 0, 0, 0, 2, 255, 97, 46, 116, 120, 116, 0, 203, 72,
+        205, 201, 201, 87, 40, 207, 47, 202, 73, 225, 2, 0, 45, 59, 8, 175, 12,
+        0, 0, 0];
+
+    fn random_words() -> ~[u8] {
+        let mut r = rand::rng();
+        let mut words = ~[];
+        20.times(|| {
+            let range = r.gen_range(1u, 10);
+            words.push(r.gen_vec::<u8>(range));
+        });
+        let mut input = ~[];
+        20000.times(|| {
+            input.push_all(r.choose(words));
+        });
+        input
+    }
+
+    fn round_trip(input: &[u8],
+                  deflate: |MemWriter| -> DeflateWriter<MemWriter>,
+                  inflate: |MemReader| -> InflateReader<MemReader>) {
+        let mut writer = deflate(MemWriter::new());
+        // feed the data through in uneven pieces
+        for chunk in input.chunks(1000) {
+            writer.write(chunk);
+        }
+        let compressed = writer.finish().unwrap();
+
+        let mut reader = inflate(MemReader::new(compressed));
+        assert_eq!(reader.read_to_end().as_slice(), input);
+    }
+
+    #[test]
+    fn raw_round_trip() {
+        round_trip(random_words(), DeflateWriter::new, InflateReader::new);
+        round_trip([], DeflateWriter::new, InflateReader::new);
+    }
+
+    #[test]
+    fn zlib_round_trip() {
+        round_trip(random_words(), DeflateWriter::new_zlib,
+                   InflateReader::new_zlib);
+        round_trip([], DeflateWriter::new_zlib, InflateReader::new_zlib);
+    }
+
+    #[test]
+    fn gzip_round_trip() {
+        round_trip(random_words(), DeflateWriter::new_gzip,
+                   InflateReader::new_gzip);
+        round_trip([], DeflateWriter::new_gzip, InflateReader::new_gzip);
+    }
+
+    // Hands out the wrapped data a byte at a time, so that the end of a
+    // compressed stream never arrives in the same read as its trailer
+    struct ByteReader {
+        inner: MemReader,
+    }
+
+    impl Reader for ByteReader {
+        fn read(&mut self, buf: &mut [u8]) -> Option<uint> {
+            self.inner.read(buf.mut_slice_to(cmp::min(buf.len(), 1)))
+        }
+    }
+
+    #[test]
+    fn gzip_trailer_after_lookahead() {
+        let mut writer = DeflateWriter::new_gzip(MemWriter::new());
+        writer.write(bytes!("hello, world"));
+        let compressed = writer.finish().unwrap();
+
+        let mut reader = InflateReader::new_gzip(MemReader::new(compressed.clone()));
+        assert_eq!(reader.read_to_end(), bytes!("hello, world").to_owned());
+
+        let inner = ByteReader { inner: MemReader::new(compressed) };
+        let mut reader = InflateReader::new_gzip(inner);
+        assert_eq!(reader.read_to_end(), bytes!("hello, world").to_owned());
+    }
+
+    #[test]
+    fn data_after_raw_stream() {
+        let input = random_words();
+        let mut writer = DeflateWriter::new(MemWriter::new());
+        writer.write(input);
+        let mut compressed = writer.finish().unwrap();
+        compressed.push_all(bytes!("trailing data"));
+
+        let mut reader = InflateReader::new(MemReader::new(compressed));
+        assert_eq!(reader.read_to_end(), input);
+        // the decompressor stops at the end of the stream, leaving whatever
+        // follows it unconsumed
+        assert_eq!(reader.input.slice(reader.in_pos, reader.in_cap),
+                   bytes!("trailing data"));
+    }
+
+    #[test]
+    fn reads_other_implementations() {
+        let expected = bytes!("hello world\n").to_owned();
+        let mut zlib = InflateReader::new_zlib(BufReader::new(ZLIB_HELLO));
+        assert_eq!(zlib.read_to_end(), expected.clone());
+        let mut gzip = InflateReader::new_gzip(BufReader::new(GZIP_HELLO));
+        assert_eq!(gzip.read_to_end(), expected.clone());
+        let mut named = InflateReader::new_gzip(BufReader::new(GZIP_NAMED_HELLO));
+        assert_eq!(named.read_to_end(), expected);
+    }
+
+    #[test]
+    fn flush_emits_complete_data() {
+        let mut writer = DeflateWriter::new(MemWriter::new());
+        writer.write(bytes!("hello"));
+        writer.flush();
+
+        let compressed = writer.get_ref().get_ref().to_owned();
+        let mut reader = InflateReader::new(MemReader::new(compressed));
+        let mut buf = [0u8, ..5];
+        // the stream isn't finished yet, but all the data is there
+        let mut called = false;
+        io_error::cond.trap(|_| called = true).inside(|| {
+            assert_eq!(reader.read(buf), Some(5));
+        });
+        assert!(!called);
+        assert_eq!(buf.as_slice(), bytes!("hello"));
+    }
+
+    #[test]
+    fn corrupt_input() {
+        let mut called = false;
+        io_error::cond.trap(|e| {
+            assert_eq!(e.kind, InvalidInput);
+            called = true;
+        }).inside(|| {
+            let mut reader = InflateReader::new_zlib(MemReader::new(~[1, 2, 3]));
+            assert_eq!(reader.read_to_end(), ~[]);
+        });
+        assert!(called);
+    }
+
+    #[test]
+    fn gzip_checksum_mismatch() {
+        let mut data = GZIP_HELLO.to_owned();
+        let len = data.len();
+        data[len - 8] ^= 1;
+
+        let mut called = false;
+        io_error::cond.trap(|e| {
+            assert_eq!(e.kind, InvalidInput);
+            called = true;
+        }).inside(|| {
+            let mut reader = InflateReader::new_gzip(MemReader::new(data));
+            reader.read_to_end();
+        });
+        assert!(called);
+    }
 }
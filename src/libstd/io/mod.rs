@@ -368,6 +368,9 @@ pub mod util;
 /// Adapatation of Chan/Port types to a Writer/Reader type.
 mod comm_adapters;
 
+/// Streaming compression and decompression
+pub mod flate;
+
 /// The default buffer size for various I/O operations
 // libuv recommends 64k buffers to maximize throughput
 // https://groups.google.com/forum/#!topic/libuv/oQO1HJAIDdA
@@ -418,6 +421,7 @@ pub enum IoErrorKind {
     ResourceUnavailable,
     IoUnavailable,
     InvalidInput,
+    TimedOut,
 }
 
 // FIXME: #8242 implementing manually because deriving doesn't work for some reason
@@ -442,6 +446,7 @@ impl ToStr for IoErrorKind {
             ResourceUnavailable => ~"ResourceUnavailable",
             ConnectionAborted => ~"ConnectionAborted",
             InvalidInput => ~"InvalidInput",
+            TimedOut => ~"TimedOut",
         }
     }
 }
@@ -1072,6 +1077,38 @@ impl<'r, T: Buffer> Iterator<~str> for Lines<'r, T> {
     }
 }
 
+/// An iterator that reads a utf8-encoded character on each iteration,
+/// until `.read_char()` returns `None`.
+///
+/// # Notes about the Iteration Protocol
+///
+/// The `Chars` may yield `None` and thus terminate
+/// an iteration, but continue to yield elements if iteration
+/// is attempted again.
+///
+/// # Failure
+///
+/// Raises the same conditions as the `read` method except for `EndOfFile`
+/// which is swallowed.
+/// Iteration yields `None` if the condition is handled.
+pub struct Chars<'r, T> {
+    priv buffer: &'r mut T,
+}
+
+impl<'r, T: Buffer> Iterator<char> for Chars<'r, T> {
+    fn next(&mut self) -> Option<char> {
+        let mut ret = None;
+        io_error::cond.trap(|e| {
+            if e.kind != EndOfFile {
+                io_error::cond.raise(e);
+            }
+        }).inside(|| {
+            ret = self.buffer.read_char();
+        });
+        ret
+    }
+}
+
 /// A Buffer is a type of reader which has some form of internal buffering to
 /// allow certain kinds of reading operations to be more optimized than others.
 /// This type extends the `Reader` trait with a few methods that are not
@@ -1132,6 +1169,19 @@ pub trait Buffer: Reader {
         }
     }
 
+    /// Create an iterator that reads a utf8-encoded character on each
+    /// iteration until EOF.
+    ///
+    /// # Failure
+    ///
+    /// Iterator raises the same conditions as the `read` method
+    /// except for `EndOfFile`.
+    fn chars<'r>(&'r mut self) -> Chars<'r, Self> {
+        Chars {
+            buffer: self,
+        }
+    }
+
     /// Reads a sequence of bytes leading up to a specified delimiter. Once the
     /// specified byte is encountered, reading ceases and the bytes up to and
     /// including the delimiter are returned.
@@ -1284,6 +1334,7 @@ pub fn standard_error(kind: IoErrorKind) -> IoError {
         EndOfFile => "end of file",
         IoUnavailable => "I/O is unavailable",
         InvalidInput => "invalid input",
+        TimedOut => "operation timed out",
         _ => fail!()
     };
     IoError {
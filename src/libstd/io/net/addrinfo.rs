@@ -28,7 +28,8 @@ pub enum SocketType {
 }
 
 /// Flags which can be or'd into the `flags` field of a `Hint`. These are used
-/// to manipulate how a query is performed.
+/// to manipulate how a query is performed. Each flag `f` occupies the bit
+/// `1 << (f as uint)` of the `flags` field.
 ///
 /// The meaning of each of these flags can be found with `man -s 3 getaddrinfo`
 pub enum Flag {
@@ -100,13 +101,7 @@ fn lookup(hostname: Option<&str>, servname: Option<&str>, hint: Option<Hint>)
 
 #[cfg(test)]
 mod test {
-    use io::net::ip::Ipv4Addr;
-    use prelude::*;
-    use super::*;
-
-    #[test]
-    #[ignore(cfg(target_os="android"))] // cannot give tcp/ip permission without help of apk
-    fn dns_smoke_test() {
+    iotest!(fn dns_smoke_test() {
         let ipaddrs = get_host_addresses("localhost").unwrap();
         let mut found_local = false;
         let local_addr = &Ipv4Addr(127, 0, 0, 1);
@@ -114,5 +109,10 @@ mod test {
             found_local = found_local || addr == local_addr;
         }
         assert!(found_local);
-    }
+    } #[ignore(cfg(target_os="android"))]) // cannot give tcp/ip permission without help of apk
+
+    iotest!(fn dns_numeric_host() {
+        let ipaddrs = get_host_addresses("127.0.0.1").unwrap();
+        assert!(ipaddrs.iter().any(|a| *a == Ipv4Addr(127, 0, 0, 1)));
+    } #[ignore(cfg(target_os="android"))])
 }
@@ -52,6 +52,62 @@ impl TcpStream {
             }
         }
     }
+
+    /// Sets the timeout, in milliseconds, for each read and write performed
+    /// on this stream. This is equivalent to calling both `set_read_timeout`
+    /// and `set_write_timeout`.
+    pub fn set_timeout(&mut self, timeout_ms: Option<u64>) {
+        self.set_read_timeout(timeout_ms);
+        self.set_write_timeout(timeout_ms);
+    }
+
+    /// Sets the timeout, in milliseconds, for each read performed on this
+    /// stream. A value of `None` means that reads will block indefinitely.
+    ///
+    /// A read which doesn't complete within the timeout will raise an
+    /// `io_error` of kind `TimedOut` and return `None`. The stream is still
+    /// usable afterwards, and no data is lost.
+    pub fn set_read_timeout(&mut self, timeout_ms: Option<u64>) {
+        match self.obj.set_read_timeout(timeout_ms) {
+            Ok(()) => {}
+            Err(ioerr) => io_error::cond.raise(ioerr),
+        }
+    }
+
+    /// Sets the timeout, in milliseconds, for each write performed on this
+    /// stream. A value of `None` means that writes will block indefinitely.
+    ///
+    /// A write which doesn't complete within the timeout will raise an
+    /// `io_error` of kind `TimedOut`. Note that some (or all) of the data may
+    /// still be written to the stream after this happens.
+    pub fn set_write_timeout(&mut self, timeout_ms: Option<u64>) {
+        match self.obj.set_write_timeout(timeout_ms) {
+            Ok(()) => {}
+            Err(ioerr) => io_error::cond.raise(ioerr),
+        }
+    }
+
+    /// Closes the write half of this stream, signaling to the other end that
+    /// no more data will be sent (it will see EOF once it has read all of the
+    /// data written up to this point).
+    ///
+    /// Any writes performed after this call will fail. Reading from the
+    /// stream is unaffected.
+    pub fn close_write(&mut self) {
+        match self.obj.close_write() {
+            Ok(()) => {}
+            Err(ioerr) => io_error::cond.raise(ioerr),
+        }
+    }
+
+    /// Closes the read half of this stream. All reads performed after this
+    /// call will return EOF. Writing to the stream is unaffected.
+    pub fn close_read(&mut self) {
+        match self.obj.close_read() {
+            Ok(()) => {}
+            Err(ioerr) => io_error::cond.raise(ioerr),
+        }
+    }
 }
 
 impl Reader for TcpStream {
@@ -117,6 +173,22 @@ pub struct TcpAcceptor {
     priv obj: ~RtioTcpAcceptor
 }
 
+impl TcpAcceptor {
+    /// Sets the timeout, in milliseconds, for each call to `accept` on this
+    /// acceptor. A value of `None` means that `accept` will block
+    /// indefinitely.
+    ///
+    /// If no connection arrives within the timeout, `accept` will raise an
+    /// `io_error` of kind `TimedOut` and return `None`. A connection which
+    /// arrives later will be returned by the next call to `accept`.
+    pub fn set_timeout(&mut self, timeout_ms: Option<u64>) {
+        match self.obj.set_timeout(timeout_ms) {
+            Ok(()) => {}
+            Err(ioerr) => io_error::cond.raise(ioerr),
+        }
+    }
+}
+
 impl Acceptor<TcpStream> for TcpAcceptor {
     fn accept(&mut self) -> Option<TcpStream> {
         match self.obj.accept() {
@@ -609,4 +681,127 @@ mod test {
         c.write([1]);
         p.recv();
     })
+
+    iotest!(fn accept_timeout() {
+        let addr = next_test_ip4();
+        let mut acceptor = TcpListener::bind(addr).listen().unwrap();
+        acceptor.set_timeout(Some(10));
+
+        match io::result(|| acceptor.accept()) {
+            Err(e) => assert_eq!(e.kind, TimedOut),
+            Ok(..) => fail!("accept should have timed out"),
+        }
+
+        // a connection which arrives after a timeout is accepted normally
+        acceptor.set_timeout(None);
+        let (port, chan) = Chan::new();
+        do spawn {
+            let _stream = TcpStream::connect(addr).unwrap();
+            port.recv();
+        }
+        assert!(acceptor.accept().is_some());
+        chan.send(());
+    })
+
+    iotest!(fn read_timeout() {
+        let addr = next_test_ip4();
+        let (port, chan) = Chan::new();
+        let (port2, chan2) = Chan::new();
+
+        do spawn {
+            let mut acceptor = TcpListener::bind(addr).listen();
+            chan.send(());
+            let mut stream = acceptor.accept().unwrap();
+            port2.recv();
+            stream.write([99]);
+        }
+
+        port.recv();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.set_read_timeout(Some(20));
+        let mut buf = [0];
+        match io::result(|| stream.read(buf)) {
+            Err(e) => assert_eq!(e.kind, TimedOut),
+            Ok(..) => fail!("read should have timed out"),
+        }
+
+        // the stream is still usable after a read times out
+        stream.set_read_timeout(None);
+        chan2.send(());
+        assert_eq!(stream.read(buf), Some(1));
+        assert_eq!(buf[0], 99);
+    })
+
+    iotest!(fn write_timeout() {
+        let addr = next_test_ip4();
+        let (port, chan) = Chan::new();
+        let (port2, chan2) = Chan::new();
+
+        do spawn {
+            let mut acceptor = TcpListener::bind(addr).listen();
+            chan.send(());
+            // keep the connection open, but never read from it
+            let _stream = acceptor.accept().unwrap();
+            port2.recv();
+        }
+
+        port.recv();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.set_write_timeout(Some(20));
+        let buf = [0u8, ..128 * 1024];
+        loop {
+            match io::result(|| stream.write(buf)) {
+                Ok(()) => {}
+                Err(e) => { assert_eq!(e.kind, TimedOut); break }
+            }
+        }
+        chan2.send(());
+    })
+
+    iotest!(fn close_write_signals_eof() {
+        let addr = next_test_ip4();
+        let (port, chan) = Chan::new();
+
+        do spawn {
+            let mut acceptor = TcpListener::bind(addr).listen();
+            chan.send(());
+            let mut stream = acceptor.accept().unwrap();
+            let mut buf = [0];
+            assert_eq!(stream.read(buf), Some(1));
+            assert_eq!(buf[0], 99);
+            // the client has closed its write half, so this is EOF
+            assert!(stream.read(buf).is_none());
+            stream.write([100]);
+        }
+
+        port.recv();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write([99]);
+        stream.close_write();
+        let mut buf = [0];
+        assert_eq!(stream.read(buf), Some(1));
+        assert_eq!(buf[0], 100);
+    })
+
+    iotest!(fn close_read_returns_eof() {
+        let addr = next_test_ip4();
+        let (port, chan) = Chan::new();
+
+        do spawn {
+            port.recv();
+            let mut stream = TcpStream::connect(addr).unwrap();
+            let mut buf = [0];
+            assert_eq!(stream.read(buf), Some(1));
+            assert_eq!(buf[0], 100);
+        }
+
+        let mut acceptor = TcpListener::bind(addr).listen();
+        chan.send(());
+        let mut stream = acceptor.accept().unwrap();
+        stream.close_read();
+        let mut buf = [0];
+        assert!(stream.read(buf).is_none());
+        // writing is unaffected by closing the read half
+        stream.write([100]);
+    })
 }
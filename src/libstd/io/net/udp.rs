@@ -60,6 +60,39 @@ impl UdpSocket {
             }
         }
     }
+
+    /// Sets the timeout, in milliseconds, for each `recvfrom` and `sendto`
+    /// performed on this socket. This is equivalent to calling both
+    /// `set_read_timeout` and `set_write_timeout`.
+    pub fn set_timeout(&mut self, timeout_ms: Option<u64>) {
+        self.set_read_timeout(timeout_ms);
+        self.set_write_timeout(timeout_ms);
+    }
+
+    /// Sets the timeout, in milliseconds, for each `recvfrom` performed on
+    /// this socket. A value of `None` means that receiving will block
+    /// indefinitely.
+    ///
+    /// If no datagram arrives within the timeout, `recvfrom` will raise an
+    /// `io_error` of kind `TimedOut` and return `None`.
+    pub fn set_read_timeout(&mut self, timeout_ms: Option<u64>) {
+        match self.obj.set_read_timeout(timeout_ms) {
+            Ok(()) => {}
+            Err(ioerr) => io_error::cond.raise(ioerr),
+        }
+    }
+
+    /// Sets the timeout, in milliseconds, for each `sendto` performed on this
+    /// socket. A value of `None` means that sending will block indefinitely.
+    ///
+    /// A send which doesn't complete within the timeout will raise an
+    /// `io_error` of kind `TimedOut`, although the datagram may still be sent.
+    pub fn set_write_timeout(&mut self, timeout_ms: Option<u64>) {
+        match self.obj.set_write_timeout(timeout_ms) {
+            Ok(()) => {}
+            Err(ioerr) => io_error::cond.raise(ioerr),
+        }
+    }
 }
 
 pub struct UdpStream {
@@ -276,4 +309,25 @@ mod test {
     iotest!(fn socket_name_ip6() {
         socket_name(next_test_ip6());
     })
+
+    iotest!(fn recvfrom_timeout() {
+        let addr = next_test_ip4();
+        let mut socket = UdpSocket::bind(addr).unwrap();
+        socket.set_read_timeout(Some(20));
+
+        let mut buf = [0];
+        match io::result(|| socket.recvfrom(buf)) {
+            Err(e) => assert_eq!(e.kind, TimedOut),
+            Ok(..) => fail!("recvfrom should have timed out"),
+        }
+
+        // the socket is still usable after a timeout
+        socket.set_read_timeout(None);
+        socket.sendto([99], addr);
+        match socket.recvfrom(buf) {
+            Some((1, src)) => assert_eq!(src, addr),
+            _ => fail!(),
+        }
+        assert_eq!(buf[0], 99);
+    })
 }
@@ -63,6 +63,18 @@ impl UnixStream {
             io.unix_connect(&path.to_c_str()).map(UnixStream::new)
         })
     }
+
+    /// Closes the write half of this stream, signaling to the other end that
+    /// no more data will be sent (it will see EOF once it has read all of the
+    /// data written up to this point).
+    ///
+    /// Any writes performed after this call will fail. Reading from the
+    /// stream is unaffected.
+    pub fn close_write(&mut self) { self.obj.close_write() }
+
+    /// Closes the read half of this stream. All reads performed after this
+    /// call will return EOF. Writing to the stream is unaffected.
+    pub fn close_read(&mut self) { self.obj.close_read() }
 }
 
 impl Reader for UnixStream {
@@ -142,7 +154,7 @@ mod tests {
     use io::*;
     use io::test::*;
 
-    fn smalltest(server: proc(UnixStream), client: proc(UnixStream)) {
+    pub fn smalltest(server: proc(UnixStream), client: proc(UnixStream)) {
         let path1 = next_test_unix();
         let path2 = path1.clone();
         let (port, chan) = Chan::new();
@@ -157,8 +169,7 @@ mod tests {
         server(acceptor.accept().unwrap());
     }
 
-    #[test]
-    fn bind_error() {
+    iotest!(fn bind_error() {
         let mut called = false;
         io_error::cond.trap(|e| {
             assert!(e.kind == PermissionDenied);
@@ -168,10 +179,9 @@ mod tests {
             assert!(listener.is_none());
         });
         assert!(called);
-    }
+    })
 
-    #[test]
-    fn connect_error() {
+    iotest!(fn connect_error() {
         let mut called = false;
         io_error::cond.trap(|e| {
             assert_eq!(e.kind,
@@ -182,10 +192,9 @@ mod tests {
             assert!(stream.is_none());
         });
         assert!(called);
-    }
+    })
 
-    #[test]
-    fn smoke() {
+    iotest!(fn smoke() {
         smalltest(proc(mut server) {
             let mut buf = [0];
             server.read(buf);
@@ -193,10 +202,9 @@ mod tests {
         }, proc(mut client) {
             client.write([99]);
         })
-    }
+    })
 
-    #[test]
-    fn read_eof() {
+    iotest!(fn read_eof() {
         smalltest(proc(mut server) {
             let mut buf = [0];
             assert!(server.read(buf).is_none());
@@ -204,10 +212,9 @@ mod tests {
         }, proc(_client) {
             // drop the client
         })
-    }
+    })
 
-    #[test]
-    fn write_begone() {
+    iotest!(fn write_begone() {
         smalltest(proc(mut server) {
             let buf = [0];
             let mut stop = false;
@@ -223,10 +230,9 @@ mod tests {
         }, proc(_client) {
             // drop the client
         })
-    }
+    })
 
-    #[test]
-    fn accept_lots() {
+    iotest!(fn accept_lots() {
         let times = 10;
         let path1 = next_test_unix();
         let path2 = path1.clone();
@@ -248,12 +254,41 @@ mod tests {
             client.read(buf);
             assert_eq!(buf[0], 100);
         })
-    }
+    })
 
-    #[test]
-    fn path_exists() {
+    iotest!(fn path_exists() {
         let path = next_test_unix();
         let _acceptor = UnixListener::bind(&path).listen();
         assert!(path.exists());
-    }
+    })
+
+    iotest!(fn close_write() {
+        smalltest(proc(mut server) {
+            // the client's data is followed by EOF, and we can still reply
+            let mut buf = [0];
+            assert_eq!(server.read(buf), Some(1));
+            assert_eq!(buf[0], 99);
+            assert!(server.read(buf).is_none());
+            server.write([100]);
+        }, proc(mut client) {
+            client.write([99]);
+            client.close_write();
+            let mut buf = [0];
+            assert_eq!(client.read(buf), Some(1));
+            assert_eq!(buf[0], 100);
+        })
+    })
+
+    iotest!(fn close_read() {
+        smalltest(proc(mut server) {
+            server.close_read();
+            let mut buf = [0];
+            assert!(server.read(buf).is_none());
+            server.write([100]);
+        }, proc(mut client) {
+            let mut buf = [0];
+            assert_eq!(client.read(buf), Some(1));
+            assert_eq!(buf[0], 100);
+        })
+    })
 }
@@ -51,6 +51,33 @@ impl PipeStream {
     pub fn new(inner: ~RtioPipe) -> PipeStream {
         PipeStream { obj: inner }
     }
+
+    /// Closes the write half of this pipe, signaling to the other end that no
+    /// more data will be sent.
+    ///
+    /// # Failure
+    ///
+    /// This will raise on the `io_error` condition if the pipe can't be
+    /// half-closed, which is the case for anonymous pipes.
+    pub fn close_write(&mut self) {
+        match self.obj.close_write() {
+            Ok(()) => {}
+            Err(ioerr) => io_error::cond.raise(ioerr),
+        }
+    }
+
+    /// Closes the read half of this pipe.
+    ///
+    /// # Failure
+    ///
+    /// This will raise on the `io_error` condition if the pipe can't be
+    /// half-closed, which is the case for anonymous pipes.
+    pub fn close_read(&mut self) {
+        match self.obj.close_read() {
+            Ok(()) => {}
+            Err(ioerr) => io_error::cond.raise(ioerr),
+        }
+    }
 }
 
 impl Reader for PipeStream {
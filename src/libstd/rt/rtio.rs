@@ -204,6 +204,7 @@ pub trait RtioTcpAcceptor : RtioSocket {
     fn accept(&mut self) -> Result<~RtioTcpStream, IoError>;
     fn accept_simultaneously(&mut self) -> Result<(), IoError>;
     fn dont_accept_simultaneously(&mut self) -> Result<(), IoError>;
+    fn set_timeout(&mut self, timeout_ms: Option<u64>) -> Result<(), IoError>;
 }
 
 pub trait RtioTcpStream : RtioSocket {
@@ -214,6 +215,12 @@ pub trait RtioTcpStream : RtioSocket {
     fn nodelay(&mut self) -> Result<(), IoError>;
     fn keepalive(&mut self, delay_in_seconds: uint) -> Result<(), IoError>;
     fn letdie(&mut self) -> Result<(), IoError>;
+    fn set_read_timeout(&mut self, timeout_ms: Option<u64>)
+        -> Result<(), IoError>;
+    fn set_write_timeout(&mut self, timeout_ms: Option<u64>)
+        -> Result<(), IoError>;
+    fn close_write(&mut self) -> Result<(), IoError>;
+    fn close_read(&mut self) -> Result<(), IoError>;
 }
 
 pub trait RtioSocket {
@@ -235,6 +242,11 @@ pub trait RtioUdpSocket : RtioSocket {
 
     fn hear_broadcasts(&mut self) -> Result<(), IoError>;
     fn ignore_broadcasts(&mut self) -> Result<(), IoError>;
+
+    fn set_read_timeout(&mut self, timeout_ms: Option<u64>)
+        -> Result<(), IoError>;
+    fn set_write_timeout(&mut self, timeout_ms: Option<u64>)
+        -> Result<(), IoError>;
 }
 
 pub trait RtioTimer {
@@ -264,6 +276,8 @@ pub trait RtioProcess {
 pub trait RtioPipe {
     fn read(&mut self, buf: &mut [u8]) -> Result<uint, IoError>;
     fn write(&mut self, buf: &[u8]) -> Result<(), IoError>;
+    fn close_write(&mut self) -> Result<(), IoError>;
+    fn close_read(&mut self) -> Result<(), IoError>;
 }
 
 pub trait RtioUnixListener {
@@ -11,20 +11,29 @@
 use codemap::{Pos, Span};
 use codemap;
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::io;
 use std::io::stdio::StdWriter;
 use std::local_data;
+use extra::json;
 use extra::term;
+use extra::treemap::TreeMap;
 
 static BUG_REPORT_URL: &'static str =
     "http://static.rust-lang.org/doc/master/complement-bugreport.html";
 
 pub trait Emitter {
+    /// Reports a diagnostic, optionally located at a span of the source code.
+    /// `code` is the diagnostic's stable error code, if it has one.
     fn emit(&self,
             cmsp: Option<(&codemap::CodeMap, Span)>,
             msg: &str,
+            code: Option<&str>,
             lvl: Level);
+
+    /// Writes out any diagnostics which have been held back. This is called
+    /// once compilation has finished.
+    fn flush(&self) {}
 }
 
 /// This structure is used to signify that a task has failed with a fatal error
@@ -49,6 +58,10 @@ impl SpanHandler {
         self.handler.emit(Some((&*self.cm, sp)), msg, Error);
         self.handler.bump_err_count();
     }
+    pub fn span_err_with_code(@self, sp: Span, msg: &str, code: &str) {
+        self.handler.emit_with_code(Some((&*self.cm, sp)), msg, code, Error);
+        self.handler.bump_err_count();
+    }
     pub fn span_warn(@self, sp: Span, msg: &str) {
         self.handler.emit(Some((&*self.cm, sp)), msg, Warning);
     }
@@ -76,11 +89,11 @@ pub struct Handler {
 
 impl Handler {
     pub fn fatal(@self, msg: &str) -> ! {
-        self.emit.emit(None, msg, Fatal);
+        self.emit.emit(None, msg, None, Fatal);
         fail!(FatalError);
     }
     pub fn err(@self, msg: &str) {
-        self.emit.emit(None, msg, Error);
+        self.emit.emit(None, msg, None, Error);
         self.bump_err_count();
     }
     pub fn bump_err_count(@self) {
@@ -105,10 +118,10 @@ impl Handler {
         self.fatal(s);
     }
     pub fn warn(@self, msg: &str) {
-        self.emit.emit(None, msg, Warning);
+        self.emit.emit(None, msg, None, Warning);
     }
     pub fn note(@self, msg: &str) {
-        self.emit.emit(None, msg, Note);
+        self.emit.emit(None, msg, None, Note);
     }
     pub fn bug(@self, msg: &str) -> ! {
         self.fatal(ice_msg(msg));
@@ -120,7 +133,14 @@ impl Handler {
             cmsp: Option<(&codemap::CodeMap, Span)>,
             msg: &str,
             lvl: Level) {
-        self.emit.emit(cmsp, msg, lvl);
+        self.emit.emit(cmsp, msg, None, lvl);
+    }
+    pub fn emit_with_code(@self,
+                          cmsp: Option<(&codemap::CodeMap, Span)>,
+                          msg: &str,
+                          code: &str,
+                          lvl: Level) {
+        self.emit.emit(cmsp, msg, Some(code), lvl);
     }
 }
 
@@ -219,7 +239,7 @@ fn print_maybe_styled(msg: &str, color: term::attr::Attr) {
     }
 }
 
-fn print_diagnostic(topic: &str, lvl: Level, msg: &str) {
+fn print_diagnostic(topic: &str, lvl: Level, msg: &str, code: Option<&str>) {
     let mut stderr = io::stderr();
 
     if !topic.is_empty() {
@@ -228,7 +248,15 @@ fn print_diagnostic(topic: &str, lvl: Level, msg: &str) {
 
     print_maybe_styled(format!("{}: ", lvl.to_str()),
                        term::attr::ForegroundColor(lvl.color()));
-    print_maybe_styled(format!("{}\n", msg), term::attr::Bold);
+    print_maybe_styled(format!("{}", msg), term::attr::Bold);
+    match code {
+        Some(code) => {
+            let style = term::attr::ForegroundColor(term::color::BRIGHT_MAGENTA);
+            print_maybe_styled(format!(" [{}]", code), style);
+        }
+        None => {}
+    }
+    write!(&mut stderr as &mut io::Writer, "\n");
 }
 
 pub struct DefaultEmitter;
@@ -237,17 +265,18 @@ impl Emitter for DefaultEmitter {
     fn emit(&self,
             cmsp: Option<(&codemap::CodeMap, Span)>,
             msg: &str,
+            code: Option<&str>,
             lvl: Level) {
         match cmsp {
             Some((cm, sp)) => {
                 let sp = cm.adjust_span(sp);
                 let ss = cm.span_to_str(sp);
                 let lines = cm.span_to_lines(sp);
-                print_diagnostic(ss, lvl, msg);
+                print_diagnostic(ss, lvl, msg, code);
                 highlight_lines(cm, sp, lvl, lines);
                 print_macro_backtrace(cm, sp);
             }
-            None => print_diagnostic("", lvl, msg),
+            None => print_diagnostic("", lvl, msg, code),
         }
     }
 }
@@ -327,13 +356,151 @@ fn print_macro_backtrace(cm: &codemap::CodeMap, sp: Span) {
             codemap::MacroBang => ("", "!")
         };
         print_diagnostic(ss, Note,
-                         format!("in expansion of {}{}{}", pre, ei.callee.name, post));
+                         format!("in expansion of {}{}{}", pre, ei.callee.name, post),
+                         None);
         let ss = cm.span_to_str(ei.call_site);
-        print_diagnostic(ss, Note, "expansion site");
+        print_diagnostic(ss, Note, "expansion site", None);
         print_macro_backtrace(cm, ei.call_site);
     }
 }
 
+/// An emitter which writes each diagnostic to stderr as a single line of JSON,
+/// for consumption by editors and other tools. Notes which follow a diagnostic
+/// are attached to it rather than written on their own, so each diagnostic is
+/// held back until the next one arrives or the emitter is flushed or dropped.
+pub struct JsonEmitter {
+    priv pending: RefCell<Option<(json::Object, ~[json::Json])>>,
+}
+
+impl JsonEmitter {
+    pub fn new() -> JsonEmitter {
+        JsonEmitter { pending: RefCell::new(None) }
+    }
+}
+
+impl Emitter for JsonEmitter {
+    fn emit(&self,
+            cmsp: Option<(&codemap::CodeMap, Span)>,
+            msg: &str,
+            code: Option<&str>,
+            lvl: Level) {
+        let diagnostic = json_diagnostic(cmsp, msg, code, lvl);
+        let mut pending = self.pending.borrow_mut();
+        if lvl == Note {
+            match *pending.get() {
+                Some((_, ref mut notes)) => {
+                    notes.push(json::Object(~diagnostic));
+                    return
+                }
+                None => {}
+            }
+        }
+
+        write_json_diagnostic(pending.get().take());
+        match lvl {
+            // nothing is reported after a fatal error, so don't hold it back
+            Fatal => write_json_diagnostic(Some((diagnostic, ~[]))),
+            _ => *pending.get() = Some((diagnostic, ~[])),
+        }
+    }
+
+    fn flush(&self) {
+        let mut pending = self.pending.borrow_mut();
+        write_json_diagnostic(pending.get().take());
+    }
+}
+
+// A task failing after an error has been emitted never gets to flush the
+// emitter, so the held back diagnostic is written when it is dropped.
+impl Drop for JsonEmitter {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+fn write_json_diagnostic(diagnostic: Option<(json::Object, ~[json::Json])>) {
+    match diagnostic {
+        Some((mut obj, notes)) => {
+            obj.insert(~"notes", json::List(notes));
+            let s = json::Object(~obj).to_str();
+            io::stderr().write(format!("{}\n", s).as_bytes());
+        }
+        None => {}
+    }
+}
+
+fn json_diagnostic(cmsp: Option<(&codemap::CodeMap, Span)>,
+                   msg: &str,
+                   code: Option<&str>,
+                   lvl: Level) -> json::Object {
+    let (span, expansion) = match cmsp {
+        Some((cm, sp)) => {
+            let sp = cm.adjust_span(sp);
+            (json_span(cm, sp), json_macro_backtrace(cm, sp))
+        }
+        None => (json::Null, ~[]),
+    };
+
+    let mut obj = TreeMap::new();
+    obj.insert(~"level", json::String(lvl.to_str()));
+    obj.insert(~"message", json::String(msg.to_owned()));
+    obj.insert(~"code", code.map_or(json::Null, |c| json::String(c.to_owned())));
+    obj.insert(~"span", span);
+    obj.insert(~"expansion", json::List(expansion));
+    obj
+}
+
+/// The JSON representation of a span, as used by `JsonEmitter`
+pub fn json_span(cm: &codemap::CodeMap, sp: Span) -> json::Json {
+    {
+        let files = cm.files.borrow();
+        if files.get().len() == 0 && sp == codemap::DUMMY_SP {
+            return json::Null;
+        }
+    }
+
+    let lo = cm.lookup_char_pos(sp.lo);
+    let hi = cm.lookup_char_pos(sp.hi);
+    let num = |n: uint| json::Number(n as f64);
+
+    // byte offsets are relative to the start of the file, and lines and
+    // columns are 1-based as they are in human-readable diagnostics
+    let mut obj = TreeMap::new();
+    obj.insert(~"file", json::String(lo.file.name.to_owned()));
+    obj.insert(~"byte_start", num((sp.lo - lo.file.start_pos).to_uint()));
+    obj.insert(~"byte_end", num((sp.hi - hi.file.start_pos).to_uint()));
+    obj.insert(~"line_start", num(lo.line));
+    obj.insert(~"line_end", num(hi.line));
+    obj.insert(~"column_start", num(lo.col.to_uint() + 1));
+    obj.insert(~"column_end", num(hi.col.to_uint() + 1));
+    json::Object(~obj)
+}
+
+// The macro backtrace of a span, innermost expansion first
+fn json_macro_backtrace(cm: &codemap::CodeMap, sp: Span) -> ~[json::Json] {
+    let mut backtrace = ~[];
+    let mut sp = sp;
+    loop {
+        let ei = match sp.expn_info {
+            Some(ei) => ei,
+            None => return backtrace,
+        };
+        let (pre, post) = match ei.callee.format {
+            codemap::MacroAttribute => ("#[", "]"),
+            codemap::MacroBang => ("", "!")
+        };
+        let def_site = ei.callee.span.map_or(json::Null, |span| json_span(cm, span));
+
+        let mut obj = TreeMap::new();
+        obj.insert(~"macro", json::String(format!("{}{}{}", pre, ei.callee.name, post)));
+        obj.insert(~"def_site_span", def_site);
+        obj.insert(~"call_site_span", json_span(cm, ei.call_site));
+        backtrace.push(json::Object(~obj));
+
+        sp = ei.call_site;
+    }
+}
+
 pub fn expect<T:Clone>(diag: @SpanHandler, opt: Option<T>, msg: || -> ~str)
               -> T {
     match opt {
@@ -15,15 +15,16 @@ use diagnostic;
 use visit;
 use visit::Visitor;
 
-struct MacroRegistrarContext {
+struct RegistrarContext {
+    attr_name: &'static str,
     registrars: ~[(ast::NodeId, Span)],
 }
 
-impl Visitor<()> for MacroRegistrarContext {
+impl Visitor<()> for RegistrarContext {
     fn visit_item(&mut self, item: &ast::Item, _: ()) {
         match item.node {
             ast::ItemFn(..) => {
-                if attr::contains_name(item.attrs, "macro_registrar") {
+                if attr::contains_name(item.attrs, self.attr_name) {
                     self.registrars.push((item.id, item.span));
                 }
             }
@@ -36,7 +37,19 @@ impl Visitor<()> for MacroRegistrarContext {
 
 pub fn find_macro_registrar(diagnostic: @diagnostic::SpanHandler,
                             crate: &ast::Crate) -> Option<ast::DefId> {
-    let mut ctx = MacroRegistrarContext { registrars: ~[] };
+    find_registrar(diagnostic, crate, "macro_registrar", "macro")
+}
+
+pub fn find_lint_registrar(diagnostic: @diagnostic::SpanHandler,
+                           crate: &ast::Crate) -> Option<ast::DefId> {
+    find_registrar(diagnostic, crate, "lint_registrar", "lint")
+}
+
+fn find_registrar(diagnostic: @diagnostic::SpanHandler,
+                  crate: &ast::Crate,
+                  attr_name: &'static str,
+                  kind: &str) -> Option<ast::DefId> {
+    let mut ctx = RegistrarContext { attr_name: attr_name, registrars: ~[] };
     visit::walk_crate(&mut ctx, crate, ());
 
     match ctx.registrars.len() {
@@ -49,7 +62,8 @@ pub fn find_macro_registrar(diagnostic: @diagnostic::SpanHandler,
             })
         },
         _ => {
-            diagnostic.handler().err("Multiple macro registration functions found");
+            diagnostic.handler().err(format!("Multiple {} registration functions found",
+                                             kind));
             for &(_, span) in ctx.registrars.iter() {
                 diagnostic.span_note(span, "one is here");
             }
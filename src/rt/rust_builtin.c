@@ -18,6 +18,9 @@
 #include <assert.h>
 #include <stdlib.h>
 
+#define MINIZ_HEADER_FILE_ONLY
+#include "miniz.c"
+
 #if !defined(__WIN32__)
 #include <sys/time.h>
 #include <sys/types.h>
@@ -457,6 +460,29 @@ rust_pthread_cond_t_size() { return sizeof(pthread_cond_t); }
 
 #endif
 
+size_t
+rust_tdefl_compressor_size() { return sizeof(tdefl_compressor); }
+size_t
+rust_tinfl_decompressor_size() { return sizeof(tinfl_decompressor); }
+
+// When tinfl isn't parsing a zlib stream it may read a few bytes past the end
+// of the deflate data into its bit buffer, and it doesn't give them back.
+// This takes those whole bytes out of the bit buffer, returning how many
+// there were (at most sizeof(tinfl_bit_buf_t)).
+size_t
+rust_tinfl_take_unread(tinfl_decompressor *r, uint8_t *buf) {
+    mz_uint32 partial = r->m_num_bits & 7;
+    size_t n = r->m_num_bits >> 3;
+    tinfl_bit_buf_t bits = r->m_bit_buf >> partial;
+    size_t i;
+    for (i = 0; i < n; i++) {
+        buf[i] = (uint8_t)(bits >> (8 * i));
+    }
+    r->m_num_bits = partial;
+    r->m_bit_buf &= ((tinfl_bit_buf_t)1 << partial) - 1;
+    return n;
+}
+
 //
 // Local Variables:
 // mode: C++
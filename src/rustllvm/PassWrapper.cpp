@@ -15,6 +15,7 @@
 #include "llvm/Support/CBindingWrapping.h"
 #include "llvm/Target/TargetLibraryInfo.h"
 #include "llvm/Transforms/IPO/PassManagerBuilder.h"
+#include "llvm/Transforms/Utils/Cloning.h"
 
 #include "llvm-c/Transforms/PassManagerBuilder.h"
 
@@ -189,6 +190,12 @@ LLVMRustPrintModule(LLVMPassManagerRef PMR,
   PM->run(*unwrap(M));
 }
 
+// The C API only gained LLVMCloneModule in later versions of LLVM
+extern "C" LLVMModuleRef
+LLVMRustCloneModule(LLVMModuleRef M) {
+  return wrap(CloneModule(unwrap(M)));
+}
+
 extern "C" void
 LLVMRustPrintPasses() {
     LLVMInitializePasses();
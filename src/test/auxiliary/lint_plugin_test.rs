@@ -0,0 +1,38 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// force-host
+
+#[feature(lint_registrar)];
+
+extern mod rustc;
+extern mod syntax;
+
+use rustc::middle::lint::{Context, Lint, LintPass, LintRegistry, warn};
+use syntax::ast;
+
+struct Pass {
+    lint: Lint,
+}
+
+impl LintPass for Pass {
+    fn check_item(&mut self, cx: &Context, it: &ast::Item) {
+        let name = cx.tcx().sess.str_of(it.ident);
+        if name.as_slice() == "lintme" {
+            cx.span_lint(self.lint, it.span, "item is named 'lintme'");
+        }
+    }
+}
+
+#[lint_registrar]
+pub fn registrar(reg: &mut LintRegistry) {
+    let lint = reg.register_lint("test_lint", warn, "warn about items named 'lintme'");
+    reg.register_pass(~Pass { lint: lint } as ~LintPass);
+}
@@ -11,6 +11,7 @@
 extern mod extra;
 
 use extra::time;
+use extra::btree::BTreeMap;
 use extra::treemap::TreeMap;
 use std::hashmap::{HashMap, HashSet};
 use std::os;
@@ -134,6 +135,25 @@ fn main() {
         vector(&mut map, n_keys, rand);
     }
 
+    // FIXME: #9970
+    println!("{}", "\nBTreeMap:");
+
+    {
+        let mut map: BTreeMap<uint,uint> = BTreeMap::new();
+        ascending(&mut map, n_keys);
+    }
+
+    {
+        let mut map: BTreeMap<uint,uint> = BTreeMap::new();
+        descending(&mut map, n_keys);
+    }
+
+    {
+        println!(" Random integers:");
+        let mut map: BTreeMap<uint,uint> = BTreeMap::new();
+        vector(&mut map, n_keys, rand);
+    }
+
     // FIXME: #9970
     println!("{}", "\nHashMap:");
 
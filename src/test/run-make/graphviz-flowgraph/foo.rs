@@ -0,0 +1,17 @@
+#[crate_type = "lib"];
+
+pub mod foo {
+    pub fn lend() -> uint {
+        let v = ~[1u, 2, 3];
+        let mut total = 0;
+        {
+            let first = &v[0];
+            total += *first;
+        }
+        while total < 10 {
+            total += v.len();
+        }
+        let w = v;
+        total + w.len()
+    }
+}
@@ -0,0 +1,8 @@
+macro_rules! bad_add(
+    ($e:expr) => ($e + "a")
+)
+
+fn main() {
+    let x: int = "not an int";
+    bad_add!(1);
+}
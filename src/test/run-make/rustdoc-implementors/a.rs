@@ -0,0 +1,12 @@
+#[crate_id = "a"];
+#[crate_type = "lib"];
+
+pub trait Frob {
+    fn frob(&self);
+}
+
+pub struct Local;
+
+impl Frob for Local {
+    fn frob(&self) {}
+}
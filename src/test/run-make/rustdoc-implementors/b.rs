@@ -0,0 +1,10 @@
+#[crate_id = "b"];
+#[crate_type = "lib"];
+
+extern mod a;
+
+pub struct Remote;
+
+impl a::Frob for Remote {
+    fn frob(&self) {}
+}
@@ -1,4 +1,5 @@
 #[crate_id = "foo#0.1"];
+#[feature(macro_rules)];
 
 //! Very docs
 
@@ -13,3 +14,10 @@ pub mod bar {
     /// *wow*
     pub trait Doge { }
 }
+
+/// Such macro
+#[macro_export]
+macro_rules! doge (
+    ($e:expr) => ($e);
+    ($e:expr, $($rest:expr),+) => ($e + doge!($($rest),+));
+)
@@ -9,9 +9,19 @@ for dir in $dirs; do if [ ! -d $1/$dir ]; then
 	exit 1
 fi done
 
-files="doc/foo/index.html doc/foo/bar/index.html doc/foo/bar/baz/fn.baz.html doc/foo/bar/trait.Doge.html doc/src/foo/foo.rs.html"
+files="doc/foo/index.html doc/foo/bar/index.html doc/foo/bar/baz/fn.baz.html doc/foo/bar/trait.Doge.html doc/foo/macro.doge.html doc/src/foo/foo.rs.html"
 
 for file in $files; do if [ ! -f $1/$file ]; then
 	echo "$1/$file is not a file!"
 	exit 1
 fi done
+
+if ! grep -q "macro_rules! doge" $1/doc/foo/macro.doge.html; then
+	echo "macro.doge.html doesn't show the macro's rules!"
+	exit 1
+fi
+
+if ! grep -q 'ty:"macro",name:"doge"' $1/doc/foo/search-index.js; then
+	echo "the search index doesn't include the macro!"
+	exit 1
+fi
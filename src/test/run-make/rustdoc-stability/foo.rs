@@ -0,0 +1,18 @@
+#[crate_id = "foo"];
+#[crate_type = "lib"];
+
+#[deprecated = "use `good` instead"]
+pub fn old() {}
+
+#[stable]
+pub fn good() {}
+
+pub fn plain() {}
+
+pub mod inner {
+    #[experimental]
+    pub struct Thing {
+        #[stable]
+        field: int,
+    }
+}
@@ -0,0 +1,18 @@
+#[crate_id = "foo"];
+#[crate_type = "lib"];
+
+pub struct Point {
+    x: int,
+    y: int,
+}
+
+impl Point {
+    pub fn norm(&self) -> int {
+        self.x * self.x + self.y * self.y
+    }
+}
+
+pub fn origin_distance(p: Point) -> int {
+    let squared = p.norm();
+    squared
+}